@@ -0,0 +1,150 @@
+use crate::errors::Error;
+use crate::graphics::{Image, LDRColor, Texture2};
+use crate::targets;
+
+use futures::{future, Future};
+use std::sync::{Arc, Mutex};
+
+/// The MIME type of UTF-8 encoded plain text.
+pub const TEXT: &str = "text/plain;charset=utf-8";
+/// The MIME type of PNG encoded images.
+pub const PNG: &str = "image/png";
+
+/// A future resolving to the result of a clipboard operation.
+pub type ClipboardFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// A clipboard holding content in one or more representations identified by MIME type.
+pub trait Clipboard: Sync + Send {
+    /// Reads the representation of the provided MIME type, resolving to `None` if the clipboard holds no such representation.
+    fn read(&self, mime: &str) -> ClipboardFuture<Option<Vec<u8>>>;
+    /// Replaces the contents of the clipboard with the provided representations, given as pairs of MIME type and data.
+    fn write(&self, data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()>;
+    /// Reads an image, resolving to `None` if the clipboard holds no image.
+    fn read_image(&self) -> ClipboardFuture<Option<Image<LDRColor, Texture2>>>;
+    /// Replaces the contents of the clipboard with an image.
+    fn write_image(&self, image: Image<LDRColor, Texture2>) -> ClipboardFuture<()>;
+    /// Reads plain text, resolving to `None` if the clipboard holds no text.
+    fn read_text(&self) -> ClipboardFuture<Option<String>> {
+        Box::new(
+            self.read(TEXT)
+                .map(|data| data.map(|data| String::from_utf8_lossy(&data).into_owned())),
+        )
+    }
+    /// Replaces the contents of the clipboard with plain text.
+    fn write_text(&self, text: String) -> ClipboardFuture<()> {
+        self.write(vec![(TEXT.to_owned(), text.into_bytes())])
+    }
+    #[doc(hidden)]
+    fn box_clone(&self) -> Box<dyn Clipboard>;
+}
+
+impl Clone for Box<dyn Clipboard> {
+    fn clone(&self) -> Box<dyn Clipboard> {
+        self.box_clone()
+    }
+}
+
+/// Returns the system clipboard. On Linux this is the X11 CLIPBOARD selection, which Wayland sessions only provide through XWayland.
+pub fn new() -> Box<dyn Clipboard> {
+    #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+    return targets::web::clipboard::new();
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    return targets::native::clipboard::new();
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    data: Vec<(String, Vec<u8>)>,
+    image: Option<Image<LDRColor, Texture2>>,
+}
+
+/// A clipboard held in memory and shared between clones, independent of the system clipboard. Images are stored as written and are not available in encoded form.
+#[derive(Clone, Debug, Default)]
+pub struct Memory {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+impl Memory {
+    /// Creates an empty in-memory clipboard.
+    pub fn new() -> Memory {
+        Memory::default()
+    }
+}
+
+impl Clipboard for Memory {
+    fn read(&self, mime: &str) -> ClipboardFuture<Option<Vec<u8>>> {
+        let state = self.state.lock().unwrap();
+        Box::new(future::ok(
+            state
+                .data
+                .iter()
+                .find(|(data_mime, _)| data_mime == mime)
+                .map(|(_, data)| data.clone()),
+        ))
+    }
+    fn write(&self, data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()> {
+        let mut state = self.state.lock().unwrap();
+        state.data = data;
+        state.image = None;
+        Box::new(future::ok(()))
+    }
+    fn read_image(&self) -> ClipboardFuture<Option<Image<LDRColor, Texture2>>> {
+        Box::new(future::ok(self.state.lock().unwrap().image.clone()))
+    }
+    fn write_image(&self, image: Image<LDRColor, Texture2>) -> ClipboardFuture<()> {
+        let mut state = self.state.lock().unwrap();
+        state.data = vec![];
+        state.image = Some(image);
+        Box::new(future::ok(()))
+    }
+    fn box_clone(&self) -> Box<dyn Clipboard> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_text() {
+        let clipboard = Memory::new();
+        assert_eq!(clipboard.read_text().wait().unwrap(), None);
+        clipboard.write_text("copied".to_owned()).wait().unwrap();
+        let shared: Box<dyn Clipboard> = Box::new(clipboard.clone());
+        assert_eq!(
+            shared.read_text().wait().unwrap(),
+            Some("copied".to_owned())
+        );
+        assert_eq!(shared.read(TEXT).wait().unwrap(), Some(b"copied".to_vec()));
+    }
+
+    #[test]
+    fn memory_replaces_contents() {
+        let clipboard = Memory::new();
+        clipboard
+            .write(vec![
+                ("application/x-vessels".to_owned(), vec![1, 2, 3]),
+                (TEXT.to_owned(), b"fallback".to_vec()),
+            ])
+            .wait()
+            .unwrap();
+        assert_eq!(
+            clipboard.read("application/x-vessels").wait().unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        let image = Image {
+            pixels: vec![LDRColor::rgba(255, 0, 0, 255)],
+            format: Texture2 {
+                width: 1,
+                height: 1,
+            },
+            color_profile: None,
+        };
+        clipboard.write_image(image).wait().unwrap();
+        assert_eq!(clipboard.read_text().wait().unwrap(), None);
+        let image = clipboard.read_image().wait().unwrap().unwrap();
+        assert_eq!(image.pixels, vec![LDRColor::rgba(255, 0, 0, 255)]);
+    }
+}
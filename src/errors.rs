@@ -15,6 +15,26 @@ impl Error {
     pub(crate) fn color_stop() -> Error {
         Error::from(ErrorKind::LDRColorStopOffsetError)
     }
+
+    pub(crate) fn color_parse() -> Error {
+        Error::from(ErrorKind::ColorParseError)
+    }
+
+    pub(crate) fn color_profile() -> Error {
+        Error::from(ErrorKind::ColorProfileError)
+    }
+
+    pub(crate) fn unsupported() -> Error {
+        Error::from(ErrorKind::UnsupportedError)
+    }
+
+    pub(crate) fn image_decode() -> Error {
+        Error::from(ErrorKind::ImageDecodeError)
+    }
+
+    pub(crate) fn clipboard() -> Error {
+        Error::from(ErrorKind::ClipboardError)
+    }
 }
 
 impl Fail for Error {
@@ -36,6 +56,11 @@ impl fmt::Display for Error {
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ErrorKind {
     LDRColorStopOffsetError,
+    ColorParseError,
+    ColorProfileError,
+    UnsupportedError,
+    ImageDecodeError,
+    ClipboardError,
 
     #[doc(hidden)]
     __Nonexhaustive,
@@ -47,6 +72,11 @@ impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             ErrorKind::LDRColorStopOffsetError => write!(f, "LDRColorstop offset out of bounds"),
+            ErrorKind::ColorParseError => write!(f, "Invalid CSS color"),
+            ErrorKind::ColorProfileError => write!(f, "Invalid or unreadable ICC profile"),
+            ErrorKind::UnsupportedError => write!(f, "Operation not supported on this target"),
+            ErrorKind::ImageDecodeError => write!(f, "Invalid or unreadable image data"),
+            ErrorKind::ClipboardError => write!(f, "Clipboard access failed"),
             ErrorKind::__Nonexhaustive => panic!("Invalid Error!"),
         }
     }
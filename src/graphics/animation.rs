@@ -0,0 +1,681 @@
+use crate::graphics::{
+    canvas::{Object, Rasterizable, Ticker},
+    color::MixSpace,
+    LDRColor, Transform2, Vector2,
+};
+
+use futures::{task::AtomicTask, Async, Future, Poll};
+
+use std::{
+    f64::consts::PI,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+};
+
+/// A type whose values can be interpolated by an animation.
+pub trait Interpolate: Clone + Send + Sync + 'static {
+    /// Returns the value the provided fraction of the way from this value to another.
+    fn interpolate(&self, other: &Self, progress: f64) -> Self;
+}
+
+fn lerp(from: f64, to: f64, progress: f64) -> f64 {
+    from + (to - from) * progress
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &f64, progress: f64) -> f64 {
+        lerp(*self, *other, progress)
+    }
+}
+
+impl Interpolate for Vector2 {
+    fn interpolate(&self, other: &Vector2, progress: f64) -> Vector2 {
+        (
+            lerp(self.x, other.x, progress),
+            lerp(self.y, other.y, progress),
+        )
+            .into()
+    }
+}
+
+impl Interpolate for Transform2 {
+    fn interpolate(&self, other: &Transform2, progress: f64) -> Transform2 {
+        Transform2 {
+            position: self.position.interpolate(&other.position, progress),
+            scale: self.scale.interpolate(&other.scale, progress),
+            rotation: lerp(self.rotation, other.rotation, progress),
+        }
+    }
+}
+
+impl Interpolate for LDRColor {
+    fn interpolate(&self, other: &LDRColor, progress: f64) -> LDRColor {
+        self.mix(*other, progress, MixSpace::Srgb)
+    }
+}
+
+/// An opacity between 0 (transparent) and 1 (opaque).
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Opacity(pub f64);
+
+impl Interpolate for Opacity {
+    fn interpolate(&self, other: &Opacity, progress: f64) -> Opacity {
+        // Overshooting easings must not produce out of range opacities.
+        Opacity(lerp(self.0, other.0, progress).clamp(0., 1.))
+    }
+}
+
+/// The point in each interval at which a [Easing::Steps] easing jumps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StepPosition {
+    /// Jumps at the start of each interval, like CSS `jump-start`.
+    Start,
+    /// Jumps at the end of each interval, like CSS `jump-end`.
+    End,
+}
+
+/// A curve mapping linear progress to eased progress.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    /// Constant rate of change.
+    Linear,
+    /// A CSS-style cubic bézier curve through (0, 0), the two provided control points and (1, 1).
+    CubicBezier(f64, f64, f64, f64),
+    /// A damped spring with unit mass released from rest, settling at the end of the animation.
+    Spring {
+        /// The spring constant.
+        stiffness: f64,
+        /// The damping coefficient.
+        damping: f64,
+    },
+    /// Oscillates with growing amplitude before snapping to the target.
+    ElasticIn,
+    /// Overshoots and oscillates with decaying amplitude around the target.
+    ElasticOut,
+    /// Elastic at both ends.
+    ElasticInOut,
+    /// Jumps between the provided number of discrete levels.
+    Steps(u32, StepPosition),
+}
+
+fn sample_bezier(a: f64, b: f64, t: f64) -> f64 {
+    ((1. - 3. * b + 3. * a) * t + (3. * b - 6. * a)) * t * t + 3. * a * t
+}
+
+fn sample_bezier_derivative(a: f64, b: f64, t: f64) -> f64 {
+    3. * (1. - 3. * b + 3. * a) * t * t + 2. * (3. * b - 6. * a) * t + 3. * a
+}
+
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, x: f64) -> f64 {
+    // Newton's method converges quickly for most curves, with bisection as a fallback.
+    let mut t = x;
+    for _ in 0..8 {
+        let error = sample_bezier(x1, x2, t) - x;
+        if error.abs() < 1e-7 {
+            return sample_bezier(y1, y2, t);
+        }
+        let derivative = sample_bezier_derivative(x1, x2, t);
+        if derivative.abs() < 1e-6 {
+            break;
+        }
+        t -= error / derivative;
+    }
+    let (mut low, mut high) = (0., 1.);
+    t = x;
+    while high - low > 1e-7 {
+        if sample_bezier(x1, x2, t) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.;
+    }
+    sample_bezier(y1, y2, t)
+}
+
+fn spring(stiffness: f64, damping: f64, progress: f64) -> f64 {
+    let omega = stiffness.max(f64::EPSILON).sqrt();
+    let zeta = damping.max(0.) / (2. * omega);
+    // Progress is mapped onto the time taken for the displacement envelope to decay to 0.1%.
+    let settle_rate = if zeta < 1. {
+        zeta * omega
+    } else {
+        omega * (zeta - (zeta * zeta - 1.).sqrt())
+    };
+    let t = progress * 1000f64.ln() / settle_rate.max(f64::EPSILON);
+    if zeta < 1. {
+        let damped_omega = omega * (1. - zeta * zeta).sqrt();
+        1. - (-zeta * omega * t).exp()
+            * ((damped_omega * t).cos() + zeta * omega / damped_omega * (damped_omega * t).sin())
+    } else if (zeta - 1.).abs() < 1e-9 {
+        1. - (-omega * t).exp() * (1. + omega * t)
+    } else {
+        let root = (zeta * zeta - 1.).sqrt();
+        let r1 = -omega * (zeta - root);
+        let r2 = -omega * (zeta + root);
+        1. - (r2 * (r1 * t).exp() - r1 * (r2 * t).exp()) / (r2 - r1)
+    }
+}
+
+impl Easing {
+    /// The CSS `ease` curve.
+    pub const EASE: Easing = Easing::CubicBezier(0.25, 0.1, 0.25, 1.);
+    /// The CSS `ease-in` curve.
+    pub const EASE_IN: Easing = Easing::CubicBezier(0.42, 0., 1., 1.);
+    /// The CSS `ease-out` curve.
+    pub const EASE_OUT: Easing = Easing::CubicBezier(0., 0., 0.58, 1.);
+    /// The CSS `ease-in-out` curve.
+    pub const EASE_IN_OUT: Easing = Easing::CubicBezier(0.42, 0., 0.58, 1.);
+
+    /// Returns the eased progress for a linear progress between 0 and 1.
+    pub fn apply(&self, progress: f64) -> f64 {
+        let progress = progress.clamp(0., 1.);
+        let elastic = 2. * PI / 3.;
+        match *self {
+            Easing::Steps(count, position) => {
+                let count = f64::from(count.max(1));
+                let step = (progress * count).floor();
+                match position {
+                    StepPosition::Start => (step + 1.).min(count) / count,
+                    StepPosition::End => step / count,
+                }
+            }
+            // The curves below only approach their endpoints asymptotically.
+            _ if progress == 0. || progress == 1. => progress,
+            Easing::Linear => progress,
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                cubic_bezier(x1.clamp(0., 1.), y1, x2.clamp(0., 1.), y2, progress)
+            }
+            Easing::Spring { stiffness, damping } => spring(stiffness, damping, progress),
+            Easing::ElasticIn => {
+                -(2f64.powf(10. * progress - 10.)) * ((progress * 10. - 10.75) * elastic).sin()
+            }
+            Easing::ElasticOut => {
+                2f64.powf(-10. * progress) * ((progress * 10. - 0.75) * elastic).sin() + 1.
+            }
+            Easing::ElasticInOut => {
+                let elastic = 2. * PI / 4.5;
+                if progress < 0.5 {
+                    -(2f64.powf(20. * progress - 10.) * ((20. * progress - 11.125) * elastic).sin())
+                        / 2.
+                } else {
+                    2f64.powf(-20. * progress + 10.) * ((20. * progress - 11.125) * elastic).sin()
+                        / 2.
+                        + 1.
+                }
+            }
+        }
+    }
+}
+
+/// The number of times a [Timeline] plays.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Iterations {
+    /// Plays the provided number of times. A count of zero holds the value at the start of the first iteration and completes after the delay.
+    Count(u32),
+    /// Plays until cancelled.
+    Infinite,
+}
+
+/// The direction in which successive iterations of a [Timeline] play.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Every iteration plays forward.
+    Normal,
+    /// Every iteration plays backward.
+    Reverse,
+    /// Iterations alternate between playing forward and backward, starting forward.
+    Alternate,
+}
+
+/// A value reached at a point in a [Timeline].
+#[derive(Clone, Debug)]
+pub struct Keyframe<T: Interpolate> {
+    /// The time of the keyframe in milliseconds from the start of the timeline.
+    pub time: f64,
+    /// The value at the keyframe.
+    pub value: T,
+    /// The easing of the segment leading into the keyframe.
+    pub easing: Easing,
+}
+
+/// A sequence of keyframes with playback options.
+#[derive(Clone, Debug)]
+pub struct Timeline<T: Interpolate> {
+    keyframes: Vec<Keyframe<T>>,
+    delay: f64,
+    iterations: Iterations,
+    direction: Direction,
+}
+
+impl<T: Interpolate> Timeline<T> {
+    /// Creates a timeline starting at the provided value.
+    pub fn new(value: T) -> Self {
+        Timeline {
+            keyframes: vec![Keyframe {
+                time: 0.,
+                value,
+                easing: Easing::Linear,
+            }],
+            delay: 0.,
+            iterations: Iterations::Count(1),
+            direction: Direction::Normal,
+        }
+    }
+    /// Appends a keyframe reached the provided number of milliseconds after the last keyframe.
+    pub fn to(mut self, value: T, duration: f64, easing: Easing) -> Self {
+        let time = self.duration() + duration.max(0.);
+        self.keyframes.push(Keyframe {
+            time,
+            value,
+            easing,
+        });
+        self
+    }
+    /// Holds the last value for the provided number of milliseconds.
+    pub fn hold(self, duration: f64) -> Self {
+        let value = self.keyframes.last().unwrap().value.clone();
+        self.to(value, duration, Easing::Linear)
+    }
+    /// Appends the keyframes of another timeline, including its delay, after the last keyframe.
+    pub fn then(mut self, other: Timeline<T>) -> Self {
+        let offset = self.duration() + other.delay;
+        let mut keyframes = other.keyframes.into_iter();
+        let first = keyframes.next().unwrap();
+        self = if other.delay > 0. {
+            self.hold(other.delay)
+        } else {
+            self
+        };
+        self.keyframes.push(Keyframe {
+            time: offset,
+            ..first
+        });
+        self.keyframes.extend(keyframes.map(|keyframe| Keyframe {
+            time: keyframe.time + offset,
+            ..keyframe
+        }));
+        self
+    }
+    /// Delays the start of playback by the provided number of milliseconds.
+    pub fn delay(mut self, delay: f64) -> Self {
+        self.delay = delay.max(0.);
+        self
+    }
+    /// Sets the number of times the timeline plays.
+    pub fn repeat(mut self, iterations: Iterations) -> Self {
+        self.iterations = iterations;
+        self
+    }
+    /// Plays the timeline until cancelled.
+    pub fn looped(self) -> Self {
+        self.repeat(Iterations::Infinite)
+    }
+    /// Plays every iteration backward.
+    pub fn reversed(mut self) -> Self {
+        self.direction = Direction::Reverse;
+        self
+    }
+    /// Alternates between playing forward and backward.
+    pub fn alternate(mut self) -> Self {
+        self.direction = Direction::Alternate;
+        self
+    }
+    /// Returns the duration of a single iteration in milliseconds.
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().unwrap().time
+    }
+    /// Returns the total playback time in milliseconds including the delay.
+    pub fn total_duration(&self) -> f64 {
+        match self.iterations {
+            Iterations::Count(count) => self.delay + self.duration() * f64::from(count),
+            Iterations::Infinite => f64::INFINITY,
+        }
+    }
+    fn value_at(&self, time: f64) -> T {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time);
+        match next {
+            None => self.keyframes.last().unwrap().value.clone(),
+            Some(0) => self.keyframes[0].value.clone(),
+            Some(index) => {
+                let (from, to) = (&self.keyframes[index - 1], &self.keyframes[index]);
+                let progress = (time - from.time) / (to.time - from.time);
+                from.value.interpolate(&to.value, to.easing.apply(progress))
+            }
+        }
+    }
+    /// Returns the value the provided number of milliseconds into playback, or `None` during the delay.
+    pub fn sample(&self, elapsed: f64) -> Option<T> {
+        let elapsed = elapsed - self.delay;
+        if elapsed < 0. {
+            return None;
+        }
+        let duration = self.duration();
+        let count = match self.iterations {
+            Iterations::Count(count) => f64::from(count),
+            Iterations::Infinite => f64::INFINITY,
+        };
+        let (iteration, time) = if count == 0. {
+            (0., 0.)
+        } else if duration <= 0. || elapsed >= duration * count {
+            (count - 1., duration)
+        } else {
+            let iteration = (elapsed / duration).floor();
+            (iteration, elapsed - iteration * duration)
+        };
+        let backward = match self.direction {
+            Direction::Normal => false,
+            Direction::Reverse => true,
+            Direction::Alternate => iteration % 2. == 1.,
+        };
+        Some(self.value_at(if backward { duration - time } else { time }))
+    }
+}
+
+/// A transition between two values.
+#[derive(Clone, Debug)]
+pub struct Tween<T: Interpolate> {
+    from: T,
+    to: T,
+    duration: f64,
+    easing: Easing,
+    delay: f64,
+}
+
+impl<T: Interpolate> Tween<T> {
+    /// Creates a linear tween lasting the provided number of milliseconds.
+    pub fn new(from: T, to: T, duration: f64) -> Self {
+        Tween {
+            from,
+            to,
+            duration,
+            easing: Easing::Linear,
+            delay: 0.,
+        }
+    }
+    /// Sets the easing curve.
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+    /// Delays the start of the tween by the provided number of milliseconds.
+    pub fn delay(mut self, delay: f64) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl<T: Interpolate> From<Tween<T>> for Timeline<T> {
+    fn from(input: Tween<T>) -> Timeline<T> {
+        Timeline::new(input.from)
+            .to(input.to, input.duration, input.easing)
+            .delay(input.delay)
+    }
+}
+
+/// The way in which an [Animation] ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Finish {
+    /// The animation played to completion.
+    Completed,
+    /// The animation was cancelled.
+    Cancelled,
+}
+
+#[derive(Default)]
+struct AnimationState {
+    finish: Mutex<Option<Finish>>,
+    task: AtomicTask,
+}
+
+impl AnimationState {
+    fn finish(&self, finish: Finish) {
+        let mut state = self.finish.lock().unwrap();
+        if state.is_none() {
+            *state = Some(finish);
+            self.task.notify();
+        }
+    }
+}
+
+/// A handle to a playing animation that resolves when it completes or is cancelled.
+#[derive(Clone)]
+pub struct Animation {
+    state: Arc<AnimationState>,
+}
+
+impl Animation {
+    /// Stops the animation, leaving its target at the current value.
+    pub fn cancel(&self) {
+        self.state.finish(Finish::Cancelled);
+    }
+    /// Returns how the animation ended, or `None` if it is still playing.
+    pub fn finished(&self) -> Option<Finish> {
+        *self.state.finish.lock().unwrap()
+    }
+}
+
+impl Debug for Animation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Animation {{ finished: {:?} }}", self.finished())
+    }
+}
+
+impl Future for Animation {
+    type Item = Finish;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if let Some(finish) = self.finished() {
+            return Ok(Async::Ready(finish));
+        }
+        self.state.task.register();
+        Ok(match self.finished() {
+            Some(finish) => Async::Ready(finish),
+            None => Async::NotReady,
+        })
+    }
+}
+
+trait Playback: Send + Sync {
+    fn advance(&mut self, delta: f64) -> bool;
+}
+
+struct Player<T: Interpolate> {
+    timeline: Timeline<T>,
+    elapsed: f64,
+    apply: Box<dyn FnMut(T) + Send + Sync>,
+    state: Arc<AnimationState>,
+}
+
+impl<T: Interpolate> Playback for Player<T> {
+    fn advance(&mut self, delta: f64) -> bool {
+        if self.state.finish.lock().unwrap().is_some() {
+            return true;
+        }
+        self.elapsed += delta;
+        if let Some(value) = self.timeline.sample(self.elapsed) {
+            (self.apply)(value);
+        }
+        if self.elapsed >= self.timeline.total_duration() {
+            self.state.finish(Finish::Completed);
+            return true;
+        }
+        false
+    }
+}
+
+/// Drives animations from the ticks of a [Ticker].
+#[derive(Clone, Default)]
+pub struct Animator {
+    players: Arc<Mutex<Vec<Box<dyn Playback>>>>,
+}
+
+impl Animator {
+    /// Creates an animator. Animations advance when it is bound to a [Ticker] or ticked manually.
+    pub fn new() -> Animator {
+        Animator::default()
+    }
+    /// Advances every animation on each tick of the provided [Ticker].
+    pub fn bind<T: Ticker + ?Sized>(&self, ticker: &mut T) {
+        let animator = self.clone();
+        ticker.bind(Box::new(move |delta| animator.tick(delta)));
+    }
+    /// Advances every animation by the provided number of milliseconds.
+    pub fn tick(&self, delta: f64) {
+        // Players are taken out so targets may start further animations while they run.
+        let mut players = std::mem::take(&mut *self.players.lock().unwrap());
+        players.retain_mut(|player| !player.advance(delta));
+        self.players.lock().unwrap().extend(players);
+    }
+    /// Plays a timeline, passing each sampled value to the provided target.
+    pub fn animate<T, U, F>(&self, timeline: U, target: F) -> Animation
+    where
+        T: Interpolate,
+        U: Into<Timeline<T>>,
+        F: FnMut(T) + Send + Sync + 'static,
+    {
+        let state = Arc::new(AnimationState::default());
+        self.players.lock().unwrap().push(Box::new(Player {
+            timeline: timeline.into(),
+            elapsed: 0.,
+            apply: Box::new(target),
+            state: state.clone(),
+        }));
+        Animation { state }
+    }
+    /// Plays a timeline over the transformation of an [Object].
+    pub fn animate_transform<U>(&self, mut object: Box<dyn Object>, timeline: U) -> Animation
+    where
+        U: Into<Timeline<Transform2>>,
+    {
+        self.animate(timeline, move |transform| object.set_transform(transform))
+    }
+    /// Plays a timeline, replacing the contents of an [Object] with those built from each sampled value.
+    pub fn animate_content<T, U, F>(
+        &self,
+        mut object: Box<dyn Object>,
+        timeline: U,
+        content: F,
+    ) -> Animation
+    where
+        T: Interpolate,
+        U: Into<Timeline<T>>,
+        F: Fn(T) -> Rasterizable + Send + Sync + 'static,
+    {
+        self.animate(timeline, move |value| object.update(content(value)))
+    }
+    /// Returns the number of animations still playing.
+    pub fn len(&self) -> usize {
+        self.players.lock().unwrap().len()
+    }
+    /// Returns whether no animations are playing.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Debug for Animator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Animator {{ playing: {} }}", self.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn easing_curves() {
+        for easing in &[
+            Easing::Linear,
+            Easing::EASE,
+            Easing::EASE_IN_OUT,
+            Easing::Spring {
+                stiffness: 170.,
+                damping: 26.,
+            },
+            Easing::Spring {
+                stiffness: 100.,
+                damping: 30.,
+            },
+            Easing::ElasticIn,
+            Easing::ElasticOut,
+            Easing::ElasticInOut,
+        ] {
+            assert!(close(easing.apply(0.), 0.), "{:?}", easing);
+            assert!(close(easing.apply(1.), 1.), "{:?}", easing);
+        }
+        assert!(close(Easing::EASE.apply(0.5), 0.8024));
+        assert!(close(Easing::CubicBezier(0., 0., 1., 1.).apply(0.3), 0.3));
+        assert!(Easing::ElasticOut.apply(0.2) > 1.);
+        assert_eq!(Easing::Steps(4, StepPosition::End).apply(0.3), 0.25);
+        assert_eq!(Easing::Steps(4, StepPosition::Start).apply(0.3), 0.5);
+        assert_eq!(Easing::Steps(4, StepPosition::Start).apply(0.), 0.25);
+        assert_eq!(Easing::Steps(4, StepPosition::End).apply(1.), 1.);
+    }
+
+    #[test]
+    fn timeline_playback() {
+        let timeline = Timeline::new(0.)
+            .to(10., 100., Easing::Linear)
+            .then(Timeline::new(10.).to(0., 100., Easing::Linear).delay(50.))
+            .delay(20.);
+        assert_eq!(timeline.duration(), 250.);
+        assert_eq!(timeline.sample(10.), None);
+        assert_eq!(timeline.sample(70.), Some(5.));
+        assert_eq!(timeline.sample(150.), Some(10.));
+        assert_eq!(timeline.sample(220.), Some(5.));
+        assert_eq!(timeline.sample(1000.), Some(0.));
+
+        let timeline = Timeline::from(Tween::new(0., 10., 100.))
+            .repeat(Iterations::Count(2))
+            .alternate();
+        assert_eq!(timeline.sample(25.), Some(2.5));
+        assert_eq!(timeline.sample(125.), Some(7.5));
+        assert_eq!(timeline.sample(200.), Some(0.));
+        assert_eq!(
+            Timeline::from(Tween::new(0., 10., 100.))
+                .reversed()
+                .sample(25.),
+            Some(7.5)
+        );
+
+        let timeline =
+            Timeline::from(Tween::new(0., 10., 100.).delay(20.)).repeat(Iterations::Count(0));
+        assert_eq!(timeline.total_duration(), 20.);
+        assert_eq!(timeline.sample(10.), None);
+        assert_eq!(timeline.sample(50.), Some(0.));
+        assert_eq!(timeline.reversed().sample(50.), Some(10.));
+    }
+
+    #[test]
+    fn animator_completion() {
+        let animator = Animator::new();
+        let value = Arc::new(Mutex::new(Opacity(0.)));
+        let target = value.clone();
+        let animation = animator.animate(
+            Tween::new(Opacity(0.), Opacity(1.), 100.).easing(Easing::ElasticOut),
+            move |opacity| *target.lock().unwrap() = opacity,
+        );
+        let looped = animator.animate(Timeline::from(Tween::new(0., 1., 10.)).looped(), |_| {});
+        animator.tick(20.);
+        assert!(value.lock().unwrap().0 <= 1.);
+        assert_eq!(animation.finished(), None);
+        animator.tick(100.);
+        assert_eq!(*value.lock().unwrap(), Opacity(1.));
+        assert_eq!(animation.wait(), Ok(Finish::Completed));
+        assert_eq!(animator.len(), 1);
+        looped.cancel();
+        animator.tick(10.);
+        assert!(animator.is_empty());
+        assert_eq!(looped.wait(), Ok(Finish::Cancelled));
+    }
+}
@@ -1,15 +1,19 @@
 use crate::{
+    errors::Error,
     graphics::{
+        color::{ColorProfile, RenderingIntent, SoftProof},
         path::{Path, Primitive},
-        text::Text,
-        ImageRepresentation, Rect, Transform2, Vector2,
+        text::{FontMetrics, Text},
+        Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
     },
-    input::Provider,
+    input::{windowing::WindowId, Provider},
     targets,
 };
 
 use std::any::Any;
 
+pub(crate) mod timing;
+
 /// Represents content optimized and cached for rendering.
 pub trait Object: Sync + Send {
     /// Composes a transformation with the existing transformation of the [Object].
@@ -44,10 +48,40 @@ pub trait Frame: Sync + Send {
     fn set_viewport(&self, viewport: Rect);
     /// Returns the size of the [Frame].
     fn get_size(&self) -> Vector2;
+    /// Returns the viewport of the [Frame].
+    fn get_viewport(&self) -> Rect;
     /// Returns an image that is a still rasterization of any rendered content.
     fn to_image(&self) -> Box<dyn ImageRepresentation>;
+    /// Returns a still rasterization of any rendered content converted to the provided color profile.
+    fn to_image_with_profile(
+        &self,
+        profile: ColorProfile,
+    ) -> Result<Box<dyn ImageRepresentation>, Error>;
     /// Returns the measured dimensions of some provided content.
     fn measure(&self, input: Rasterizable) -> Vector2;
+    /// Returns the metrics of the font face, size and weight of some provided text.
+    fn font_metrics(&self, input: Text) -> FontMetrics;
+    /// Returns the glyph outlines of some provided text as a [Path] laid out as the text would be rendered.
+    /// The path is filled with the text color and carries the stroke and shadows of the text.
+    ///
+    /// Returns `None` if the target cannot provide glyph outlines.
+    fn outline(&self, input: Text) -> Option<Path>;
+    /// Attaches a display color profile to the [Frame] in place of the profile reported by the platform.
+    /// Content added or updated afterward is converted from its source color space to the profile.
+    ///
+    /// Targets where color management is handled by the platform ignore the profile.
+    fn set_color_profile(&self, profile: ColorProfile) -> Result<(), Error>;
+    /// Sets the rendering intent used when converting content to the display color profile.
+    /// Fails if the display profile cannot be converted to with the intent, leaving the previous intent in place.
+    fn set_rendering_intent(&self, intent: RenderingIntent) -> Result<(), Error>;
+    /// Renders content added or updated afterward as it would reproduce on the output device of a [SoftProof],
+    /// including images returned by [Frame::to_image]. Passing `None` disables soft-proofing.
+    fn set_soft_proof(&self, proof: Option<SoftProof>) -> Result<(), Error>;
+    /// Sets how many converted colors are cached for the display color profile. Larger caches speed up redrawing
+    /// scenes with many distinct colors, and a size of zero disables caching.
+    ///
+    /// Targets where color management is handled by the platform ignore the size.
+    fn set_color_cache_size(&self, size: usize);
     #[doc(hidden)]
     fn box_clone(&self) -> Box<dyn Frame>;
     #[doc(hidden)]
@@ -179,8 +213,145 @@ impl Clone for Box<dyn ActiveCanvas> {
     }
 }
 
-/// An active canvas.
-pub trait ActiveCanvas: CanvasContext {
+/// A standard cursor icon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorIcon {
+    /// The platform default cursor, usually an arrow.
+    Default,
+    /// A pointing hand indicating a link or other clickable content.
+    Pointer,
+    /// An I-beam indicating selectable text.
+    Text,
+    /// An I-beam indicating selectable vertical text.
+    VerticalText,
+    /// A crosshair.
+    Crosshair,
+    /// Indicates that something can be moved.
+    Move,
+    /// Indicates that something can be grabbed.
+    Grab,
+    /// Indicates that something is being grabbed.
+    Grabbing,
+    /// Indicates that an action is not allowed.
+    NotAllowed,
+    /// Indicates that a dragged item cannot be dropped here.
+    NoDrop,
+    /// Indicates that the application is busy.
+    Wait,
+    /// Indicates that the application is busy but can still be interacted with.
+    Progress,
+    /// Indicates that help is available.
+    Help,
+    /// Indicates that a context menu is available.
+    ContextMenu,
+    /// Indicates that a cell or set of cells may be selected.
+    Cell,
+    /// Indicates that an alias or shortcut is to be created.
+    Alias,
+    /// Indicates that something is to be copied.
+    Copy,
+    /// Indicates that something can be scrolled in any direction.
+    AllScroll,
+    /// Indicates that something can be zoomed in.
+    ZoomIn,
+    /// Indicates that something can be zoomed out.
+    ZoomOut,
+    /// Indicates that an edge is to be moved east.
+    EResize,
+    /// Indicates that an edge is to be moved north.
+    NResize,
+    /// Indicates that an edge is to be moved north-east.
+    NeResize,
+    /// Indicates that an edge is to be moved north-west.
+    NwResize,
+    /// Indicates that an edge is to be moved south.
+    SResize,
+    /// Indicates that an edge is to be moved south-east.
+    SeResize,
+    /// Indicates that an edge is to be moved south-west.
+    SwResize,
+    /// Indicates that an edge is to be moved west.
+    WResize,
+    /// Indicates a bidirectional east-west resize.
+    EwResize,
+    /// Indicates a bidirectional north-south resize.
+    NsResize,
+    /// Indicates a bidirectional north-east to south-west resize.
+    NeswResize,
+    /// Indicates a bidirectional north-west to south-east resize.
+    NwseResize,
+    /// Indicates that a column can be resized horizontally.
+    ColResize,
+    /// Indicates that a row can be resized vertically.
+    RowResize,
+}
+
+/// A cursor shown over a window.
+#[derive(Clone, Debug)]
+pub enum Cursor {
+    /// A standard cursor icon.
+    Icon(CursorIcon),
+    /// A custom cursor image.
+    Custom {
+        /// The cursor image.
+        image: Image<LDRColor, Texture2>,
+        /// The point within the image in pixels that corresponds to the cursor position.
+        hotspot: Vector2,
+    },
+}
+
+impl From<CursorIcon> for Cursor {
+    fn from(icon: CursorIcon) -> Cursor {
+        Cursor::Icon(icon)
+    }
+}
+
+/// The constraint applied to the cursor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorGrab {
+    /// The cursor moves freely.
+    None,
+    /// The cursor is confined to the window.
+    Confine,
+    /// The cursor is hidden and held in place. Movement is reported through [Motion](crate::input::mouse::Event::Motion) events.
+    Lock,
+}
+
+/// A window of a graphics context.
+pub trait Window: Provider + Sync + Send {
+    /// Returns the identifier carried by input events directed at the window.
+    fn id(&self) -> WindowId;
+    /// Sets the title of the window.
+    fn set_title(&self, title: &str);
+    /// Sets the logical size of the window contents. Has no effect on the web, where the browser sizes the page.
+    fn set_size(&self, size: Vector2);
+    /// Enters or exits fullscreen.
+    fn set_fullscreen(&self, fullscreen: bool);
+    /// Closes the window. Closing the main window ends the runloop.
+    fn close(&self);
+    /// Sets whether close requests are left to the application. See [WindowConfig::intercept_close].
+    fn set_intercept_close(&self, intercept: bool);
+    /// Sets the cursor shown over the window. Fails if custom cursors are not supported on this target, which natively
+    /// supports them only on X11. Windows without an X display show the default cursor instead.
+    fn set_cursor(&self, cursor: Cursor) -> Result<(), Error>;
+    /// Shows or hides the cursor over the window.
+    fn set_cursor_visible(&self, visible: bool);
+    /// Sets the constraint applied to the cursor. Fails if the constraint is not supported on this target.
+    /// Requests may also be refused later, e.g. while the window is unfocused, so the constraint in effect
+    /// is reported by [CursorGrab](crate::input::windowing::Event::CursorGrab) events.
+    fn set_cursor_grab(&self, grab: CursorGrab) -> Result<(), Error>;
+    /// Sets the position in viewport coordinates at which input method editors place their candidate window, typically the text cursor.
+    fn set_ime_position(&self, position: Vector2);
+}
+
+/// An active canvas. Windowing and input apply to the main window.
+pub trait ActiveCanvas: CanvasContext + Window {
+    /// Opens an additional window configured as specified using the provided [Frame] as its root.
+    fn open_window(
+        &self,
+        root: Box<dyn Frame>,
+        config: WindowConfig,
+    ) -> Result<Box<dyn Window>, Error>;
     #[doc(hidden)]
     fn box_clone(&self) -> Box<dyn ActiveCanvas>;
 }
@@ -193,16 +364,199 @@ pub trait InactiveCanvas: CanvasContext {
     fn run(self: Box<Self>);
 }
 
+/// Timing statistics over recent frames. Frame times are in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// Frames per second.
+    pub fps: f64,
+    /// The mean frame time.
+    pub mean_frame_time: f64,
+    /// The median frame time.
+    pub median_frame_time: f64,
+    /// The 95th percentile frame time.
+    pub p95_frame_time: f64,
+    /// The 99th percentile frame time.
+    pub p99_frame_time: f64,
+    /// The longest frame time.
+    pub max_frame_time: f64,
+    /// The number of frames presented since the runloop started.
+    pub frames: u64,
+    /// The number of frames missed due to long frame times since the runloop started.
+    pub dropped_frames: u64,
+}
+
 /// A type that permits the binding of tick handlers.
 pub trait Ticker {
-    /// Binds a handler to receive ticks.
+    /// Binds a handler to receive ticks with the milliseconds elapsed since the previous tick.
     fn bind(&mut self, handler: Box<dyn FnMut(f64) + 'static + Send + Sync>);
+    /// Binds handlers for a fixed-timestep simulation. `update` is called with the step in milliseconds
+    /// as many times as the elapsed time requires, then `interpolate` is called once per tick with the
+    /// fraction of a step left over for blending between simulation states.
+    fn bind_fixed(
+        &mut self,
+        step: f64,
+        update: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+        interpolate: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+    );
+    /// Returns timing statistics over recent frames.
+    fn frame_stats(&self) -> FrameStats;
+}
+
+/// Options for the creation of a window. Sizes and positions are in logical pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowConfig {
+    /// The title of the window. An empty title leaves the document title unchanged on the web.
+    pub title: String,
+    /// The initial size of the window contents.
+    pub size: Vector2,
+    /// The minimum size of the window contents, if any.
+    pub min_size: Option<Vector2>,
+    /// The maximum size of the window contents, if any.
+    pub max_size: Option<Vector2>,
+    /// Whether the window may be resized by the user.
+    pub resizable: bool,
+    /// Whether the window has decorations such as a title bar and borders.
+    pub decorations: bool,
+    /// Whether the window starts fullscreen.
+    pub fullscreen: bool,
+    /// Whether the window is kept above other windows.
+    pub always_on_top: bool,
+    /// Whether the window background is transparent.
+    pub transparent: bool,
+    /// Whether presentation is synchronized with the display refresh.
+    pub vsync: bool,
+    /// The initial position of the window, if any.
+    pub position: Option<Vector2>,
+    /// Whether close requests are left to the application, which may then close the window using [Window::close].
+    pub intercept_close: bool,
+    /// The height in logical pixels of a line used to convert line-based scroll input to pixels.
+    pub scroll_line_height: f64,
+    /// The dead zone applied to gamepad axes, as a fraction of their range.
+    pub gamepad_dead_zone: f64,
+}
+
+impl Default for WindowConfig {
+    fn default() -> WindowConfig {
+        WindowConfig {
+            title: String::new(),
+            size: (700., 700.).into(),
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            decorations: true,
+            fullscreen: false,
+            always_on_top: false,
+            transparent: false,
+            vsync: true,
+            position: None,
+            intercept_close: false,
+            scroll_line_height: 20.,
+            gamepad_dead_zone: 0.1,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Creates a new default window configuration.
+    pub fn new() -> Self {
+        WindowConfig::default()
+    }
+    /// Sets the title of the window.
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_owned();
+        self
+    }
+    /// Sets the initial size of the window contents.
+    pub fn size<T>(mut self, size: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.size = size.into();
+        self
+    }
+    /// Sets the minimum size of the window contents.
+    pub fn min_size<T>(mut self, size: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.min_size = Some(size.into());
+        self
+    }
+    /// Sets the maximum size of the window contents.
+    pub fn max_size<T>(mut self, size: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.max_size = Some(size.into());
+        self
+    }
+    /// Sets whether the window may be resized by the user.
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+    /// Sets whether the window has decorations.
+    pub fn decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+    /// Sets whether the window starts fullscreen.
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+    /// Sets whether the window is kept above other windows.
+    pub fn always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+    /// Sets whether the window background is transparent.
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+    /// Sets whether presentation is synchronized with the display refresh.
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+    /// Sets the initial position of the window.
+    pub fn position<T>(mut self, position: T) -> Self
+    where
+        T: Into<Vector2>,
+    {
+        self.position = Some(position.into());
+        self
+    }
+    /// Sets whether close requests are left to the application.
+    pub fn intercept_close(mut self, intercept: bool) -> Self {
+        self.intercept_close = intercept;
+        self
+    }
+    /// Sets the height of a line used to convert line-based scroll input to pixels.
+    pub fn scroll_line_height(mut self, line_height: f64) -> Self {
+        self.scroll_line_height = line_height;
+        self
+    }
+    /// Sets the dead zone applied to gamepad axes.
+    pub fn gamepad_dead_zone(mut self, dead_zone: f64) -> Self {
+        self.gamepad_dead_zone = dead_zone;
+        self
+    }
 }
 
 /// A graphics context that can provide input and windowing.
 pub trait InteractiveCanvas: Canvas {
     /// Starts a windowed context using the provided [Frame] as the document root.
-    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas>;
+    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
+        self.start_with_config(root, WindowConfig::default())
+    }
+    /// Starts a windowed context configured as specified using the provided [Frame] as the document root.
+    fn start_with_config(
+        self: Box<Self>,
+        root: Box<dyn Frame>,
+        config: WindowConfig,
+    ) -> Box<dyn InactiveCanvas>;
 }
 
 /// Initializes a new graphics context.
@@ -0,0 +1,164 @@
+use crate::graphics::canvas::FrameStats;
+
+use std::collections::VecDeque;
+
+const STATS_WINDOW: usize = 120;
+// Fixed-timestep simulations skip ahead rather than spiral when a frame takes longer than this.
+const MAX_ACCUMULATED: f64 = 250.;
+
+struct FixedHandler {
+    step: f64,
+    accumulator: f64,
+    update: Box<dyn FnMut(f64) + Send + Sync>,
+    interpolate: Box<dyn FnMut(f64) + Send + Sync>,
+}
+
+/// Dispatches ticks from monotonic frame timestamps and records frame timing.
+pub(crate) struct TickDispatcher {
+    handlers: Vec<Box<dyn FnMut(f64) + Send + Sync>>,
+    fixed_handlers: Vec<FixedHandler>,
+    last_timestamp: Option<f64>,
+    frame_times: VecDeque<f64>,
+    frames: u64,
+    dropped_frames: u64,
+}
+
+fn percentile(sorted: &[f64], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.;
+    }
+    let rank = (percentile / 100. * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
+
+impl TickDispatcher {
+    pub(crate) fn new() -> TickDispatcher {
+        TickDispatcher {
+            handlers: vec![],
+            fixed_handlers: vec![],
+            last_timestamp: None,
+            frame_times: VecDeque::with_capacity(STATS_WINDOW),
+            frames: 0,
+            dropped_frames: 0,
+        }
+    }
+    pub(crate) fn bind(&mut self, handler: Box<dyn FnMut(f64) + Send + Sync>) {
+        self.handlers.push(handler);
+    }
+    pub(crate) fn bind_fixed(
+        &mut self,
+        step: f64,
+        update: Box<dyn FnMut(f64) + Send + Sync>,
+        interpolate: Box<dyn FnMut(f64) + Send + Sync>,
+    ) {
+        self.fixed_handlers.push(FixedHandler {
+            step: step.max(f64::EPSILON),
+            accumulator: 0.,
+            update,
+            interpolate,
+        });
+    }
+    /// Advances every handler to the provided timestamp in milliseconds from a monotonic clock.
+    pub(crate) fn tick(&mut self, timestamp: f64) {
+        let delta = match self.last_timestamp {
+            Some(last_timestamp) => (timestamp - last_timestamp).max(0.),
+            None => 0.,
+        };
+        if self.last_timestamp.is_some() {
+            self.record(delta);
+        }
+        self.last_timestamp = Some(timestamp);
+        self.handlers
+            .iter_mut()
+            .for_each(|handler| (handler)(delta));
+        for handler in &mut self.fixed_handlers {
+            handler.accumulator = (handler.accumulator + delta).min(MAX_ACCUMULATED);
+            while handler.accumulator >= handler.step {
+                (handler.update)(handler.step);
+                handler.accumulator -= handler.step;
+            }
+            (handler.interpolate)(handler.accumulator / handler.step);
+        }
+    }
+    fn record(&mut self, frame_time: f64) {
+        // Frames taking well over the typical frame time are counted as having displaced the
+        // frames that would have been presented in the meantime.
+        let typical = self.stats().median_frame_time;
+        if typical > 0. && frame_time > typical * 1.5 {
+            self.dropped_frames += (frame_time / typical).round() as u64 - 1;
+        }
+        if self.frame_times.len() == STATS_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+        self.frames += 1;
+    }
+    pub(crate) fn stats(&self) -> FrameStats {
+        let mut sorted: Vec<f64> = self.frame_times.iter().cloned().collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mean = if sorted.is_empty() {
+            0.
+        } else {
+            sorted.iter().sum::<f64>() / sorted.len() as f64
+        };
+        FrameStats {
+            fps: if mean > 0. { 1000. / mean } else { 0. },
+            mean_frame_time: mean,
+            median_frame_time: percentile(&sorted, 50.),
+            p95_frame_time: percentile(&sorted, 95.),
+            p99_frame_time: percentile(&sorted, 99.),
+            max_frame_time: sorted.last().cloned().unwrap_or(0.),
+            frames: self.frames,
+            dropped_frames: self.dropped_frames,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn fixed_timestep() {
+        let mut dispatcher = TickDispatcher::new();
+        let steps = Arc::new(Mutex::new(vec![]));
+        let alphas = Arc::new(Mutex::new(vec![]));
+        let (update_steps, interpolate_alphas) = (steps.clone(), alphas.clone());
+        dispatcher.bind_fixed(
+            10.,
+            Box::new(move |step| update_steps.lock().unwrap().push(step)),
+            Box::new(move |alpha| interpolate_alphas.lock().unwrap().push(alpha)),
+        );
+        for timestamp in &[100., 125., 130., 1000.] {
+            dispatcher.tick(*timestamp);
+        }
+        // 25ms then 5ms then a stall clamped to 250ms, for 2 + 1 + 25 steps.
+        assert_eq!(steps.lock().unwrap().len(), 28);
+        assert!(steps.lock().unwrap().iter().all(|step| *step == 10.));
+        let alphas = alphas.lock().unwrap();
+        assert_eq!(alphas[0], 0.);
+        assert!((alphas[1] - 0.5).abs() < 1e-9);
+        assert!(alphas[2].abs() < 1e-9);
+    }
+
+    #[test]
+    fn frame_stats() {
+        let mut dispatcher = TickDispatcher::new();
+        let mut timestamp = 0.;
+        dispatcher.tick(timestamp);
+        for _ in 0..99 {
+            timestamp += 10.;
+            dispatcher.tick(timestamp);
+        }
+        timestamp += 40.;
+        dispatcher.tick(timestamp);
+        let stats = dispatcher.stats();
+        assert_eq!(stats.frames, 100);
+        assert_eq!(stats.dropped_frames, 3);
+        assert_eq!(stats.median_frame_time, 10.);
+        assert_eq!(stats.max_frame_time, 40.);
+        assert!((stats.mean_frame_time - 10.3).abs() < 1e-9);
+        assert!((stats.fps - 1000. / 10.3).abs() < 1e-9);
+    }
+}
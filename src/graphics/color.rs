@@ -0,0 +1,903 @@
+use crate::errors::Error;
+use crate::graphics::{srgb_to_linear, ColorSpace, HDRColor, LDRColor};
+
+use std::f64::consts::PI;
+use std::{fs, path::Path, str::FromStr};
+
+const LAB_WHITE: [f64; 3] = [0.950_455_9, 1., 1.089_057_7];
+const LAB_EPSILON: f64 = 6. / 29.;
+
+/// A color in the HSL (hue, saturation, lightness) model of sRGB.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HSLColor {
+    /// Hue in degrees between 0 and 360.
+    pub h: f64,
+    /// Saturation between 0 and 1.
+    pub s: f64,
+    /// Lightness between 0 and 1.
+    pub l: f64,
+    /// Alpha channel data between 0 and 1.
+    pub alpha: f64,
+}
+
+/// A color in the HSV (hue, saturation, value) model of sRGB.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HSVColor {
+    /// Hue in degrees between 0 and 360.
+    pub h: f64,
+    /// Saturation between 0 and 1.
+    pub s: f64,
+    /// Value between 0 and 1.
+    pub v: f64,
+    /// Alpha channel data between 0 and 1.
+    pub alpha: f64,
+}
+
+/// A color in the CIE L\*a\*b\* space relative to the D65 white point.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LabColor {
+    /// Perceptual lightness between 0 and 100.
+    pub l: f64,
+    /// Green-red axis.
+    pub a: f64,
+    /// Blue-yellow axis.
+    pub b: f64,
+    /// Alpha channel data between 0 and 1.
+    pub alpha: f64,
+}
+
+/// A color in the cylindrical CIE LCh form of [LabColor].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LChColor {
+    /// Perceptual lightness between 0 and 100.
+    pub l: f64,
+    /// Chroma.
+    pub c: f64,
+    /// Hue in degrees between 0 and 360.
+    pub h: f64,
+    /// Alpha channel data between 0 and 1.
+    pub alpha: f64,
+}
+
+/// A color in the OKLab perceptual space.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OkLabColor {
+    /// Perceptual lightness between 0 and 1.
+    pub l: f64,
+    /// Green-red axis.
+    pub a: f64,
+    /// Blue-yellow axis.
+    pub b: f64,
+    /// Alpha channel data between 0 and 1.
+    pub alpha: f64,
+}
+
+/// The color model in which two colors are interpolated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MixSpace {
+    /// Gamma-encoded sRGB, matching the default behavior of CSS gradients.
+    Srgb,
+    /// Linear-light sRGB.
+    LinearSrgb,
+    /// HSL, interpolating hue along the shorter arc.
+    HSL,
+    /// CIE L\*a\*b\*.
+    Lab,
+    /// CIE LCh, interpolating hue along the shorter arc.
+    LCh,
+    /// OKLab.
+    OkLab,
+}
+
+/// The strategy used to map colors that fall outside the gamut of a destination profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RenderingIntent {
+    /// Compresses the full source gamut to preserve the relationships between colors.
+    #[default]
+    Perceptual,
+    /// Reproduces in-gamut colors exactly relative to the destination white point and clips the rest.
+    RelativeColorimetric,
+    /// Preserves saturation at the expense of hue and lightness accuracy.
+    Saturation,
+    /// Reproduces in-gamut colors exactly without white point adaptation and clips the rest.
+    AbsoluteColorimetric,
+}
+
+/// An ICC color profile describing the color reproduction of a device.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorProfile {
+    data: Vec<u8>,
+}
+
+impl ColorProfile {
+    /// Creates a profile from the raw bytes of an ICC profile.
+    pub fn from_icc(data: &'_ [u8]) -> Result<ColorProfile, Error> {
+        // The profile header is 128 bytes followed by the tag count, and the header
+        // begins with the declared profile size and carries the `acsp` signature at byte 36.
+        if data.len() < 132 || &data[36..40] != b"acsp" {
+            return Err(Error::color_profile());
+        }
+        let size = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if size < 132 || size > data.len() {
+            return Err(Error::color_profile());
+        }
+        Ok(ColorProfile {
+            data: data[..size].to_vec(),
+        })
+    }
+    /// Reads an ICC profile from the file at the provided path.
+    pub fn from_path<P>(path: P) -> Result<ColorProfile, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let data = fs::read(path).map_err(|_| Error::color_profile())?;
+        ColorProfile::from_icc(&data)
+    }
+    /// Returns the raw bytes of the ICC profile.
+    pub fn data(&self) -> &'_ [u8] {
+        &self.data
+    }
+}
+
+/// Simulates the reproduction of content on an output device such as a printing press.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoftProof {
+    /// The ICC profile of the simulated output device.
+    pub profile: ColorProfile,
+    /// The rendering intent used when converting to the output device.
+    pub intent: RenderingIntent,
+    /// The color used to highlight content outside the gamut of the output device, if any.
+    pub gamut_warning: Option<LDRColor>,
+}
+
+impl SoftProof {
+    /// Creates a soft proof for the output device described by the provided profile.
+    pub fn new(profile: ColorProfile) -> SoftProof {
+        SoftProof {
+            profile,
+            intent: RenderingIntent::RelativeColorimetric,
+            gamut_warning: None,
+        }
+    }
+    /// Sets the rendering intent used when converting to the output device.
+    pub fn intent(mut self, intent: RenderingIntent) -> Self {
+        self.intent = intent;
+        self
+    }
+    /// Highlights content outside the gamut of the output device with the provided color.
+    pub fn gamut_warning(mut self, color: LDRColor) -> Self {
+        self.gamut_warning = Some(color);
+        self
+    }
+}
+
+fn linear_rgb(color: HDRColor) -> [f64; 3] {
+    let color = color.to_space(ColorSpace::Srgb);
+    [f64::from(color.r), f64::from(color.g), f64::from(color.b)]
+}
+
+fn from_linear_rgb(rgb: [f64; 3], alpha: f64) -> HDRColor {
+    HDRColor::rgba(rgb[0] as f32, rgb[1] as f32, rgb[2] as f32, alpha as f32)
+}
+
+fn from_encoded_rgb(rgb: [f64; 3], alpha: f64) -> HDRColor {
+    from_linear_rgb(
+        [
+            srgb_to_linear(rgb[0]),
+            srgb_to_linear(rgb[1]),
+            srgb_to_linear(rgb[2]),
+        ],
+        alpha,
+    )
+}
+
+fn normalize_hue(hue: f64) -> f64 {
+    let hue = hue % 360.;
+    if hue < 0. {
+        hue + 360.
+    } else {
+        hue
+    }
+}
+
+fn clamp_unit(value: f64) -> f64 {
+    value.clamp(0., 1.)
+}
+
+fn hue_and_chroma(rgb: [f64; 3]) -> (f64, f64, f64) {
+    let max = rgb[0].max(rgb[1]).max(rgb[2]);
+    let min = rgb[0].min(rgb[1]).min(rgb[2]);
+    let chroma = max - min;
+    let hue = if chroma == 0. {
+        0.
+    } else if max == rgb[0] {
+        60. * ((rgb[1] - rgb[2]) / chroma)
+    } else if max == rgb[1] {
+        60. * ((rgb[2] - rgb[0]) / chroma + 2.)
+    } else {
+        60. * ((rgb[0] - rgb[1]) / chroma + 4.)
+    };
+    (normalize_hue(hue), chroma, max)
+}
+
+fn from_hue_and_chroma(hue: f64, chroma: f64, offset: f64) -> [f64; 3] {
+    let sector = normalize_hue(hue) / 60.;
+    let x = chroma * (1. - (sector % 2. - 1.).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    [r + offset, g + offset, b + offset]
+}
+
+impl From<HDRColor> for HSLColor {
+    fn from(input: HDRColor) -> HSLColor {
+        let [r, g, b, alpha] = input.to_srgb_encoded();
+        let (h, chroma, max) = hue_and_chroma([r, g, b]);
+        let l = max - chroma / 2.;
+        let s = if l <= 0. || l >= 1. {
+            0.
+        } else {
+            chroma / (1. - (2. * l - 1.).abs())
+        };
+        HSLColor { h, s, l, alpha }
+    }
+}
+
+impl From<HSLColor> for HDRColor {
+    fn from(input: HSLColor) -> HDRColor {
+        let s = clamp_unit(input.s);
+        let l = clamp_unit(input.l);
+        let chroma = (1. - (2. * l - 1.).abs()) * s;
+        from_encoded_rgb(
+            from_hue_and_chroma(input.h, chroma, l - chroma / 2.),
+            input.alpha,
+        )
+    }
+}
+
+impl From<HDRColor> for HSVColor {
+    fn from(input: HDRColor) -> HSVColor {
+        let [r, g, b, alpha] = input.to_srgb_encoded();
+        let (h, chroma, v) = hue_and_chroma([r, g, b]);
+        let s = if v == 0. { 0. } else { chroma / v };
+        HSVColor { h, s, v, alpha }
+    }
+}
+
+impl From<HSVColor> for HDRColor {
+    fn from(input: HSVColor) -> HDRColor {
+        let v = clamp_unit(input.v);
+        let chroma = v * clamp_unit(input.s);
+        from_encoded_rgb(
+            from_hue_and_chroma(input.h, chroma, v - chroma),
+            input.alpha,
+        )
+    }
+}
+
+fn lab_forward(value: f64) -> f64 {
+    if value > LAB_EPSILON.powi(3) {
+        value.cbrt()
+    } else {
+        value / (3. * LAB_EPSILON.powi(2)) + 4. / 29.
+    }
+}
+
+fn lab_inverse(value: f64) -> f64 {
+    if value > LAB_EPSILON {
+        value.powi(3)
+    } else {
+        3. * LAB_EPSILON.powi(2) * (value - 4. / 29.)
+    }
+}
+
+impl From<HDRColor> for LabColor {
+    fn from(input: HDRColor) -> LabColor {
+        let xyz = super::multiply(ColorSpace::Srgb.rgb_to_xyz(), linear_rgb(input));
+        let fx = lab_forward(xyz[0] / LAB_WHITE[0]);
+        let fy = lab_forward(xyz[1] / LAB_WHITE[1]);
+        let fz = lab_forward(xyz[2] / LAB_WHITE[2]);
+        LabColor {
+            l: 116. * fy - 16.,
+            a: 500. * (fx - fy),
+            b: 200. * (fy - fz),
+            alpha: f64::from(input.a),
+        }
+    }
+}
+
+impl From<LabColor> for HDRColor {
+    fn from(input: LabColor) -> HDRColor {
+        let fy = (input.l + 16.) / 116.;
+        let fx = fy + input.a / 500.;
+        let fz = fy - input.b / 200.;
+        let xyz = [
+            lab_inverse(fx) * LAB_WHITE[0],
+            lab_inverse(fy) * LAB_WHITE[1],
+            lab_inverse(fz) * LAB_WHITE[2],
+        ];
+        from_linear_rgb(
+            super::multiply(ColorSpace::Srgb.xyz_to_rgb(), xyz),
+            input.alpha,
+        )
+    }
+}
+
+impl From<LabColor> for LChColor {
+    fn from(input: LabColor) -> LChColor {
+        LChColor {
+            l: input.l,
+            c: input.a.hypot(input.b),
+            h: normalize_hue(input.b.atan2(input.a) * 180. / PI),
+            alpha: input.alpha,
+        }
+    }
+}
+
+impl From<LChColor> for LabColor {
+    fn from(input: LChColor) -> LabColor {
+        let h = input.h * PI / 180.;
+        LabColor {
+            l: input.l,
+            a: input.c * h.cos(),
+            b: input.c * h.sin(),
+            alpha: input.alpha,
+        }
+    }
+}
+
+impl From<HDRColor> for LChColor {
+    fn from(input: HDRColor) -> LChColor {
+        LabColor::from(input).into()
+    }
+}
+
+impl From<LChColor> for HDRColor {
+    fn from(input: LChColor) -> HDRColor {
+        LabColor::from(input).into()
+    }
+}
+
+impl From<HDRColor> for OkLabColor {
+    fn from(input: HDRColor) -> OkLabColor {
+        let [r, g, b] = linear_rgb(input);
+        let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+        OkLabColor {
+            l: 0.210_454_255_3 * l + 0.793_617_785 * m - 0.004_072_046_8 * s,
+            a: 1.977_998_495_1 * l - 2.428_592_205 * m + 0.450_593_709_9 * s,
+            b: 0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766 * s,
+            alpha: f64::from(input.a),
+        }
+    }
+}
+
+impl From<OkLabColor> for HDRColor {
+    fn from(input: OkLabColor) -> HDRColor {
+        let l = (input.l + 0.396_337_777_4 * input.a + 0.215_803_757_3 * input.b).powi(3);
+        let m = (input.l - 0.105_561_345_8 * input.a - 0.063_854_172_8 * input.b).powi(3);
+        let s = (input.l - 0.089_484_177_5 * input.a - 1.291_485_548 * input.b).powi(3);
+        from_linear_rgb(
+            [
+                4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+                -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+                -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701 * s,
+            ],
+            input.alpha,
+        )
+    }
+}
+
+macro_rules! ldr_conversions {
+    ($($model:ty),*) => {
+        $(
+            impl From<LDRColor> for $model {
+                fn from(input: LDRColor) -> $model {
+                    HDRColor::from(input).into()
+                }
+            }
+
+            impl From<$model> for LDRColor {
+                fn from(input: $model) -> LDRColor {
+                    HDRColor::from(input).to_ldr()
+                }
+            }
+        )*
+    };
+}
+
+ldr_conversions!(HSLColor, HSVColor, LabColor, LChColor, OkLabColor);
+
+fn lerp(from: f64, to: f64, amount: f64) -> f64 {
+    from + (to - from) * amount
+}
+
+fn lerp_hue(from: f64, to: f64, amount: f64) -> f64 {
+    let mut delta = normalize_hue(to) - normalize_hue(from);
+    if delta > 180. {
+        delta -= 360.;
+    } else if delta < -180. {
+        delta += 360.;
+    }
+    normalize_hue(from + delta * amount)
+}
+
+fn lerp_rgb(from: [f64; 3], to: [f64; 3], amount: f64) -> [f64; 3] {
+    [
+        lerp(from[0], to[0], amount),
+        lerp(from[1], to[1], amount),
+        lerp(from[2], to[2], amount),
+    ]
+}
+
+impl LDRColor {
+    /// Returns the interpolation between this color and another color in the provided color model,
+    /// where an amount of zero yields this color and an amount of one yields the other.
+    pub fn mix(self, other: LDRColor, amount: f64, space: MixSpace) -> LDRColor {
+        let alpha = lerp(f64::from(self.a), f64::from(other.a), amount) / 255.;
+        let from = HDRColor::from(self);
+        let to = HDRColor::from(other);
+        let mixed = match space {
+            MixSpace::Srgb => {
+                let from = from.to_srgb_encoded();
+                let to = to.to_srgb_encoded();
+                from_encoded_rgb(
+                    lerp_rgb([from[0], from[1], from[2]], [to[0], to[1], to[2]], amount),
+                    alpha,
+                )
+            }
+            MixSpace::LinearSrgb => {
+                from_linear_rgb(lerp_rgb(linear_rgb(from), linear_rgb(to), amount), alpha)
+            }
+            MixSpace::HSL => {
+                let from = HSLColor::from(from);
+                let to = HSLColor::from(to);
+                HSLColor {
+                    h: match (from.s == 0., to.s == 0.) {
+                        (true, false) => to.h,
+                        (false, true) => from.h,
+                        _ => lerp_hue(from.h, to.h, amount),
+                    },
+                    s: lerp(from.s, to.s, amount),
+                    l: lerp(from.l, to.l, amount),
+                    alpha,
+                }
+                .into()
+            }
+            MixSpace::Lab => {
+                let from = LabColor::from(from);
+                let to = LabColor::from(to);
+                LabColor {
+                    l: lerp(from.l, to.l, amount),
+                    a: lerp(from.a, to.a, amount),
+                    b: lerp(from.b, to.b, amount),
+                    alpha,
+                }
+                .into()
+            }
+            MixSpace::LCh => {
+                let from = LChColor::from(from);
+                let to = LChColor::from(to);
+                let achromatic = |color: &LChColor| color.c < 1e-4;
+                LChColor {
+                    l: lerp(from.l, to.l, amount),
+                    c: lerp(from.c, to.c, amount),
+                    h: match (achromatic(&from), achromatic(&to)) {
+                        (true, false) => to.h,
+                        (false, true) => from.h,
+                        _ => lerp_hue(from.h, to.h, amount),
+                    },
+                    alpha,
+                }
+                .into()
+            }
+            MixSpace::OkLab => {
+                let from = OkLabColor::from(from);
+                let to = OkLabColor::from(to);
+                OkLabColor {
+                    l: lerp(from.l, to.l, amount),
+                    a: lerp(from.a, to.a, amount),
+                    b: lerp(from.b, to.b, amount),
+                    alpha,
+                }
+                .into()
+            }
+        };
+        mixed.to_ldr()
+    }
+    fn adjust_hsl<F>(self, adjust: F) -> LDRColor
+    where
+        F: FnOnce(&mut HSLColor),
+    {
+        let mut color = HSLColor::from(self);
+        adjust(&mut color);
+        let mut output = LDRColor::from(color);
+        output.a = self.a;
+        output
+    }
+    /// Increases the HSL lightness of the color by the provided amount between 0 and 1.
+    pub fn lighten(self, amount: f64) -> LDRColor {
+        self.adjust_hsl(|color| color.l = clamp_unit(color.l + amount))
+    }
+    /// Decreases the HSL lightness of the color by the provided amount between 0 and 1.
+    pub fn darken(self, amount: f64) -> LDRColor {
+        self.lighten(-amount)
+    }
+    /// Increases the HSL saturation of the color by the provided amount between 0 and 1.
+    pub fn saturate(self, amount: f64) -> LDRColor {
+        self.adjust_hsl(|color| color.s = clamp_unit(color.s + amount))
+    }
+    /// Decreases the HSL saturation of the color by the provided amount between 0 and 1.
+    pub fn desaturate(self, amount: f64) -> LDRColor {
+        self.saturate(-amount)
+    }
+    /// Returns the WCAG 2 relative luminance of the color, ignoring alpha.
+    pub fn relative_luminance(self) -> f64 {
+        let [r, g, b] = linear_rgb(self.into());
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+    /// Returns the WCAG 2 contrast ratio between this color and another, from 1 to 21.
+    pub fn contrast_ratio(self, other: LDRColor) -> f64 {
+        let first = self.relative_luminance();
+        let second = other.relative_luminance();
+        (first.max(second) + 0.05) / (first.min(second) + 0.05)
+    }
+}
+
+fn parse_hex(input: &str) -> Option<LDRColor> {
+    if !input.is_ascii() {
+        return None;
+    }
+    let digit = |index: usize, length: usize| {
+        let value = u8::from_str_radix(&input[index * length..(index + 1) * length], 16).ok()?;
+        Some(if length == 1 { value * 17 } else { value })
+    };
+    let length = match input.len() {
+        3 | 4 => 1,
+        6 | 8 => 2,
+        _ => return None,
+    };
+    let alpha = if input.len() / length == 4 {
+        digit(3, length)?
+    } else {
+        255
+    };
+    Some(LDRColor::rgba(
+        digit(0, length)?,
+        digit(1, length)?,
+        digit(2, length)?,
+        alpha,
+    ))
+}
+
+fn parse_number(input: &str, percent_scale: f64) -> Option<f64> {
+    match input.strip_suffix('%') {
+        Some(percentage) => f64::from_str(percentage)
+            .ok()
+            .map(|value| value / 100. * percent_scale),
+        None => f64::from_str(input).ok(),
+    }
+}
+
+fn parse_hue(input: &str) -> Option<f64> {
+    let units: [(&str, f64); 4] = [
+        ("deg", 1.),
+        ("grad", 0.9),
+        ("rad", 180. / PI),
+        ("turn", 360.),
+    ];
+    for (unit, scale) in units.iter() {
+        if let Some(value) = input.strip_suffix(unit) {
+            return f64::from_str(value).ok().map(|value| value * scale);
+        }
+    }
+    f64::from_str(input).ok()
+}
+
+fn parse_function(name: &str, arguments: &str) -> Option<LDRColor> {
+    let (channels, alpha) = match arguments.find('/') {
+        Some(index) => (&arguments[..index], Some(arguments[index + 1..].trim())),
+        None => (arguments, None),
+    };
+    let mut channels: Vec<&str> = if channels.contains(',') {
+        channels.split(',').map(str::trim).collect()
+    } else {
+        channels.split_whitespace().collect()
+    };
+    let alpha = match (alpha, channels.len()) {
+        (Some(alpha), 3) => Some(alpha),
+        (None, 4) => channels.pop(),
+        (None, 3) => None,
+        _ => return None,
+    };
+    let alpha = match alpha {
+        Some(alpha) => clamp_unit(parse_number(alpha, 1.)?),
+        None => 1.,
+    };
+    let color: HDRColor = match name {
+        "rgb" | "rgba" => from_encoded_rgb(
+            [
+                clamp_unit(parse_number(channels[0], 255.)? / 255.),
+                clamp_unit(parse_number(channels[1], 255.)? / 255.),
+                clamp_unit(parse_number(channels[2], 255.)? / 255.),
+            ],
+            alpha,
+        ),
+        "hsl" | "hsla" => HSLColor {
+            h: parse_hue(channels[0])?,
+            s: parse_number(channels[1], 1.)?,
+            l: parse_number(channels[2], 1.)?,
+            alpha,
+        }
+        .into(),
+        _ => return None,
+    };
+    Some(color.to_ldr())
+}
+
+impl FromStr for LDRColor {
+    type Err = Error;
+
+    /// Parses a CSS color in hex, `rgb()`, `rgba()`, `hsl()`, `hsla()` or named color syntax.
+    fn from_str(input: &str) -> Result<LDRColor, Error> {
+        let input = input.trim().to_ascii_lowercase();
+        let color = if let Some(hex) = input.strip_prefix('#') {
+            parse_hex(hex)
+        } else if let (Some(open), true) = (input.find('('), input.ends_with(')')) {
+            parse_function(input[..open].trim(), &input[open + 1..input.len() - 1])
+        } else {
+            named_color(&input)
+        };
+        color.ok_or_else(Error::color_parse)
+    }
+}
+
+/// Returns the CSS named color with the provided lowercase name.
+pub fn named_color(name: &str) -> Option<LDRColor> {
+    if name == "transparent" {
+        return Some(LDRColor::rgba(0, 0, 0, 0));
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|(_, value)| {
+            LDRColor::rgb(
+                (value >> 16) as u8,
+                (value >> 8 & 0xff) as u8,
+                (value & 0xff) as u8,
+            )
+        })
+}
+
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorProfile, HSLColor, LabColor, MixSpace, OkLabColor};
+    use crate::graphics::{LDRColor, ToHexLDRColor};
+
+    #[test]
+    fn hex_output_is_padded() {
+        assert_eq!(LDRColor::rgba(10, 11, 12, 255).to_hex_color(), "#0a0b0cff");
+    }
+
+    #[test]
+    fn css_parsing() {
+        let parse = |input: &str| input.parse::<LDRColor>().unwrap();
+        assert_eq!(parse("#0a0b0c"), LDRColor::rgb(10, 11, 12));
+        assert_eq!(parse("#F00a"), LDRColor::rgba(255, 0, 0, 170));
+        assert_eq!(parse("rgb(255, 128, 0)"), LDRColor::rgb(255, 128, 0));
+        assert_eq!(parse("rgba(0,0,255,0.5)"), LDRColor::rgba(0, 0, 255, 128));
+        assert_eq!(
+            parse("rgb(100% 0% 0% / 25%)"),
+            LDRColor::rgba(255, 0, 0, 64)
+        );
+        assert_eq!(parse("hsl(120, 100%, 50%)"), LDRColor::rgb(0, 255, 0));
+        assert_eq!(parse("hsl(0.5turn 100% 25%)"), LDRColor::rgb(0, 128, 128));
+        assert_eq!(parse(" RebeccaPurple "), LDRColor::rgb(102, 51, 153));
+        assert_eq!(parse("transparent"), LDRColor::rgba(0, 0, 0, 0));
+        assert!("#12345".parse::<LDRColor>().is_err());
+        assert!("rgb(1, 2)".parse::<LDRColor>().is_err());
+        assert!("notacolor".parse::<LDRColor>().is_err());
+    }
+
+    #[test]
+    fn model_round_trips() {
+        let color = LDRColor::rgb(34, 139, 34);
+        assert_eq!(LDRColor::from(HSLColor::from(color)), color);
+        assert_eq!(LDRColor::from(LabColor::from(color)), color);
+        assert_eq!(LDRColor::from(OkLabColor::from(color)), color);
+        let lab = LabColor::from(LDRColor::white());
+        assert!((lab.l - 100.).abs() < 1e-3 && lab.a.abs() < 1e-3 && lab.b.abs() < 1e-3);
+    }
+
+    #[test]
+    fn mixing_and_contrast() {
+        let black = LDRColor::black();
+        let white = LDRColor::white();
+        assert_eq!(
+            black.mix(white, 0.5, MixSpace::Srgb),
+            LDRColor::rgb(128, 128, 128)
+        );
+        assert_eq!(
+            black.mix(white, 0.5, MixSpace::LinearSrgb),
+            LDRColor::rgb(188, 188, 188)
+        );
+        assert_eq!(black.mix(white, 0., MixSpace::OkLab), black);
+        assert!((black.contrast_ratio(white) - 21.).abs() < 1e-6);
+        assert_eq!(LDRColor::rgb(255, 0, 0).lighten(0.5), white);
+        assert_eq!(
+            LDRColor::rgb(255, 0, 0).desaturate(1.),
+            LDRColor::rgb(128, 128, 128)
+        );
+    }
+
+    #[test]
+    fn profile_validation() {
+        let mut data = vec![0; 200];
+        data[3] = 160;
+        assert!(ColorProfile::from_icc(&data).is_err());
+        data[36..40].copy_from_slice(b"acsp");
+        assert_eq!(ColorProfile::from_icc(&data).unwrap().data().len(), 160);
+        data[2] = 1;
+        assert!(ColorProfile::from_icc(&data).is_err());
+        data[2] = 0;
+        data[3] = 100;
+        assert!(ColorProfile::from_icc(&data).is_err());
+    }
+}
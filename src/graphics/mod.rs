@@ -1,11 +1,18 @@
+use self::color::ColorProfile;
+use crate::{errors::Error, targets};
+
 use std::{
     any::Any,
     borrow::Cow,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
 };
 
+/// Provides tweening and keyframe animation driven by the ticks of a canvas.
+pub mod animation;
 /// Provides bindings to 2D vector graphic rendering functionality.
 pub mod canvas;
+/// Provides color model conversions, color manipulation helpers and CSS color parsing.
+pub mod color;
 /// Provides helper types that allow ergonomic construction and styling of 2D vector graphics.
 pub mod path;
 /// Contains types to help represent and construct styled text.
@@ -31,6 +38,10 @@ pub trait ImageRepresentation: Any + Sync + Send {
     fn from_texture(texture: Image<LDRColor, Texture2>) -> Self
     where
         Self: Sized;
+    /// Returns the ICC profile embedded in the image. Images without one are treated as sRGB.
+    fn color_profile(&self) -> Option<ColorProfile> {
+        None
+    }
 }
 
 impl Clone for Box<dyn ImageRepresentation> {
@@ -55,6 +66,9 @@ impl ImageRepresentation for Image<LDRColor, Texture2> {
     fn from_texture(texture: Image<LDRColor, Texture2>) -> Image<LDRColor, Texture2> {
         texture
     }
+    fn color_profile(&self) -> Option<ColorProfile> {
+        self.color_profile.clone()
+    }
 }
 
 /// Indicates that a type is a pixel format for image data.
@@ -144,7 +158,7 @@ impl LDRColor {
 impl ToHexLDRColor for LDRColor {
     fn to_hex_color(&self) -> Cow<'_, str> {
         Cow::from(format!(
-            "#{:x?}{:x?}{:x?}{:x?}",
+            "#{:02x}{:02x}{:02x}{:02x}",
             self.r, self.g, self.b, self.a
         ))
     }
@@ -152,6 +166,289 @@ impl ToHexLDRColor for LDRColor {
 
 impl PixelFormat for LDRColor {}
 
+/// An RGB color space with a D65 white point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    /// The sRGB/Rec.709 primaries used by most displays and the web.
+    #[default]
+    Srgb,
+    /// The DCI-P3 primaries used by wide-gamut displays.
+    DisplayP3,
+    /// The ITU-R BT.2020 primaries used for UHD and HDR video.
+    Rec2020,
+}
+
+impl ColorSpace {
+    /// Returns the CIE 1931 xy chromaticities of the red, green and blue primaries.
+    pub fn primaries(self) -> [(f64, f64); 3] {
+        match self {
+            ColorSpace::Srgb => [(0.64, 0.33), (0.30, 0.60), (0.15, 0.06)],
+            ColorSpace::DisplayP3 => [(0.680, 0.320), (0.265, 0.690), (0.150, 0.060)],
+            ColorSpace::Rec2020 => [(0.708, 0.292), (0.170, 0.797), (0.131, 0.046)],
+        }
+    }
+    /// Returns the CIE 1931 xy chromaticity of the white point.
+    pub fn white_point(self) -> (f64, f64) {
+        (0.3127, 0.3290)
+    }
+    fn rgb_to_xyz(self) -> [[f64; 3]; 3] {
+        match self {
+            ColorSpace::Srgb => [
+                [0.412_390_8, 0.357_584_3, 0.180_480_8],
+                [0.212_639_0, 0.715_168_7, 0.072_192_3],
+                [0.019_330_8, 0.119_194_8, 0.950_532_2],
+            ],
+            ColorSpace::DisplayP3 => [
+                [0.486_570_9, 0.265_667_7, 0.198_217_3],
+                [0.228_974_6, 0.691_738_5, 0.079_286_9],
+                [0.000_000_0, 0.045_113_4, 1.043_944_4],
+            ],
+            ColorSpace::Rec2020 => [
+                [0.636_958_0, 0.144_616_9, 0.168_881_0],
+                [0.262_700_2, 0.677_998_1, 0.059_301_7],
+                [0.000_000_0, 0.028_072_7, 1.060_985_1],
+            ],
+        }
+    }
+    fn xyz_to_rgb(self) -> [[f64; 3]; 3] {
+        match self {
+            ColorSpace::Srgb => [
+                [3.240_969_9, -1.537_383_2, -0.498_610_8],
+                [-0.969_243_6, 1.875_967_5, 0.041_555_1],
+                [0.055_630_1, -0.203_977_0, 1.056_971_5],
+            ],
+            ColorSpace::DisplayP3 => [
+                [2.493_496_9, -0.931_383_6, -0.402_710_8],
+                [-0.829_489_0, 1.762_664_1, 0.023_624_7],
+                [0.035_845_8, -0.076_172_4, 0.956_884_5],
+            ],
+            ColorSpace::Rec2020 => [
+                [1.716_651_2, -0.355_670_8, -0.253_366_3],
+                [-0.666_684_4, 1.616_481_2, 0.015_768_5],
+                [0.017_639_9, -0.042_770_6, 0.942_103_1],
+            ],
+        }
+    }
+}
+
+fn multiply(matrix: [[f64; 3]; 3], vector: [f64; 3]) -> [f64; 3] {
+    let mut output = [0.; 3];
+    for (row, value) in matrix.iter().zip(output.iter_mut()) {
+        *value = row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2];
+    }
+    output
+}
+
+fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// A floating-point color in linear light with straight alpha.
+///
+/// Channel values outside of the range 0 to 1 represent colors outside the gamut of the color space
+/// or brighter than its reference white.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HDRColor {
+    /// Linear red channel data.
+    pub r: f32,
+    /// Linear green channel data.
+    pub g: f32,
+    /// Linear blue channel data.
+    pub b: f32,
+    /// Alpha channel data between 0 and 1.
+    pub a: f32,
+    /// The color space of the channel data.
+    pub space: ColorSpace,
+}
+
+impl HDRColor {
+    /// Creates a new fully opaque linear sRGB color from the provided RGB values.
+    pub fn rgb(r: f32, g: f32, b: f32) -> HDRColor {
+        HDRColor::rgba(r, g, b, 1.)
+    }
+    /// Creates a new linear sRGB color from the provided RGBA values.
+    pub fn rgba(r: f32, g: f32, b: f32, a: f32) -> HDRColor {
+        HDRColor {
+            r,
+            g,
+            b,
+            a,
+            space: ColorSpace::Srgb,
+        }
+    }
+    /// Reinterprets the channel data as belonging to the provided color space without conversion.
+    pub fn with_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+    /// Sets the alpha channel.
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.a = alpha;
+        self
+    }
+    /// Converts the color to the provided color space. Out-of-gamut values are preserved.
+    pub fn to_space(self, space: ColorSpace) -> HDRColor {
+        if self.space == space {
+            return self;
+        }
+        let xyz = multiply(
+            self.space.rgb_to_xyz(),
+            [f64::from(self.r), f64::from(self.g), f64::from(self.b)],
+        );
+        let rgb = multiply(space.xyz_to_rgb(), xyz);
+        HDRColor {
+            r: rgb[0] as f32,
+            g: rgb[1] as f32,
+            b: rgb[2] as f32,
+            a: self.a,
+            space,
+        }
+    }
+    /// Returns the sRGB-encoded channel data of the color, with out-of-gamut values clamped.
+    pub fn to_srgb_encoded(self) -> [f64; 4] {
+        let color = self.to_space(ColorSpace::Srgb);
+        let encode = |value: f32| linear_to_srgb(f64::from(value).clamp(0., 1.));
+        [
+            encode(color.r),
+            encode(color.g),
+            encode(color.b),
+            f64::from(color.a).clamp(0., 1.),
+        ]
+    }
+    /// Converts the color to 8-bit sRGB, clamping values that fall outside of its range.
+    pub fn to_ldr(self) -> LDRColor {
+        let channels = self.to_srgb_encoded();
+        let quantize = |value: f64| (value * 255.).round() as u8;
+        LDRColor::rgba(
+            quantize(channels[0]),
+            quantize(channels[1]),
+            quantize(channels[2]),
+            quantize(channels[3]),
+        )
+    }
+}
+
+impl From<LDRColor> for HDRColor {
+    fn from(input: LDRColor) -> HDRColor {
+        let linearize = |value: u8| srgb_to_linear(f64::from(value) / 255.) as f32;
+        HDRColor::rgba(
+            linearize(input.r),
+            linearize(input.g),
+            linearize(input.b),
+            f32::from(input.a) / 255.,
+        )
+    }
+}
+
+impl From<HDRColor> for LDRColor {
+    fn from(input: HDRColor) -> LDRColor {
+        input.to_ldr()
+    }
+}
+
+impl PixelFormat for HDRColor {}
+
+impl ImageRepresentation for Image<HDRColor, Texture2> {
+    fn as_any(&self) -> Box<dyn Any> {
+        Box::new(self.clone())
+    }
+    fn get_size(&self) -> Vector2 {
+        (f64::from(self.format.width), f64::from(self.format.height)).into()
+    }
+    fn box_clone(&self) -> Box<dyn ImageRepresentation> {
+        Box::new(self.clone())
+    }
+    fn as_texture(&self) -> Image<LDRColor, Texture2> {
+        Image {
+            pixels: self.pixels.iter().map(|pixel| pixel.to_ldr()).collect(),
+            format: self.format,
+            color_profile: self.color_profile.clone(),
+        }
+    }
+    fn from_texture(texture: Image<LDRColor, Texture2>) -> Image<HDRColor, Texture2> {
+        Image {
+            pixels: texture.pixels.into_iter().map(HDRColor::from).collect(),
+            format: texture.format,
+            color_profile: texture.color_profile,
+        }
+    }
+    fn color_profile(&self) -> Option<ColorProfile> {
+        self.color_profile.clone()
+    }
+}
+
+/// A color in either of the supported precisions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    /// An 8-bit sRGB color.
+    LDR(LDRColor),
+    /// A floating-point linear-light color.
+    HDR(HDRColor),
+}
+
+impl Default for Color {
+    fn default() -> Color {
+        Color::LDR(LDRColor::default())
+    }
+}
+
+impl Color {
+    /// Converts the color to 8-bit sRGB, clamping high dynamic range values.
+    pub fn to_ldr(self) -> LDRColor {
+        match self {
+            Color::LDR(color) => color,
+            Color::HDR(color) => color.to_ldr(),
+        }
+    }
+    /// Converts the color to linear light without loss of precision.
+    pub fn to_hdr(self) -> HDRColor {
+        match self {
+            Color::LDR(color) => color.into(),
+            Color::HDR(color) => color,
+        }
+    }
+    /// Returns the sRGB-encoded channel data of the color as values between 0 and 1.
+    pub fn to_srgb_encoded(self) -> [f64; 4] {
+        match self {
+            Color::LDR(color) => [
+                f64::from(color.r) / 255.,
+                f64::from(color.g) / 255.,
+                f64::from(color.b) / 255.,
+                f64::from(color.a) / 255.,
+            ],
+            Color::HDR(color) => color.to_srgb_encoded(),
+        }
+    }
+    /// Returns a CSS-compatible rgba color string of the 8-bit sRGB conversion of the color.
+    pub fn to_rgba_color(self) -> Cow<'static, str> {
+        Cow::from(self.to_ldr().to_rgba_color().into_owned())
+    }
+}
+
+impl From<LDRColor> for Color {
+    fn from(input: LDRColor) -> Color {
+        Color::LDR(input)
+    }
+}
+
+impl From<HDRColor> for Color {
+    fn from(input: HDRColor) -> Color {
+        Color::HDR(input)
+    }
+}
+
 /// Indicates that a type is an organizational format for image data.
 pub trait ImageFormat {}
 
@@ -173,6 +470,37 @@ pub struct Image<T: PixelFormat, U: ImageFormat> {
     pub pixels: Vec<T>,
     /// Format of this image.
     pub format: U,
+    pub(crate) color_profile: Option<ColorProfile>,
+}
+
+impl<T: PixelFormat, U: ImageFormat> Image<T, U> {
+    /// Creates an image from pixel data. Images without an embedded profile are treated as sRGB.
+    pub fn new(pixels: Vec<T>, format: U) -> Self {
+        Image {
+            pixels,
+            format,
+            color_profile: None,
+        }
+    }
+    /// Embeds the ICC profile describing the colors of the pixel data.
+    pub fn with_color_profile(mut self, profile: ColorProfile) -> Self {
+        self.color_profile = Some(profile);
+        self
+    }
+}
+
+impl Image<LDRColor, Texture2> {
+    /// Decodes a PNG image along with its embedded ICC profile. Not supported on the web, where images are only decoded asynchronously.
+    pub fn from_png(data: &'_ [u8]) -> Result<Image<LDRColor, Texture2>, Error> {
+        #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+        {
+            let _ = data;
+            return Err(Error::unsupported());
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+        return targets::native::graphics::decode_png(data).ok_or_else(Error::image_decode);
+    }
 }
 
 /// A 2-dimensional cartesian vector or point
@@ -437,3 +765,28 @@ impl From<(f64, f64)> for Transform2 {
         Vector2::from(input).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorSpace, HDRColor, LDRColor};
+
+    #[test]
+    fn ldr_round_trip() {
+        for value in 0..=255 {
+            let color = LDRColor::rgba(value, 255 - value, value / 2, value);
+            assert_eq!(HDRColor::from(color).to_ldr(), color);
+        }
+    }
+
+    #[test]
+    fn color_space_round_trip() {
+        let color = HDRColor::rgb(0.2, 0.5, 0.8);
+        let converted = color
+            .to_space(ColorSpace::Rec2020)
+            .to_space(ColorSpace::DisplayP3)
+            .to_space(ColorSpace::Srgb);
+        assert!((converted.r - color.r).abs() < 1e-4);
+        assert!((converted.g - color.g).abs() < 1e-4);
+        assert!((converted.b - color.b).abs() < 1e-4);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::graphics::{ImageRepresentation, LDRColor, Rect, Vector2};
+use crate::graphics::{Color, HDRColor, ImageRepresentation, LDRColor, Rect, Vector2};
 
 use crate::errors::Error;
 
@@ -27,17 +27,23 @@ pub struct GradientStop {
     /// Zero represents the start of the gradient; one represents the end.
     pub offset: f64,
     /// The color of the stop.
-    pub color: LDRColor,
+    pub color: Color,
 }
 
 impl GradientStop {
     /// Creates a new gradient stop with the provided offset and color data.
-    pub fn new(offset: f64, color: LDRColor) -> Result<Self, Error> {
+    pub fn new<T>(offset: f64, color: T) -> Result<Self, Error>
+    where
+        T: Into<Color>,
+    {
         if offset > 1.0 || offset < 0.0 {
             return Err(Error::color_stop());
         }
 
-        Ok(GradientStop { offset, color })
+        Ok(GradientStop {
+            offset,
+            color: color.into(),
+        })
     }
 }
 
@@ -114,7 +120,7 @@ pub struct RadialGradient {
 #[derive(Clone)]
 pub enum Texture {
     /// A solid color texture.
-    Solid(LDRColor),
+    Solid(Color),
     /// A linear gradient texture.
     LinearGradient(LinearGradient),
     /// A radial gradient texture.
@@ -123,9 +129,21 @@ pub enum Texture {
     Image(Box<dyn ImageRepresentation>),
 }
 
+impl From<Color> for Texture {
+    fn from(color: Color) -> Texture {
+        Texture::Solid(color)
+    }
+}
+
 impl From<LDRColor> for Texture {
     fn from(color: LDRColor) -> Texture {
-        Texture::Solid(color)
+        Texture::Solid(color.into())
+    }
+}
+
+impl From<HDRColor> for Texture {
+    fn from(color: HDRColor) -> Texture {
+        Texture::Solid(color.into())
     }
 }
 
@@ -1,4 +1,11 @@
-use crate::graphics::LDRColor;
+use crate::{
+    errors::Error,
+    graphics::{
+        path::{Shadow, Stroke},
+        Color, LDRColor,
+    },
+};
+use std::fmt;
 
 /// A font face.
 #[derive(Clone, Copy, Debug)]
@@ -14,26 +21,28 @@ impl Default for Font {
 }
 
 /// Specifies the weight of a font.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Weight {
-    /// Hairline weight.
+    /// Hairline weight (100).
     Hairline,
-    /// Light weight.
-    Light,
-    /// Thin weight.
+    /// Thin weight (200).
     Thin,
-    /// Normal font weight.
+    /// Light weight (300).
+    Light,
+    /// Normal font weight (400).
     Normal,
-    /// Standard bold font weight.
-    Bold,
-    /// A heavy/black font weight.
-    Heavy,
-    /// A slightly bold font weight.
+    /// A slightly bold font weight (500).
     Medium,
-    /// An extra-bold font weight.
-    ExtraBold,
-    /// A somewhat bold font weight.
+    /// A somewhat bold font weight (600).
     SemiBold,
+    /// Standard bold font weight (700).
+    Bold,
+    /// An extra-bold font weight (800).
+    ExtraBold,
+    /// A heavy/black font weight (900).
+    Heavy,
+    /// An arbitrary numeric weight between 1 and 1000, as used by variable fonts.
+    Numeric(u16),
 }
 
 impl Default for Weight {
@@ -42,6 +51,102 @@ impl Default for Weight {
     }
 }
 
+impl Weight {
+    /// Returns the numeric value of the weight, clamped to the range 1 to 1000.
+    pub fn value(self) -> u16 {
+        match self {
+            Weight::Hairline => 100,
+            Weight::Thin => 200,
+            Weight::Light => 300,
+            Weight::Normal => 400,
+            Weight::Medium => 500,
+            Weight::SemiBold => 600,
+            Weight::Bold => 700,
+            Weight::ExtraBold => 800,
+            Weight::Heavy => 900,
+            Weight::Numeric(weight) => weight.clamp(1, 1000),
+        }
+    }
+}
+
+impl From<u16> for Weight {
+    fn from(input: u16) -> Weight {
+        Weight::Numeric(input)
+    }
+}
+
+/// Specifies the width of a font face.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Stretch {
+    /// Ultra-condensed width (50%).
+    UltraCondensed,
+    /// Extra-condensed width (62.5%).
+    ExtraCondensed,
+    /// Condensed width (75%).
+    Condensed,
+    /// Semi-condensed width (87.5%).
+    SemiCondensed,
+    /// Normal width.
+    #[default]
+    Normal,
+    /// Semi-expanded width (112.5%).
+    SemiExpanded,
+    /// Expanded width (125%).
+    Expanded,
+    /// Extra-expanded width (150%).
+    ExtraExpanded,
+    /// Ultra-expanded width (200%).
+    UltraExpanded,
+}
+
+/// An OpenType font feature setting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Feature {
+    /// The four-character feature tag, e.g. `tnum`.
+    pub tag: [u8; 4],
+    /// The feature value. Zero disables the feature and one enables it, alternate-selecting features accept larger values.
+    pub value: u32,
+}
+
+impl Feature {
+    /// Creates a new feature setting with the given tag and value.
+    pub fn new(tag: [u8; 4], value: u32) -> Self {
+        Feature { tag, value }
+    }
+    /// Enables the feature with the given tag.
+    pub fn enable(tag: [u8; 4]) -> Self {
+        Feature::new(tag, 1)
+    }
+    /// Disables the feature with the given tag.
+    pub fn disable(tag: [u8; 4]) -> Self {
+        Feature::new(tag, 0)
+    }
+    /// Fixed-width numerals that don't shift when digits change.
+    pub fn tabular_numerals() -> Self {
+        Feature::enable(*b"tnum")
+    }
+    /// Toggles standard ligatures.
+    pub fn ligatures(enabled: bool) -> Self {
+        Feature::new(*b"liga", enabled as u32)
+    }
+    /// Enables the numbered stylistic set. Valid sets are 1 through 20.
+    pub fn stylistic_set(set: u8) -> Self {
+        let set = set.clamp(1, 20);
+        Feature::enable([b's', b's', b'0' + set / 10, b'0' + set % 10])
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            r#""{}" {}"#,
+            String::from_utf8_lossy(&self.tag),
+            self.value
+        )
+    }
+}
+
 /// Specifies the type of text wrap used.
 #[derive(Clone, Copy, Debug)]
 pub enum Wrap {
@@ -52,14 +157,114 @@ pub enum Wrap {
 }
 
 /// Specifices the alignment of text.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Align {
     /// Center-justification.
     Center,
-    /// Left-justification.
+    /// Justification to the start edge, i.e. the left edge of left-to-right text.
     Start,
-    /// Right-justification.
+    /// Justification to the end edge, i.e. the right edge of left-to-right text.
     End,
+    /// Full justification. Wrapped lines are stretched to the wrap width and the
+    /// final line of each paragraph is justified to the start edge.
+    Justify,
+}
+
+/// Specifies the base direction of text.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Direction {
+    /// Left-to-right text such as Latin or Cyrillic.
+    Ltr,
+    /// Right-to-left text such as Arabic or Hebrew.
+    Rtl,
+    /// Direction determined by the first strongly directional character of the content.
+    #[default]
+    Auto,
+}
+
+impl Direction {
+    /// Resolves [Direction::Auto] to a concrete direction for the provided content.
+    ///
+    /// Content without any strongly directional characters resolves to [Direction::Ltr].
+    pub fn resolve(self, content: &'_ str) -> Direction {
+        match self {
+            Direction::Auto => content
+                .chars()
+                .find_map(|character| {
+                    if is_strong_rtl(character) {
+                        Some(Direction::Rtl)
+                    } else if character.is_alphabetic() {
+                        Some(Direction::Ltr)
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(Direction::Ltr),
+            direction => direction,
+        }
+    }
+}
+
+// Characters of the R and AL bidi classes as of Unicode 14, including unassigned code points that default to them.
+// Digits and combining marks in right-to-left scripts are weak or neutral and do not set the direction.
+fn is_strong_rtl(character: char) -> bool {
+    matches!(
+        u32::from(character),
+        0x0590
+        | 0x05BE
+        | 0x05C0
+        | 0x05C3
+        | 0x05C6
+        | 0x05C8..=0x05FF
+        | 0x0608
+        | 0x060B
+        | 0x060D
+        | 0x061B..=0x064A
+        | 0x066D..=0x066F
+        | 0x0671..=0x06D5
+        | 0x06E5..=0x06E6
+        | 0x06EE..=0x06EF
+        | 0x06FA..=0x0710
+        | 0x0712..=0x072F
+        | 0x074B..=0x07A5
+        | 0x07B1..=0x07EA
+        | 0x07F4..=0x07F5
+        | 0x07FA..=0x07FC
+        | 0x07FE..=0x0815
+        | 0x081A
+        | 0x0824
+        | 0x0828
+        | 0x082E..=0x0858
+        | 0x085C..=0x088F
+        | 0x0892..=0x0897
+        | 0x08A0..=0x08C9
+        | 0x200F
+        | 0xFB1D
+        | 0xFB1F..=0xFB28
+        | 0xFB2A..=0xFD3D
+        | 0xFD50..=0xFDCE
+        | 0xFDF0..=0xFDFC
+        | 0xFE70..=0xFEFE
+        | 0x1_0800..=0x1_091E
+        | 0x1_0920..=0x1_0A00
+        | 0x1_0A04
+        | 0x1_0A07..=0x1_0A0B
+        | 0x1_0A10..=0x1_0A37
+        | 0x1_0A3B..=0x1_0A3E
+        | 0x1_0A40..=0x1_0AE4
+        | 0x1_0AE7..=0x1_0B38
+        | 0x1_0B40..=0x1_0D23
+        | 0x1_0D28..=0x1_0D2F
+        | 0x1_0D3A..=0x1_0E5F
+        | 0x1_0E7F..=0x1_0EAA
+        | 0x1_0EAD..=0x1_0F45
+        | 0x1_0F51..=0x1_0F81
+        | 0x1_0F86..=0x1_0FFF
+        | 0x1_E800..=0x1_E8CF
+        | 0x1_E8D7..=0x1_E943
+        | 0x1_E94B..=0x1_EEEF
+        | 0x1_EEF2..=0x1_EFFF
+    )
 }
 
 /// Specifies the origin of text.
@@ -73,6 +278,76 @@ pub enum Origin {
     Middle,
 }
 
+/// Specifies the position of a decoration line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DecorationLine {
+    /// A line below the alphabetic baseline.
+    Underline,
+    /// A line above the ascenders of the text.
+    Overline,
+    /// A line through the middle of the lowercase glyphs.
+    Strikethrough,
+}
+
+/// A line drawn across or alongside rendered text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Decoration {
+    /// The position of the line.
+    pub line: DecorationLine,
+    /// The color of the line. If this is `None` the color of the text is used.
+    pub color: Option<LDRColor>,
+    /// The thickness of the line in pixels. If this is `None` a thickness derived from the font is used.
+    pub thickness: Option<f64>,
+}
+
+impl Decoration {
+    /// Creates a new decoration with the text color and a font-derived thickness.
+    pub fn new(line: DecorationLine) -> Self {
+        Decoration {
+            line,
+            color: None,
+            thickness: None,
+        }
+    }
+    /// Sets the color of the line.
+    pub fn color(mut self, color: LDRColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+    /// Sets the thickness of the line.
+    pub fn thickness(mut self, thickness: f64) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+}
+
+impl From<DecorationLine> for Decoration {
+    fn from(input: DecorationLine) -> Decoration {
+        Decoration::new(input)
+    }
+}
+
+/// Typographic metrics of a font face at a particular size and weight.
+///
+/// All values are in pixels. Vertical positions are measured from the alphabetic baseline.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FontMetrics {
+    /// The distance from the baseline to the top of the font bounding box.
+    pub ascent: f64,
+    /// The distance from the baseline to the bottom of the font bounding box.
+    pub descent: f64,
+    /// The height of capital letters above the baseline.
+    pub cap_height: f64,
+    /// The height of lowercase letters without ascenders above the baseline.
+    pub x_height: f64,
+    /// The distance from the baseline to the top edge of the underline. Positive values are below the baseline.
+    pub underline_position: f64,
+    /// The thickness of the underline.
+    pub underline_thickness: f64,
+    /// The approximate average advance width of a character.
+    pub average_character_width: f64,
+}
+
 /// Provides an abstraction for representing text content.
 #[derive(Clone, Debug)]
 pub struct Text {
@@ -83,23 +358,37 @@ pub struct Text {
     /// The font size in pixels.
     pub size: f64,
     /// The color of the rendered text.
-    pub color: LDRColor,
+    pub color: Color,
     /// Whether the text is styled as oblique/italic.
     pub italic: bool,
     /// The maximum width or wrap width of the text.
     pub max_width: Option<f64>,
     /// The justification or alignment style of the text.
     pub align: Align,
+    /// The base direction of the text.
+    pub direction: Direction,
     /// The line height in pixels.
     pub line_height: f64,
     /// The type of text wrap used.
     pub wrap: Wrap,
     /// The font weight used.
     pub weight: Weight,
+    /// The font width used.
+    pub stretch: Stretch,
+    /// Whether lowercase letters are rendered as small capitals.
+    pub small_caps: bool,
+    /// The OpenType features applied when shaping the text. Ignored on the web, as canvas text rendering does not support font features.
+    pub features: Vec<Feature>,
     /// The letter spacing of the text.
     pub letter_spacing: f64,
     /// The origin of the rendered text.
     pub origin: Origin,
+    /// The decoration lines drawn with the text.
+    pub decorations: Vec<Decoration>,
+    /// The outline stroke of the glyphs.
+    pub stroke: Option<Stroke>,
+    /// The associated drop shadows.
+    pub shadows: Vec<Shadow>,
 }
 
 impl Text {
@@ -112,8 +401,11 @@ impl Text {
         self
     }
     /// Sets the color of the text.
-    pub fn with_color(mut self, color: LDRColor) -> Self {
-        self.color = color;
+    pub fn with_color<T>(mut self, color: T) -> Self
+    where
+        T: Into<Color>,
+    {
+        self.color = color.into();
         self
     }
     /// Makes the text italic.
@@ -151,16 +443,58 @@ impl Text {
         self.align = Align::Center;
         self
     }
-    /// Sets the text alignment to be right-justified.
+    /// Sets the text alignment to be justified to the end edge.
     pub fn justify_end(mut self) -> Self {
         self.align = Align::End;
         self
     }
+    /// Sets the text alignment to be fully justified.
+    pub fn justify(mut self) -> Self {
+        self.align = Align::Justify;
+        self
+    }
+    /// Sets the base direction of the text.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
     /// Sets the font weight.
-    pub fn with_weight(mut self, weight: Weight) -> Self {
-        self.weight = weight;
+    pub fn with_weight<T>(mut self, weight: T) -> Self
+    where
+        T: Into<Weight>,
+    {
+        self.weight = weight.into();
+        self
+    }
+    /// Sets the font width.
+    pub fn with_stretch(mut self, stretch: Stretch) -> Self {
+        self.stretch = stretch;
         self
     }
+    /// Renders lowercase letters as small capitals.
+    pub fn small_caps(mut self) -> Self {
+        self.small_caps = true;
+        self
+    }
+    /// Applies the provided OpenType feature, replacing any existing setting with the same tag. Not supported on the web, where canvas text cannot select font features.
+    pub fn with_feature(mut self, feature: Feature) -> Result<Self, Error> {
+        #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+        {
+            let _ = (&mut self, feature);
+            return Err(Error::unsupported());
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+        {
+            self.features.retain(|existing| existing.tag != feature.tag);
+            self.features.push(feature);
+            Ok(self)
+        }
+    }
+    /// Uses fixed-width numerals. Not supported on the web.
+    pub fn tabular_numerals(self) -> Result<Self, Error> {
+        self.with_feature(Feature::tabular_numerals())
+    }
     /// Sets text origin to be the baseline start.
     pub fn with_baseline_origin(mut self) -> Self {
         self.origin = Origin::Baseline;
@@ -171,6 +505,36 @@ impl Text {
         self.origin = Origin::Middle;
         self
     }
+    /// Adds the provided decoration line.
+    pub fn with_decoration<T>(mut self, decoration: T) -> Self
+    where
+        T: Into<Decoration>,
+    {
+        self.decorations.push(decoration.into());
+        self
+    }
+    /// Underlines the text.
+    pub fn underline(self) -> Self {
+        self.with_decoration(DecorationLine::Underline)
+    }
+    /// Overlines the text.
+    pub fn overline(self) -> Self {
+        self.with_decoration(DecorationLine::Overline)
+    }
+    /// Strikes through the text.
+    pub fn strikethrough(self) -> Self {
+        self.with_decoration(DecorationLine::Strikethrough)
+    }
+    /// Outlines the glyphs using the provided style information.
+    pub fn stroke(mut self, stroke: Stroke) -> Self {
+        self.stroke = Some(stroke);
+        self
+    }
+    /// Shadows the text using the provided style information.
+    pub fn shadow(mut self, shadow: Shadow) -> Self {
+        self.shadows.push(shadow);
+        self
+    }
 }
 
 impl Default for Text {
@@ -179,15 +543,54 @@ impl Default for Text {
             font: Font::SystemFont,
             content: "".to_owned(),
             size: 15.,
-            color: LDRColor::black(),
+            color: LDRColor::black().into(),
             italic: false,
             max_width: None,
             align: Align::Start,
+            direction: Direction::Auto,
             letter_spacing: 0.,
             line_height: 26.,
             wrap: Wrap::None,
             origin: Origin::Top,
             weight: Weight::Normal,
+            stretch: Stretch::Normal,
+            small_caps: false,
+            features: vec![],
+            decorations: vec![],
+            stroke: None,
+            shadows: vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Direction, Feature, Text, Weight};
+
+    #[test]
+    fn direction_resolution() {
+        assert_eq!(Direction::Auto.resolve("שלום world"), Direction::Rtl);
+        assert_eq!(Direction::Auto.resolve("123 مرحبا"), Direction::Rtl);
+        assert_eq!(Direction::Auto.resolve("hello עולם"), Direction::Ltr);
+        assert_eq!(Direction::Auto.resolve("123"), Direction::Ltr);
+        assert_eq!(Direction::Auto.resolve("١٢٣ hello"), Direction::Ltr);
+        assert_eq!(Direction::Auto.resolve("\u{05B0}hello"), Direction::Ltr);
+        assert_eq!(Direction::Auto.resolve("\u{200F}hello"), Direction::Rtl);
+        assert_eq!(Direction::Rtl.resolve("hello"), Direction::Rtl);
+    }
+
+    #[test]
+    fn feature_settings() {
+        assert_eq!(Feature::tabular_numerals().to_string(), r#""tnum" 1"#);
+        assert_eq!(Feature::ligatures(false).to_string(), r#""liga" 0"#);
+        assert_eq!(Feature::stylistic_set(3).to_string(), r#""ss03" 1"#);
+        assert_eq!(Feature::stylistic_set(20).to_string(), r#""ss20" 1"#);
+        assert_eq!(Weight::from(1200).value(), 1000);
+        assert_eq!(Weight::Numeric(0).value(), 1);
+        let text = Text::new("0")
+            .with_feature(Feature::disable(*b"tnum"))
+            .and_then(Text::tabular_numerals)
+            .unwrap();
+        assert_eq!(text.features, vec![Feature::tabular_numerals()]);
+    }
+}
@@ -0,0 +1,233 @@
+use crate::errors::Error;
+use crate::targets;
+
+use std::collections::{HashMap, HashSet};
+
+/// An identifier for a connected gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub(crate) u64);
+
+/// A gamepad button using the standard gamepad layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Button {
+    /// The bottom face button, e.g. A on Xbox or Cross on PlayStation controllers.
+    South,
+    /// The right face button, e.g. B on Xbox or Circle on PlayStation controllers.
+    East,
+    /// The left face button, e.g. X on Xbox or Square on PlayStation controllers.
+    West,
+    /// The top face button, e.g. Y on Xbox or Triangle on PlayStation controllers.
+    North,
+    /// The left shoulder button.
+    LeftBumper,
+    /// The right shoulder button.
+    RightBumper,
+    /// The left trigger when reported as a digital button.
+    LeftTrigger,
+    /// The right trigger when reported as a digital button.
+    RightTrigger,
+    /// The select, back or share button.
+    Select,
+    /// The start, forward or options button.
+    Start,
+    /// The left stick when pressed down.
+    LeftStick,
+    /// The right stick when pressed down.
+    RightStick,
+    /// The up button of the directional pad.
+    DPadUp,
+    /// The down button of the directional pad.
+    DPadDown,
+    /// The left button of the directional pad.
+    DPadLeft,
+    /// The right button of the directional pad.
+    DPadRight,
+    /// The central guide or home button.
+    Mode,
+    /// A button outside of the standard layout identified by a device-specific code.
+    Other(u16),
+}
+
+/// A gamepad axis using the standard gamepad layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    /// The horizontal axis of the left stick, from -1 at the left to 1 at the right.
+    LeftStickX,
+    /// The vertical axis of the left stick, from -1 at the top to 1 at the bottom.
+    LeftStickY,
+    /// The horizontal axis of the right stick, from -1 at the left to 1 at the right.
+    RightStickX,
+    /// The vertical axis of the right stick, from -1 at the top to 1 at the bottom.
+    RightStickY,
+    /// The left analog trigger, from 0 when released to 1 when fully pressed.
+    LeftTrigger,
+    /// The right analog trigger, from 0 when released to 1 when fully pressed.
+    RightTrigger,
+    /// An axis outside of the standard layout identified by a device-specific code.
+    Other(u16),
+}
+
+/// A gamepad event.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A gamepad was connected.
+    Connect {
+        /// The connected gamepad.
+        gamepad: GamepadId,
+        /// The name of the gamepad as reported by the device.
+        name: String,
+    },
+    /// A gamepad was disconnected.
+    Disconnect(GamepadId),
+    /// A button was pressed.
+    Down {
+        /// The gamepad the button belongs to.
+        gamepad: GamepadId,
+        /// The pressed button.
+        button: Button,
+    },
+    /// A button was released.
+    Up {
+        /// The gamepad the button belongs to.
+        gamepad: GamepadId,
+        /// The released button.
+        button: Button,
+    },
+    /// An axis changed value after dead zone handling.
+    Axis {
+        /// The gamepad the axis belongs to.
+        gamepad: GamepadId,
+        /// The changed axis.
+        axis: Axis,
+        /// The new value of the axis.
+        value: f64,
+    },
+}
+
+/// A dual-motor rumble effect.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rumble {
+    /// The magnitude of the low-frequency motor between zero and one.
+    pub strong: f64,
+    /// The magnitude of the high-frequency motor between zero and one.
+    pub weak: f64,
+    /// The duration of the effect in milliseconds.
+    pub duration: u32,
+}
+
+/// Plays a rumble effect on a connected gamepad, failing if the gamepad or target does not support rumble.
+pub fn rumble(gamepad: GamepadId, effect: Rumble) -> Result<(), Error> {
+    #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+    return targets::web::input::gamepad::rumble(gamepad, effect);
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    return targets::native::input::gamepad::rumble(gamepad, effect);
+}
+
+/// Maps an axis value into the range outside of the dead zone, so that values within the dead zone become zero and the remainder is rescaled to reach the full range.
+pub(crate) fn apply_dead_zone(value: f64, dead_zone: f64) -> f64 {
+    let magnitude = value.abs();
+    if magnitude <= dead_zone {
+        0.
+    } else {
+        value.signum() * ((magnitude - dead_zone) / (1. - dead_zone)).min(1.)
+    }
+}
+
+/// Tracks the state of a gamepad to produce events only when a button or axis changes.
+#[derive(Debug)]
+pub(crate) struct Tracker {
+    gamepad: GamepadId,
+    dead_zone: f64,
+    pressed: HashSet<Button>,
+    axes: HashMap<Axis, f64>,
+}
+
+impl Tracker {
+    pub(crate) fn new(gamepad: GamepadId, dead_zone: f64) -> Tracker {
+        Tracker {
+            gamepad,
+            dead_zone,
+            pressed: HashSet::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn button(&mut self, button: Button, pressed: bool) -> Option<Event> {
+        let gamepad = self.gamepad;
+        if pressed && self.pressed.insert(button) {
+            Some(Event::Down { gamepad, button })
+        } else if !pressed && self.pressed.remove(&button) {
+            Some(Event::Up { gamepad, button })
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn axis(&mut self, axis: Axis, value: f64) -> Option<Event> {
+        let value = apply_dead_zone(value, self.dead_zone);
+        let previous = self.axes.insert(axis, value).unwrap_or(0.);
+        if (previous - value).abs() > f64::EPSILON {
+            Some(Event::Axis {
+                gamepad: self.gamepad,
+                axis,
+                value,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dead_zone() {
+        assert_eq!(apply_dead_zone(0.05, 0.1), 0.);
+        assert_eq!(apply_dead_zone(-0.1, 0.1), 0.);
+        assert!((apply_dead_zone(0.55, 0.1) - 0.5).abs() < 1e-9);
+        assert!((apply_dead_zone(-0.55, 0.1) + 0.5).abs() < 1e-9);
+        assert_eq!(apply_dead_zone(1., 0.1), 1.);
+    }
+
+    #[test]
+    fn tracker_changes() {
+        let gamepad = GamepadId(3);
+        let mut tracker = Tracker::new(gamepad, 0.1);
+        assert_eq!(
+            tracker.button(Button::South, true),
+            Some(Event::Down {
+                gamepad,
+                button: Button::South
+            })
+        );
+        assert_eq!(tracker.button(Button::South, true), None);
+        assert_eq!(
+            tracker.button(Button::South, false),
+            Some(Event::Up {
+                gamepad,
+                button: Button::South
+            })
+        );
+        assert_eq!(tracker.axis(Axis::LeftStickX, 0.05), None);
+        assert_eq!(
+            tracker.axis(Axis::LeftStickX, 1.),
+            Some(Event::Axis {
+                gamepad,
+                axis: Axis::LeftStickX,
+                value: 1.
+            })
+        );
+        assert_eq!(tracker.axis(Axis::LeftStickX, 1.), None);
+        assert_eq!(
+            tracker.axis(Axis::LeftStickX, 0.02),
+            Some(Event::Axis {
+                gamepad,
+                axis: Axis::LeftStickX,
+                value: 0.
+            })
+        );
+    }
+}
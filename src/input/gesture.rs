@@ -0,0 +1,694 @@
+use crate::graphics::Vector2;
+use crate::input::{
+    mouse::{self, Button},
+    pointer::{self, Phase},
+    windowing, Event, Input, Kind,
+};
+use crate::targets;
+
+use futures::{task, Async, Poll, Stream};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    f64::consts::PI,
+    fmt,
+};
+
+/// Thresholds used to recognize gestures. Times are in milliseconds and distances are in viewport units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    /// The longest interval between consecutive taps that counts them as a multi-tap.
+    pub multi_tap_interval: f64,
+    /// The distance a press may move before it is considered a drag.
+    pub drag_threshold: f64,
+    /// The duration a press must be held without dragging to be considered a long press.
+    pub long_press_duration: f64,
+    /// The speed in units per second above which the end of a drag is considered a fling.
+    pub fling_velocity: f64,
+    /// The period of recent movement over which the velocity of a drag is measured.
+    pub velocity_window: f64,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            multi_tap_interval: 300.,
+            drag_threshold: 8.,
+            long_press_duration: 500.,
+            fling_velocity: 500.,
+            velocity_window: 100.,
+        }
+    }
+}
+
+/// A recognized gesture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    /// A press released without dragging. The count is the number of consecutive taps, e.g. 2 for a double click.
+    Tap {
+        /// The position of the tap.
+        position: Vector2,
+        /// The number of consecutive taps.
+        count: u32,
+        /// The mouse button used, or none for touch and pen input.
+        button: Option<Button>,
+    },
+    /// A press held without dragging.
+    LongPress {
+        /// The position of the press.
+        position: Vector2,
+        /// The mouse button used, or none for touch and pen input.
+        button: Option<Button>,
+    },
+    /// A press moved beyond the drag threshold.
+    DragStart {
+        /// The position at which the press began.
+        position: Vector2,
+        /// The mouse button used, or none for touch and pen input.
+        button: Option<Button>,
+    },
+    /// A drag moved.
+    DragMove {
+        /// The current position of the drag.
+        position: Vector2,
+        /// The movement since the last event of the drag.
+        delta: Vector2,
+    },
+    /// A drag ended.
+    DragEnd {
+        /// The final position of the drag.
+        position: Vector2,
+        /// The velocity of the drag when released in units per second.
+        velocity: Vector2,
+    },
+    /// A drag was released above the fling velocity.
+    Fling {
+        /// The velocity of the drag when released in units per second.
+        velocity: Vector2,
+    },
+    /// Content was panned by two contacts or by scrolling.
+    Pan {
+        /// The movement since the last pan.
+        delta: Vector2,
+    },
+    /// Two contacts moved apart or together, or a trackpad was pinched.
+    Pinch {
+        /// The midpoint of the contacts.
+        center: Vector2,
+        /// The ratio of the current distance between the contacts to that at the last pinch.
+        scale: f64,
+    },
+    /// Two contacts rotated about their midpoint. Trackpad rotation is not reported by the input stream.
+    Rotate {
+        /// The midpoint of the contacts.
+        center: Vector2,
+        /// The clockwise rotation in radians since the last rotation.
+        angle: f64,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Source {
+    Mouse(Button),
+    Contact(u64),
+}
+
+impl Source {
+    fn button(self) -> Option<Button> {
+        match self {
+            Source::Mouse(button) => Some(button),
+            Source::Contact(_) => None,
+        }
+    }
+}
+
+struct Press {
+    source: Source,
+    origin: Vector2,
+    position: Vector2,
+    start: f64,
+    dragging: bool,
+    long_pressed: bool,
+    samples: VecDeque<(f64, Vector2)>,
+}
+
+struct Tap {
+    source: Source,
+    position: Vector2,
+    time: f64,
+    count: u32,
+}
+
+struct Span {
+    center: Vector2,
+    distance: f64,
+    angle: f64,
+}
+
+fn length(vector: Vector2) -> f64 {
+    vector.x.hypot(vector.y)
+}
+
+/// Recognizes gestures from a sequence of input events and the times at which they occurred.
+pub struct Recognizer {
+    config: Config,
+    cursor: Vector2,
+    press: Option<Press>,
+    last_tap: Option<Tap>,
+    contacts: HashMap<u64, Vector2>,
+    span: Option<Span>,
+}
+
+impl fmt::Debug for Recognizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recognizer")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Recognizer {
+    /// Creates a new recognizer with the provided thresholds.
+    pub fn new(config: Config) -> Recognizer {
+        Recognizer {
+            config,
+            cursor: Vector2::default(),
+            press: None,
+            last_tap: None,
+            contacts: HashMap::new(),
+            span: None,
+        }
+    }
+    /// Handles an input event occurring at the provided time in milliseconds, returning any recognized gestures.
+    pub fn handle(&mut self, event: &Event, time: f64) -> Vec<Gesture> {
+        let mut gestures = self.update(time);
+        match &event.kind {
+            Kind::Mouse(event) => match *event {
+                mouse::Event::Down(button) if self.press.is_none() && self.contacts.is_empty() => {
+                    self.begin(Source::Mouse(button), self.cursor, time);
+                }
+                mouse::Event::Move(position) => {
+                    self.cursor = position;
+                    if let Some(Source::Mouse(_)) = self.press.as_ref().map(|press| press.source) {
+                        self.move_press(position, time, &mut gestures);
+                    }
+                }
+                mouse::Event::Up(button)
+                    if self.press.as_ref().map(|press| press.source)
+                        == Some(Source::Mouse(button)) =>
+                {
+                    self.end(self.cursor, time, &mut gestures);
+                }
+                // Scrolling towards the bottom of content moves the content up.
+                mouse::Event::Scroll(scroll) => gestures.push(Gesture::Pan {
+                    delta: -scroll.delta,
+                }),
+                mouse::Event::Magnify(scale) => gestures.push(Gesture::Pinch {
+                    center: self.cursor,
+                    scale,
+                }),
+                _ => {}
+            },
+            Kind::Pointer(event) => self.contact(event, time, &mut gestures),
+            Kind::Windowing(windowing::Event::Blur) => {
+                self.cancel(&mut gestures);
+                self.contacts.clear();
+                self.span = None;
+            }
+            _ => {}
+        }
+        gestures
+    }
+    /// Advances time without input, returning any gestures recognized by the passage of time such as long presses.
+    pub fn update(&mut self, time: f64) -> Vec<Gesture> {
+        let mut gestures = vec![];
+        if let Some(press) = &mut self.press {
+            if !press.dragging
+                && !press.long_pressed
+                && time - press.start >= self.config.long_press_duration
+            {
+                press.long_pressed = true;
+                gestures.push(Gesture::LongPress {
+                    position: press.position,
+                    button: press.source.button(),
+                });
+            }
+        }
+        gestures
+    }
+    /// Returns the time at which [Recognizer::update] will next recognize a gesture absent further input, if any.
+    pub fn deadline(&self) -> Option<f64> {
+        self.press
+            .as_ref()
+            .filter(|press| !press.dragging && !press.long_pressed)
+            .map(|press| press.start + self.config.long_press_duration)
+    }
+    fn begin(&mut self, source: Source, position: Vector2, time: f64) {
+        let mut samples = VecDeque::new();
+        samples.push_back((time, position));
+        self.press = Some(Press {
+            source,
+            origin: position,
+            position,
+            start: time,
+            dragging: false,
+            long_pressed: false,
+            samples,
+        });
+    }
+    fn move_press(&mut self, position: Vector2, time: f64, gestures: &mut Vec<Gesture>) {
+        let config = self.config;
+        if let Some(press) = &mut self.press {
+            let delta = position - press.position;
+            press.position = position;
+            press.samples.push_back((time, position));
+            while press
+                .samples
+                .front()
+                .map(|sample| time - sample.0 > config.velocity_window)
+                .unwrap_or(false)
+            {
+                press.samples.pop_front();
+            }
+            if !press.dragging && length(position - press.origin) > config.drag_threshold {
+                press.dragging = true;
+                gestures.push(Gesture::DragStart {
+                    position: press.origin,
+                    button: press.source.button(),
+                });
+                gestures.push(Gesture::DragMove {
+                    position,
+                    delta: position - press.origin,
+                });
+            } else if press.dragging && delta != Vector2::default() {
+                gestures.push(Gesture::DragMove { position, delta });
+            }
+        }
+    }
+    fn end(&mut self, position: Vector2, time: f64, gestures: &mut Vec<Gesture>) {
+        self.move_press(position, time, gestures);
+        let press = match self.press.take() {
+            Some(press) => press,
+            None => return,
+        };
+        if press.dragging {
+            let velocity = match (press.samples.front(), press.samples.back()) {
+                (Some(first), Some(last)) if last.0 > first.0 => {
+                    (last.1 - first.1) * (1000. / (last.0 - first.0))
+                }
+                _ => Vector2::default(),
+            };
+            gestures.push(Gesture::DragEnd { position, velocity });
+            if length(velocity) >= self.config.fling_velocity {
+                gestures.push(Gesture::Fling { velocity });
+            }
+        } else if !press.long_pressed {
+            let count = match &self.last_tap {
+                Some(tap)
+                    if tap.source == press.source
+                        && time - tap.time <= self.config.multi_tap_interval
+                        && length(position - tap.position) <= self.config.drag_threshold =>
+                {
+                    tap.count + 1
+                }
+                _ => 1,
+            };
+            self.last_tap = Some(Tap {
+                source: press.source,
+                position,
+                time,
+                count,
+            });
+            gestures.push(Gesture::Tap {
+                position,
+                count,
+                button: press.source.button(),
+            });
+        }
+    }
+    fn cancel(&mut self, gestures: &mut Vec<Gesture>) {
+        if let Some(press) = self.press.take() {
+            if press.dragging {
+                gestures.push(Gesture::DragEnd {
+                    position: press.position,
+                    velocity: Vector2::default(),
+                });
+            }
+        }
+    }
+    fn contact(&mut self, event: &pointer::Event, time: f64, gestures: &mut Vec<Gesture>) {
+        let source = Source::Contact(event.id);
+        match event.phase {
+            Phase::Begin => {
+                self.contacts.insert(event.id, event.position);
+                if self.contacts.len() == 1 && self.press.is_none() {
+                    self.begin(source, event.position, time);
+                } else {
+                    // Further contacts turn a press into a multi-contact gesture.
+                    self.cancel(gestures);
+                    self.span = self.span();
+                }
+            }
+            Phase::Move => {
+                if self.contacts.insert(event.id, event.position).is_none() {
+                    return;
+                }
+                if self.press.as_ref().map(|press| press.source) == Some(source) {
+                    self.move_press(event.position, time, gestures);
+                } else if let (Some(last), Some(span)) = (self.span.take(), self.span()) {
+                    let delta = span.center - last.center;
+                    if delta != Vector2::default() {
+                        gestures.push(Gesture::Pan { delta });
+                    }
+                    if last.distance > 0. && span.distance != last.distance {
+                        gestures.push(Gesture::Pinch {
+                            center: span.center,
+                            scale: span.distance / last.distance,
+                        });
+                    }
+                    let mut angle = span.angle - last.angle;
+                    if angle > PI {
+                        angle -= 2. * PI;
+                    } else if angle < -PI {
+                        angle += 2. * PI;
+                    }
+                    if angle != 0. {
+                        gestures.push(Gesture::Rotate {
+                            center: span.center,
+                            angle,
+                        });
+                    }
+                    self.span = Some(span);
+                }
+            }
+            // A hovering pen only matters when it was lifted from a contact in progress.
+            Phase::Hover if !self.contacts.contains_key(&event.id) => {}
+            Phase::End | Phase::Hover => {
+                self.contacts.remove(&event.id);
+                if self.press.as_ref().map(|press| press.source) == Some(source) {
+                    self.end(event.position, time, gestures);
+                }
+                self.span = self.span();
+            }
+            Phase::Cancel => {
+                self.contacts.remove(&event.id);
+                if self.press.as_ref().map(|press| press.source) == Some(source) {
+                    self.cancel(gestures);
+                }
+                self.span = self.span();
+            }
+        }
+    }
+    fn span(&self) -> Option<Span> {
+        if self.contacts.len() < 2 {
+            return None;
+        }
+        let mut contacts: Vec<(&u64, &Vector2)> = self.contacts.iter().collect();
+        contacts.sort_by_key(|contact| *contact.0);
+        let (first, second) = (*contacts[0].1, *contacts[1].1);
+        let offset = second - first;
+        Some(Span {
+            center: (first + second) / 2.,
+            distance: length(offset),
+            angle: offset.y.atan2(offset.x),
+        })
+    }
+}
+
+/// A stream of gestures recognized from an input stream.
+pub struct Gestures {
+    input: Box<dyn Input>,
+    recognizer: Recognizer,
+    pending: VecDeque<Gesture>,
+    scheduled: Option<f64>,
+}
+
+impl fmt::Debug for Gestures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gestures")
+            .field("recognizer", &self.recognizer)
+            .finish()
+    }
+}
+
+impl Gestures {
+    /// Recognizes gestures from the provided input stream.
+    pub fn new(input: Box<dyn Input>, config: Config) -> Gestures {
+        Gestures {
+            input,
+            recognizer: Recognizer::new(config),
+            pending: VecDeque::new(),
+            scheduled: None,
+        }
+    }
+}
+
+fn now() -> f64 {
+    #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+    return targets::web::input::now();
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    return targets::native::input::now();
+}
+
+fn wake_after(delay: f64, task: task::Task) {
+    #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+    return targets::web::input::wake_after(delay, task);
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "windows"))]
+    return targets::native::input::wake_after(delay, task);
+}
+
+impl Stream for Gestures {
+    type Item = Gesture;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(gesture) = self.pending.pop_front() {
+                return Ok(Async::Ready(Some(gesture)));
+            }
+            match self.input.poll()? {
+                Async::Ready(Some(event)) => {
+                    let gestures = self.recognizer.handle(&event, event.time);
+                    self.pending.extend(gestures);
+                }
+                Async::Ready(None) => return Ok(Async::Ready(None)),
+                Async::NotReady => {
+                    let time = now();
+                    let gestures = self.recognizer.update(time);
+                    if gestures.is_empty() {
+                        let deadline = self.recognizer.deadline();
+                        if let Some(deadline) = deadline {
+                            if self.scheduled != Some(deadline) {
+                                wake_after(deadline - time, task::current());
+                            }
+                        }
+                        self.scheduled = deadline;
+                        return Ok(Async::NotReady);
+                    }
+                    self.pending.extend(gestures);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{
+        pointer::{Device, Event as PointerEvent},
+        windowing::WindowId,
+    };
+
+    fn event(kind: Kind) -> Event {
+        Event {
+            window: WindowId::MAIN,
+            time: 0.,
+            kind,
+        }
+    }
+
+    fn mouse(event: mouse::Event) -> Event {
+        self::event(Kind::Mouse(event))
+    }
+
+    fn contact(id: u64, phase: Phase, position: (f64, f64)) -> Event {
+        event(Kind::Pointer(PointerEvent {
+            id,
+            phase,
+            device: Device::Touch,
+            position: position.into(),
+            pressure: None,
+            contact_size: None,
+        }))
+    }
+
+    fn feed(recognizer: &mut Recognizer, events: Vec<(f64, Event)>) -> Vec<Gesture> {
+        events
+            .into_iter()
+            .flat_map(|(time, event)| recognizer.handle(&event, time))
+            .collect()
+    }
+
+    #[test]
+    fn multi_click() {
+        let mut recognizer = Recognizer::new(Config::default());
+        let click = |time: f64| {
+            vec![
+                (time, mouse(mouse::Event::Down(Button::Left))),
+                (time + 50., mouse(mouse::Event::Up(Button::Left))),
+            ]
+        };
+        let mut events = vec![(0., mouse(mouse::Event::Move((10., 10.).into())))];
+        events.extend(click(0.));
+        events.extend(click(200.));
+        events.extend(click(1000.));
+        let counts: Vec<u32> = feed(&mut recognizer, events)
+            .into_iter()
+            .map(|gesture| match gesture {
+                Gesture::Tap { count, .. } => count,
+                gesture => panic!("unexpected gesture {:?}", gesture),
+            })
+            .collect();
+        assert_eq!(counts, vec![1, 2, 1]);
+    }
+
+    #[test]
+    fn long_press() {
+        let mut recognizer = Recognizer::new(Config::default());
+        feed(
+            &mut recognizer,
+            vec![(0., contact(1, Phase::Begin, (5., 5.)))],
+        );
+        assert_eq!(recognizer.deadline(), Some(500.));
+        assert!(recognizer.update(499.).is_empty());
+        assert_eq!(
+            recognizer.update(500.),
+            vec![Gesture::LongPress {
+                position: (5., 5.).into(),
+                button: None,
+            }]
+        );
+        assert_eq!(recognizer.deadline(), None);
+        assert!(feed(
+            &mut recognizer,
+            vec![(600., contact(1, Phase::End, (5., 5.)))]
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn pen_lift_by_hover() {
+        let mut recognizer = Recognizer::new(Config::default());
+        let tap = vec![Gesture::Tap {
+            position: (5., 5.).into(),
+            count: 1,
+            button: None,
+        }];
+        assert!(feed(
+            &mut recognizer,
+            vec![(0., contact(1, Phase::Hover, (0., 0.)))]
+        )
+        .is_empty());
+        assert_eq!(
+            feed(
+                &mut recognizer,
+                vec![
+                    (10., contact(1, Phase::Begin, (5., 5.))),
+                    (60., contact(1, Phase::Hover, (5., 5.))),
+                    (70., contact(1, Phase::Hover, (6., 6.))),
+                ]
+            ),
+            tap
+        );
+    }
+
+    #[test]
+    fn drag_and_fling() {
+        let mut recognizer = Recognizer::new(Config::default());
+        let gestures = feed(
+            &mut recognizer,
+            vec![
+                (0., contact(1, Phase::Begin, (0., 0.))),
+                (10., contact(1, Phase::Move, (5., 0.))),
+                (20., contact(1, Phase::Move, (20., 0.))),
+                (30., contact(1, Phase::Move, (40., 0.))),
+                (40., contact(1, Phase::End, (40., 0.))),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            vec![
+                Gesture::DragStart {
+                    position: (0., 0.).into(),
+                    button: None,
+                },
+                Gesture::DragMove {
+                    position: (20., 0.).into(),
+                    delta: (20., 0.).into(),
+                },
+                Gesture::DragMove {
+                    position: (40., 0.).into(),
+                    delta: (20., 0.).into(),
+                },
+                Gesture::DragEnd {
+                    position: (40., 0.).into(),
+                    velocity: (1000., 0.).into(),
+                },
+                Gesture::Fling {
+                    velocity: (1000., 0.).into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn pinch_and_rotate() {
+        let mut recognizer = Recognizer::new(Config::default());
+        let gestures = feed(
+            &mut recognizer,
+            vec![
+                (0., contact(1, Phase::Begin, (-10., 0.))),
+                (0., contact(2, Phase::Begin, (10., 0.))),
+                (10., contact(2, Phase::Move, (-10., 40.))),
+            ],
+        );
+        assert_eq!(gestures.len(), 3);
+        match gestures[0] {
+            Gesture::Pan { delta } => assert_eq!(delta, (-10., 20.).into()),
+            gesture => panic!("unexpected gesture {:?}", gesture),
+        }
+        match gestures[1] {
+            Gesture::Pinch { center, scale } => {
+                assert_eq!(center, (-10., 20.).into());
+                assert!((scale - 2.).abs() < 1e-9);
+            }
+            gesture => panic!("unexpected gesture {:?}", gesture),
+        }
+        match gestures[2] {
+            Gesture::Rotate { angle, .. } => assert!((angle - PI / 2.).abs() < 1e-9),
+            gesture => panic!("unexpected gesture {:?}", gesture),
+        }
+    }
+
+    #[test]
+    fn trackpad_pinch() {
+        let mut recognizer = Recognizer::new(Config::default());
+        let gestures = feed(
+            &mut recognizer,
+            vec![
+                (0., mouse(mouse::Event::Move((3., 4.).into()))),
+                (10., mouse(mouse::Event::Magnify(1.5))),
+            ],
+        );
+        assert_eq!(
+            gestures,
+            vec![Gesture::Pinch {
+                center: (3., 4.).into(),
+                scale: 1.5,
+            }]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::ops::{BitOr, BitOrAssign};
 
 /// A number pad key.
 #[derive(Clone, Eq, PartialEq, Hash, Copy, Debug)]
@@ -271,6 +272,80 @@ pub enum Action {
     Down(Key),
 }
 
+/// A set of modifier keys and lock states.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    /// Either shift key.
+    pub const SHIFT: Modifiers = Modifiers(1);
+    /// Either control key.
+    pub const CONTROL: Modifiers = Modifiers(1 << 1);
+    /// Either alt or option key.
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    /// Either meta key, i.e. the Windows or command key.
+    pub const META: Modifiers = Modifiers(1 << 3);
+    /// The caps lock state.
+    pub const CAPS_LOCK: Modifiers = Modifiers(1 << 4);
+    /// The num lock state.
+    pub const NUM_LOCK: Modifiers = Modifiers(1 << 5);
+
+    const NAMES: [(Modifiers, &'static str); 6] = [
+        (Modifiers::SHIFT, "SHIFT"),
+        (Modifiers::CONTROL, "CONTROL"),
+        (Modifiers::ALT, "ALT"),
+        (Modifiers::META, "META"),
+        (Modifiers::CAPS_LOCK, "CAPS_LOCK"),
+        (Modifiers::NUM_LOCK, "NUM_LOCK"),
+    ];
+
+    /// Returns an empty set.
+    pub fn empty() -> Modifiers {
+        Modifiers(0)
+    }
+    /// Returns whether the set is empty.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+    /// Returns whether every modifier in the provided set is in this set.
+    pub fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+    /// Adds or removes the provided modifiers.
+    pub fn set(&mut self, other: Modifiers, active: bool) {
+        if active {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, other: Modifiers) -> Modifiers {
+        Modifiers(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+}
+
+impl Debug for Modifiers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = Modifiers::NAMES
+            .iter()
+            .filter(|(modifier, _)| self.contains(*modifier))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "Modifiers({})", names.join(" | "))
+    }
+}
+
 /// A keyboard event.
 #[derive(Clone)]
 pub struct Event {
@@ -278,14 +353,35 @@ pub struct Event {
     pub action: Action,
     /// The associated layout-dependant printable character of the relevant key if applicable.
     pub printable: Option<char>,
+    /// The modifier keys and lock states active when the event occurred.
+    pub modifiers: Modifiers,
+    /// Whether the event was generated automatically by the key being held down.
+    pub is_repeat: bool,
 }
 
 impl Debug for Event {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "Event {{ action: {:?}, printable: {:?} }}",
-            self.action, self.printable
+            "Event {{ action: {:?}, printable: {:?}, modifiers: {:?}, is_repeat: {:?} }}",
+            self.action, self.printable, self.modifiers, self.is_repeat
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifier_set() {
+        let mut modifiers = Modifiers::CONTROL | Modifiers::SHIFT;
+        assert!(modifiers.contains(Modifiers::CONTROL));
+        assert!(!modifiers.contains(Modifiers::CONTROL | Modifiers::ALT));
+        modifiers.set(Modifiers::SHIFT, false);
+        modifiers |= Modifiers::CAPS_LOCK;
+        assert_eq!(modifiers, Modifiers::CONTROL | Modifiers::CAPS_LOCK);
+        assert_eq!(format!("{:?}", modifiers), "Modifiers(CONTROL | CAPS_LOCK)");
+        assert!(Modifiers::empty().is_empty());
+    }
+}
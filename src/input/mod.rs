@@ -1,23 +1,48 @@
 use futures::Stream;
 
+/// Types to permit handling of gamepad and joystick input.
+pub mod gamepad;
+/// Recognizers for high-level gestures such as taps, drags and pinches.
+pub mod gesture;
 /// Types to permit handling of keyboard input.
 pub mod keyboard;
 /// Types to permit handling of mouse input.
 pub mod mouse;
+/// Types to permit handling of touch and pen input.
+pub mod pointer;
+/// Types to permit handling of text input and input method composition.
+pub mod text;
 /// Types to permit handling of windowing.
 pub mod windowing;
 
 /// Encapsulates a variety of possible input events.
 #[derive(Clone, Debug)]
-pub enum Event {
+pub enum Kind {
+    /// A gamepad event. Gamepads are not bound to a window, so these are directed at the main window.
+    Gamepad(gamepad::Event),
     /// A keyboard event.
     Keyboard(keyboard::Event),
     /// A mouse event.
     Mouse(mouse::Event),
+    /// A touch or pen event.
+    Pointer(pointer::Event),
+    /// A text input event.
+    Text(text::Event),
     /// A windowing event.
     Windowing(windowing::Event),
 }
 
+/// An input event directed at a window.
+#[derive(Clone, Debug)]
+pub struct Event {
+    /// The window the event is directed at.
+    pub window: windowing::WindowId,
+    /// The time at which the event was received in milliseconds, measured by a monotonic clock shared by all events.
+    pub time: f64,
+    /// The content of the event.
+    pub kind: Kind,
+}
+
 /// A context that provides input handling functionality.
 pub trait Provider {
     /// Returns an input event stream for this context.
@@ -1,7 +1,8 @@
-use crate::graphics::Vector2;
+use crate::graphics::{Rect, Vector2};
+use crate::input::pointer::Phase;
 
 /// A mouse button.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Button {
     /// The left or primary mouse button.
     Left,
@@ -36,6 +37,52 @@ impl From<u8> for Button {
     }
 }
 
+/// The unit of a scroll delta as reported by the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollUnit {
+    /// Pixels.
+    Pixel,
+    /// Lines of text.
+    Line,
+    /// Pages, i.e. the size of the window contents.
+    Page,
+}
+
+/// A scroll input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scroll {
+    /// The scrolled distance in logical pixels. Positive values scroll towards the right and bottom of content.
+    pub delta: Vector2,
+    /// The scrolled distance in the units reported by the device with the same orientation as the pixel delta.
+    pub raw_delta: Vector2,
+    /// The unit of the raw delta.
+    pub unit: ScrollUnit,
+    /// The phase of the scroll for devices such as trackpads that report when scrolling begins and ends.
+    pub phase: Option<Phase>,
+}
+
+impl Scroll {
+    /// Creates a scroll input, normalizing the raw delta to pixels using the provided line height and page size.
+    pub(crate) fn new(
+        raw_delta: Vector2,
+        unit: ScrollUnit,
+        line_height: f64,
+        page_size: Vector2,
+        phase: Option<Phase>,
+    ) -> Scroll {
+        Scroll {
+            delta: match unit {
+                ScrollUnit::Pixel => raw_delta,
+                ScrollUnit::Line => raw_delta * line_height,
+                ScrollUnit::Page => raw_delta * page_size,
+            },
+            raw_delta,
+            unit,
+            phase,
+        }
+    }
+}
+
 /// A mouse event.
 #[derive(Clone, Copy, Debug)]
 pub enum Event {
@@ -43,8 +90,102 @@ pub enum Event {
     Up(Button),
     /// The transition of a button from an inactivated state to an activated state.
     Down(Button),
-    /// A mouse movement to the given position.
+    /// A mouse movement to the given position in the coordinates of the viewport of the root [Frame](crate::graphics::canvas::Frame) of the window.
     Move(Vector2),
+    /// A raw relative mouse movement unaffected by the viewport, pointer acceleration or the edges of the window.
+    Motion(Vector2),
     /// A mouse scroll input.
-    Scroll(Vector2),
+    Scroll(Scroll),
+    /// A trackpad pinch, as the ratio by which content should be scaled. Only reported on the web.
+    Magnify(f64),
+    /// The cursor entered the window.
+    Enter,
+    /// The cursor left the window.
+    Leave,
+}
+
+// Returns the factor scaling lengths along one axis from one extent to another, or zero if the source extent is empty.
+fn scale(from: f64, to: f64) -> f64 {
+    if from == 0. {
+        0.
+    } else {
+        to / from
+    }
+}
+
+/// Maps a position within a window of the provided size to the coordinates of the provided viewport.
+/// Windows without area, such as minimized ones, map every position to the viewport origin.
+pub(crate) fn to_viewport(position: Vector2, size: Vector2, viewport: Rect) -> Vector2 {
+    viewport.position
+        + position
+            * Vector2::from((
+                scale(size.x, viewport.size.x),
+                scale(size.y, viewport.size.y),
+            ))
+}
+
+/// Maps a position in the coordinates of the provided viewport to a position within a window of the provided size.
+/// Viewports without area map every position to the window origin.
+pub(crate) fn from_viewport(position: Vector2, size: Vector2, viewport: Rect) -> Vector2 {
+    (position - viewport.position)
+        * Vector2::from((
+            scale(viewport.size.x, size.x),
+            scale(viewport.size.y, size.y),
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewport_mapping() {
+        let viewport = Rect::new((50., -20.), (400., 200.));
+        let size: Vector2 = (200., 100.).into();
+        assert_eq!(
+            to_viewport((0., 0.).into(), size, viewport),
+            (50., -20.).into()
+        );
+        assert_eq!(
+            to_viewport((100., 50.).into(), size, viewport),
+            (250., 80.).into()
+        );
+        assert_eq!(to_viewport(size, size, viewport), (450., 180.).into());
+        let identity = Rect::new((0., 0.), size);
+        assert_eq!(
+            to_viewport((12.5, 40.).into(), size, identity),
+            (12.5, 40.).into()
+        );
+        assert_eq!(
+            from_viewport((250., 80.).into(), size, viewport),
+            (100., 50.).into()
+        );
+        assert_eq!(
+            to_viewport((10., 10.).into(), Vector2::default(), viewport),
+            (50., -20.).into()
+        );
+        assert_eq!(
+            from_viewport((10., 10.).into(), size, Rect::new((0., 0.), (0., 0.))),
+            (0., 0.).into()
+        );
+    }
+
+    #[test]
+    fn scroll_normalization() {
+        let page: Vector2 = (800., 600.).into();
+        let scroll = Scroll::new((0., 3.).into(), ScrollUnit::Line, 20., page, None);
+        assert_eq!(scroll.delta, (0., 60.).into());
+        assert_eq!(scroll.raw_delta, (0., 3.).into());
+        let scroll = Scroll::new((-1., 0.5).into(), ScrollUnit::Page, 20., page, None);
+        assert_eq!(scroll.delta, (-800., 300.).into());
+        let scroll = Scroll::new(
+            (2., -7.).into(),
+            ScrollUnit::Pixel,
+            20.,
+            page,
+            Some(Phase::Move),
+        );
+        assert_eq!(scroll.delta, (2., -7.).into());
+        assert_eq!(scroll.phase, Some(Phase::Move));
+    }
 }
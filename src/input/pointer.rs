@@ -0,0 +1,82 @@
+use crate::graphics::Vector2;
+
+/// The phase of a pointer contact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// The contact began, e.g. a finger or pen touched the surface.
+    Begin,
+    /// The contact moved.
+    Move,
+    /// The contact ended, e.g. a finger or pen was lifted from the surface.
+    End,
+    /// The contact was cancelled by the system and should not be treated as having ended normally.
+    Cancel,
+    /// The pointer moved above the surface without touching it, e.g. a pen held within range.
+    /// A hover reported for a contact in progress means the contact was lifted.
+    Hover,
+}
+
+/// Pen or stylus data.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pen {
+    /// The tilt of the pen away from the normal of the surface along the x and y axes in degrees.
+    pub tilt: Vector2,
+    /// Whether the eraser end of the pen is in use.
+    pub eraser: bool,
+    /// Whether the barrel button of the pen is held.
+    pub barrel_button: bool,
+}
+
+/// The device producing a pointer contact.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Device {
+    /// A finger on a touchscreen.
+    Touch,
+    /// A pen or stylus.
+    Pen(Pen),
+}
+
+/// A pointer event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Event {
+    /// An identifier for the contact that is unique among simultaneous contacts.
+    pub id: u64,
+    /// The phase of the contact.
+    pub phase: Phase,
+    /// The device producing the contact.
+    pub device: Device,
+    /// The position of the contact in the coordinates of the viewport of the root [Frame](crate::graphics::canvas::Frame) of the window.
+    pub position: Vector2,
+    /// The normalized pressure of the contact between zero and one, if reported.
+    pub pressure: Option<f64>,
+    /// The size of the contact area, if reported.
+    pub contact_size: Option<Vector2>,
+}
+
+/// Maps the phase reported for a pen to [Phase::Hover] while the pen is out of contact, as the web reports hover as movement.
+/// Contact is signalled by the first button for the tip and the sixth for the eraser.
+#[cfg(any(target_arch = "wasm32", target_arch = "asmjs", test))]
+pub(crate) fn pen_phase(phase: Phase, pressure: f64, buttons: u32) -> Phase {
+    match phase {
+        Phase::Begin | Phase::Move if pressure == 0. || buttons & (1 | 32) == 0 => Phase::Hover,
+        phase => phase,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pen_hover() {
+        assert_eq!(pen_phase(Phase::Move, 0., 0), Phase::Hover);
+        // A barrel button pressed while hovering is not a contact.
+        assert_eq!(pen_phase(Phase::Begin, 0., 2), Phase::Hover);
+        assert_eq!(pen_phase(Phase::Move, 0.5, 2), Phase::Hover);
+        assert_eq!(pen_phase(Phase::Begin, 0.5, 1), Phase::Begin);
+        assert_eq!(pen_phase(Phase::Move, 0.5, 32), Phase::Move);
+        // Lifting the pen reports neither pressure nor buttons.
+        assert_eq!(pen_phase(Phase::End, 0., 0), Phase::End);
+        assert_eq!(pen_phase(Phase::Cancel, 0., 0), Phase::Cancel);
+    }
+}
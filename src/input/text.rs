@@ -0,0 +1,62 @@
+/// A text input event, reporting text produced by the keyboard layout or an input method editor (IME).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Text was committed, either typed directly or as the result of a composition.
+    Commit(String),
+    /// An IME composition began.
+    CompositionStart,
+    /// The uncommitted preedit text of the ongoing composition changed.
+    Preedit {
+        /// The current preedit text, which replaces any previous preedit text.
+        text: String,
+        /// The selected range within the preedit text as byte offsets, or the cursor position if both are equal.
+        cursor: Option<(usize, usize)>,
+    },
+    /// The ongoing composition ended. Any text resulting from the composition follows as a commit.
+    CompositionEnd,
+}
+
+/// Returns a commit event for a character received from the keyboard layout, ignoring control characters such as backspace and escape.
+pub(crate) fn commit(character: char) -> Option<Event> {
+    if character.is_control() {
+        None
+    } else {
+        Some(Event::Commit(character.to_string()))
+    }
+}
+
+/// Converts an offset in UTF-16 code units, as used by the web, to a byte offset into the provided text.
+#[cfg(any(target_arch = "wasm32", target_arch = "asmjs", test))]
+pub(crate) fn utf16_to_byte_offset(text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (index, character) in text.char_indices() {
+        if units >= offset {
+            return index;
+        }
+        units += character.len_utf16();
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(commit('a'), Some(Event::Commit("a".to_owned())));
+        assert_eq!(commit('\u{8}'), None);
+        assert_eq!(commit('\r'), None);
+        assert_eq!(commit('\u{7f}'), None);
+    }
+
+    #[test]
+    fn utf16_offsets() {
+        let text = "か😀a";
+        assert_eq!(utf16_to_byte_offset(text, 0), 0);
+        assert_eq!(utf16_to_byte_offset(text, 1), 3);
+        assert_eq!(utf16_to_byte_offset(text, 3), 7);
+        assert_eq!(utf16_to_byte_offset(text, 4), 8);
+        assert_eq!(utf16_to_byte_offset(text, 10), 8);
+    }
+}
@@ -1,10 +1,54 @@
-use crate::graphics::Vector2;
+use crate::graphics::{canvas::CursorGrab, Vector2};
+
+use std::path::PathBuf;
 
 /// A windowing event.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
     /// A window resize event.
-    Resize,
+    Resize {
+        /// The new size of the window contents in logical pixels.
+        logical: Vector2,
+        /// The new size of the window contents in physical pixels.
+        physical: Vector2,
+    },
     /// A window move event.
     Move(Vector2),
+    /// The window gained input focus.
+    Focus,
+    /// The window lost input focus.
+    Blur,
+    /// Closing the window was requested. The request may be intercepted using [crate::graphics::canvas::WindowConfig::intercept_close].
+    CloseRequested,
+    /// The window has been closed.
+    Close,
+    /// The window was minimized.
+    Minimize,
+    /// The window was restored after being minimized.
+    Restore,
+    /// The ratio of physical to logical pixels of the window changed.
+    ScaleFactor(f64),
+    /// A file was dropped on the window.
+    DroppedFile(PathBuf),
+    /// A file is being dragged over the window.
+    HoveredFile(PathBuf),
+    /// A file that was being dragged over the window left it or the drag was cancelled.
+    HoveredFileCancelled,
+    /// The window contents became visible or were hidden.
+    Visibility(bool),
+    /// The application was suspended by the system.
+    Suspend,
+    /// The application was resumed after being suspended.
+    Resume,
+    /// The constraint in effect on the cursor changed, either as requested or because the platform refused or released it.
+    CursorGrab(CursorGrab),
+}
+
+/// Identifies a window of a graphics context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowId(pub(crate) u64);
+
+impl WindowId {
+    /// The identifier of the window opened when a graphics context is started.
+    pub const MAIN: WindowId = WindowId(0);
 }
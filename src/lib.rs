@@ -28,6 +28,8 @@ extern crate gl;
 #[cfg(any(target_arch = "linux", target_arch = "macos", target_arch = "linux"))]
 extern crate glutin;
 
+/// Provides access to system clipboards.
+pub mod clipboard;
 mod errors;
 /// Provides a cross-platform abstracted asynchronous executor.
 pub mod executor;
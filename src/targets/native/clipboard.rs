@@ -0,0 +1,620 @@
+use crate::clipboard::{Clipboard, ClipboardFuture, PNG};
+use crate::errors::Error;
+use crate::graphics::{Image, LDRColor, Texture2};
+use crate::targets::native::graphics::{decode_png, encode_png};
+
+use futures::{future, Future};
+
+#[derive(Clone, Copy)]
+struct SystemClipboard;
+
+pub(crate) fn new() -> Box<dyn Clipboard> {
+    Box::new(SystemClipboard)
+}
+
+impl Clipboard for SystemClipboard {
+    fn read(&self, mime: &str) -> ClipboardFuture<Option<Vec<u8>>> {
+        selection::read(mime.to_owned())
+    }
+    fn write(&self, data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()> {
+        selection::write(data)
+    }
+    fn read_image(&self) -> ClipboardFuture<Option<Image<LDRColor, Texture2>>> {
+        Box::new(self.read(PNG).and_then(|data| match data {
+            Some(data) => decode_png(&data).map(Some).ok_or_else(Error::clipboard),
+            None => Ok(None),
+        }))
+    }
+    fn write_image(&self, image: Image<LDRColor, Texture2>) -> ClipboardFuture<()> {
+        match encode_png(image) {
+            Some(data) => self.write(vec![(PNG.to_owned(), data)]),
+            None => Box::new(future::err(Error::clipboard())),
+        }
+    }
+    fn box_clone(&self) -> Box<dyn Clipboard> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod selection {
+    use super::*;
+
+    pub(super) fn read(_mime: String) -> ClipboardFuture<Option<Vec<u8>>> {
+        Box::new(future::err(Error::unsupported()))
+    }
+
+    pub(super) fn write(_data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()> {
+        Box::new(future::err(Error::unsupported()))
+    }
+}
+
+// Only the X11 CLIPBOARD selection is implemented, so Wayland sessions are supported only through XWayland.
+#[cfg(target_os = "linux")]
+mod selection {
+    use super::*;
+    use crate::clipboard::TEXT;
+
+    use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
+    use futures::sync::oneshot;
+    use lazy_static::lazy_static;
+    use libc::{c_int, c_long, c_short, c_uchar, c_ulong, c_void};
+    use std::{
+        collections::VecDeque,
+        ffi::CString,
+        mem::size_of,
+        ptr, slice,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+    use x11_dl::xlib;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+    const POLL_TIMEOUT: c_int = 10;
+
+    type ReadReply = oneshot::Sender<Result<Option<Vec<u8>>, Error>>;
+
+    enum Request {
+        Read(String, ReadReply),
+        Write(Vec<(String, Vec<u8>)>, oneshot::Sender<Result<(), Error>>),
+    }
+
+    lazy_static! {
+        static ref REQUESTS: Option<Sender<Request>> = spawn();
+    }
+
+    fn spawn() -> Option<Sender<Request>> {
+        let (sender, receiver) = unbounded();
+        let (ready_sender, ready) = mpsc::channel();
+        thread::spawn(move || match Selection::open() {
+            Some(selection) => {
+                let _ = ready_sender.send(true);
+                selection.run(receiver);
+            }
+            None => {
+                let _ = ready_sender.send(false);
+            }
+        });
+        if ready.recv().unwrap_or(false) {
+            Some(sender)
+        } else {
+            None
+        }
+    }
+
+    fn request<T, F>(make: F) -> ClipboardFuture<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(oneshot::Sender<Result<T, Error>>) -> Request,
+    {
+        let sender = match REQUESTS.as_ref() {
+            Some(sender) => sender,
+            None => return Box::new(future::err(Error::unsupported())),
+        };
+        let (reply, response) = oneshot::channel();
+        if sender.send(make(reply)).is_err() {
+            return Box::new(future::err(Error::clipboard()));
+        }
+        Box::new(
+            response
+                .map_err(|_| Error::clipboard())
+                .and_then(|result| result),
+        )
+    }
+
+    pub(super) fn read(mime: String) -> ClipboardFuture<Option<Vec<u8>>> {
+        request(|reply| Request::Read(mime, reply))
+    }
+
+    pub(super) fn write(data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()> {
+        request(|reply| Request::Write(data, reply))
+    }
+
+    struct PendingRead {
+        reply: ReadReply,
+        deadline: Instant,
+        incremental: Option<Vec<u8>>,
+    }
+
+    /// An incremental transfer of owned data to a requestor, sent a chunk at a time as the requestor deletes the property.
+    struct PendingWrite {
+        requestor: xlib::Window,
+        property: xlib::Atom,
+        target: xlib::Atom,
+        data: Vec<u8>,
+        offset: usize,
+        deadline: Instant,
+    }
+
+    /// Owns an X11 connection and an invisible window used to request and serve the CLIPBOARD selection.
+    struct Selection {
+        xlib: xlib::Xlib,
+        display: *mut xlib::Display,
+        window: xlib::Window,
+        clipboard: xlib::Atom,
+        targets: xlib::Atom,
+        incr: xlib::Atom,
+        property: xlib::Atom,
+        text_targets: Vec<xlib::Atom>,
+        owned: Vec<(String, Vec<u8>)>,
+        reads: VecDeque<(String, ReadReply)>,
+        current: Option<PendingRead>,
+        writes: Vec<PendingWrite>,
+    }
+
+    impl Selection {
+        fn open() -> Option<Selection> {
+            let xlib = xlib::Xlib::open().ok()?;
+            unsafe {
+                let display = (xlib.XOpenDisplay)(ptr::null());
+                if display.is_null() {
+                    return None;
+                }
+                let window = (xlib.XCreateSimpleWindow)(
+                    display,
+                    (xlib.XDefaultRootWindow)(display),
+                    0,
+                    0,
+                    1,
+                    1,
+                    0,
+                    0,
+                    0,
+                );
+                (xlib.XSelectInput)(display, window, xlib::PropertyChangeMask);
+                let mut selection = Selection {
+                    xlib,
+                    display,
+                    window,
+                    clipboard: 0,
+                    targets: 0,
+                    incr: 0,
+                    property: 0,
+                    text_targets: vec![],
+                    owned: vec![],
+                    reads: VecDeque::new(),
+                    current: None,
+                    writes: vec![],
+                };
+                selection.clipboard = selection.atom("CLIPBOARD");
+                selection.targets = selection.atom("TARGETS");
+                selection.incr = selection.atom("INCR");
+                selection.property = selection.atom("VESSELS_CLIPBOARD");
+                selection.text_targets = vec![
+                    selection.atom("UTF8_STRING"),
+                    selection.atom(TEXT),
+                    selection.atom("STRING"),
+                    selection.atom("TEXT"),
+                ];
+                Some(selection)
+            }
+        }
+
+        fn atom(&self, name: &str) -> xlib::Atom {
+            let name = CString::new(name).unwrap();
+            unsafe { (self.xlib.XInternAtom)(self.display, name.as_ptr(), xlib::False) }
+        }
+
+        /// The target requested from other clients for a MIME type.
+        fn target(&self, mime: &str) -> xlib::Atom {
+            if mime == TEXT {
+                self.text_targets[0]
+            } else {
+                self.atom(mime)
+            }
+        }
+
+        fn owned_data(&self, target: xlib::Atom) -> Option<&[u8]> {
+            self.owned
+                .iter()
+                .find(|(mime, _)| {
+                    if mime == TEXT {
+                        self.text_targets.contains(&target)
+                    } else {
+                        self.atom(mime) == target
+                    }
+                })
+                .map(|(_, data)| data.as_slice())
+        }
+
+        fn is_owner(&self) -> bool {
+            unsafe { (self.xlib.XGetSelectionOwner)(self.display, self.clipboard) == self.window }
+        }
+
+        fn run(mut self, requests: Receiver<Request>) {
+            loop {
+                loop {
+                    match requests.try_recv() {
+                        Ok(Request::Read(mime, reply)) => self.reads.push_back((mime, reply)),
+                        Ok(Request::Write(data, reply)) => {
+                            let _ = reply.send(self.own(data));
+                        }
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => return,
+                    }
+                }
+                if self.current.is_none() {
+                    if let Some((mime, reply)) = self.reads.pop_front() {
+                        self.begin_read(&mime, reply);
+                    }
+                }
+                unsafe {
+                    while (self.xlib.XPending)(self.display) > 0 {
+                        let mut event: xlib::XEvent = std::mem::zeroed();
+                        (self.xlib.XNextEvent)(self.display, &mut event);
+                        self.handle(&event);
+                    }
+                }
+                if let Some(current) = self.current.take() {
+                    if Instant::now() >= current.deadline {
+                        let _ = current.reply.send(Err(Error::clipboard()));
+                    } else {
+                        self.current = Some(current);
+                    }
+                }
+                // Requestors that stop deleting the property, e.g. because they exited, abandon their transfer.
+                let now = Instant::now();
+                self.writes.retain(|write| now < write.deadline);
+                let mut fd = libc::pollfd {
+                    fd: unsafe { (self.xlib.XConnectionNumber)(self.display) },
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                unsafe {
+                    libc::poll(&mut fd, 1, POLL_TIMEOUT);
+                }
+            }
+        }
+
+        fn own(&mut self, data: Vec<(String, Vec<u8>)>) -> Result<(), Error> {
+            self.owned = data;
+            unsafe {
+                (self.xlib.XSetSelectionOwner)(
+                    self.display,
+                    self.clipboard,
+                    self.window,
+                    xlib::CurrentTime,
+                );
+            }
+            if self.is_owner() {
+                Ok(())
+            } else {
+                self.owned.clear();
+                Err(Error::clipboard())
+            }
+        }
+
+        fn begin_read(&mut self, mime: &str, reply: ReadReply) {
+            if self.is_owner() {
+                let data = self.owned_data(self.target(mime)).map(<[u8]>::to_vec);
+                let _ = reply.send(Ok(data));
+                return;
+            }
+            unsafe {
+                (self.xlib.XConvertSelection)(
+                    self.display,
+                    self.clipboard,
+                    self.target(mime),
+                    self.property,
+                    self.window,
+                    xlib::CurrentTime,
+                );
+                (self.xlib.XFlush)(self.display);
+            }
+            self.current = Some(PendingRead {
+                reply,
+                deadline: Instant::now() + TIMEOUT,
+                incremental: None,
+            });
+        }
+
+        /// Reads and deletes the transfer property of the window, returning its type and contents.
+        fn take_property(&self) -> Option<(xlib::Atom, Vec<u8>)> {
+            let mut actual_type: c_ulong = 0;
+            let mut actual_format: c_int = 0;
+            let mut item_count: c_ulong = 0;
+            let mut bytes_after: c_ulong = 0;
+            let mut data: *mut c_uchar = ptr::null_mut();
+            unsafe {
+                let status = (self.xlib.XGetWindowProperty)(
+                    self.display,
+                    self.window,
+                    self.property,
+                    0,
+                    c_long::MAX / 4,
+                    xlib::True,
+                    xlib::AnyPropertyType as c_ulong,
+                    &mut actual_type,
+                    &mut actual_format,
+                    &mut item_count,
+                    &mut bytes_after,
+                    &mut data,
+                );
+                if status != c_int::from(xlib::Success) {
+                    return None;
+                }
+                if data.is_null() {
+                    return Some((actual_type, vec![]));
+                }
+                // Xlib returns 16 and 32 bit items as shorts and longs.
+                let item_size = match actual_format {
+                    16 => size_of::<c_short>(),
+                    32 => size_of::<c_long>(),
+                    _ => 1,
+                };
+                let contents =
+                    slice::from_raw_parts(data, item_count as usize * item_size).to_vec();
+                (self.xlib.XFree)(data as *mut c_void);
+                Some((actual_type, contents))
+            }
+        }
+
+        fn finish_read(&mut self, result: Result<Option<Vec<u8>>, Error>) {
+            if let Some(current) = self.current.take() {
+                let _ = current.reply.send(result);
+            }
+        }
+
+        fn handle(&mut self, event: &xlib::XEvent) {
+            match event.get_type() {
+                xlib::SelectionNotify => {
+                    let event = unsafe { event.selection };
+                    if self.current.is_none() || event.selection != self.clipboard {
+                        return;
+                    }
+                    if event.property == 0 {
+                        self.finish_read(Ok(None));
+                        return;
+                    }
+                    match self.take_property() {
+                        // Large transfers are sent in chunks, each announced by a property change.
+                        Some((kind, _)) if kind == self.incr => {
+                            if let Some(current) = self.current.as_mut() {
+                                current.incremental = Some(vec![]);
+                                current.deadline = Instant::now() + TIMEOUT;
+                            }
+                        }
+                        Some((_, data)) => self.finish_read(Ok(Some(data))),
+                        None => self.finish_read(Err(Error::clipboard())),
+                    }
+                }
+                xlib::PropertyNotify => {
+                    let event = unsafe { event.property };
+                    if event.window != self.window {
+                        self.continue_write(&event);
+                        return;
+                    }
+                    let incremental = self
+                        .current
+                        .as_ref()
+                        .and_then(|current| current.incremental.as_ref())
+                        .is_some();
+                    if !incremental
+                        || event.atom != self.property
+                        || event.state != xlib::PropertyNewValue
+                    {
+                        return;
+                    }
+                    match self.take_property() {
+                        Some((_, ref chunk)) if chunk.is_empty() => {
+                            let data = self
+                                .current
+                                .as_mut()
+                                .and_then(|current| current.incremental.take());
+                            self.finish_read(Ok(data));
+                        }
+                        Some((_, chunk)) => {
+                            if let Some(current) = self.current.as_mut() {
+                                if let Some(data) = current.incremental.as_mut() {
+                                    data.extend(chunk);
+                                }
+                                current.deadline = Instant::now() + TIMEOUT;
+                            }
+                        }
+                        None => self.finish_read(Err(Error::clipboard())),
+                    }
+                }
+                xlib::SelectionRequest => {
+                    let request = unsafe { event.selection_request };
+                    self.serve(request);
+                }
+                xlib::SelectionClear => {
+                    let event = unsafe { event.selection_clear };
+                    if event.selection == self.clipboard {
+                        self.owned.clear();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        /// The largest property in bytes that can be written in a single request.
+        fn max_size(&self) -> usize {
+            let size = unsafe {
+                match (self.xlib.XExtendedMaxRequestSize)(self.display) {
+                    0 => (self.xlib.XMaxRequestSize)(self.display),
+                    size => size,
+                }
+            };
+            size as usize * 4
+        }
+
+        /// Announces an incremental transfer to the requestor, which starts it by deleting the property.
+        fn begin_write(
+            &mut self,
+            requestor: xlib::Window,
+            property: xlib::Atom,
+            target: xlib::Atom,
+        ) {
+            let data = match self.owned_data(target) {
+                Some(data) => data.to_vec(),
+                None => return,
+            };
+            let size = data.len() as c_long;
+            unsafe {
+                (self.xlib.XSelectInput)(self.display, requestor, xlib::PropertyChangeMask);
+                (self.xlib.XChangeProperty)(
+                    self.display,
+                    requestor,
+                    property,
+                    self.incr,
+                    32,
+                    xlib::PropModeReplace,
+                    &size as *const c_long as *const c_uchar,
+                    1,
+                );
+            }
+            self.writes
+                .retain(|write| write.requestor != requestor || write.property != property);
+            self.writes.push(PendingWrite {
+                requestor,
+                property,
+                target,
+                data,
+                offset: 0,
+                deadline: Instant::now() + TIMEOUT,
+            });
+        }
+
+        /// Sends the next chunk of an incremental transfer once the requestor has read the previous one.
+        /// The transfer ends with an empty chunk.
+        fn continue_write(&mut self, event: &xlib::XPropertyEvent) {
+            if event.state != xlib::PropertyDelete {
+                return;
+            }
+            let index =
+                match self.writes.iter().position(|write| {
+                    write.requestor == event.window && write.property == event.atom
+                }) {
+                    Some(index) => index,
+                    None => return,
+                };
+            // Chunks are kept well below the request limit to leave room for the request header.
+            let chunk_size = self.max_size() / 2;
+            let write = &mut self.writes[index];
+            let end = (write.offset + chunk_size).min(write.data.len());
+            let chunk = &write.data[write.offset..end];
+            unsafe {
+                (self.xlib.XChangeProperty)(
+                    self.display,
+                    write.requestor,
+                    write.property,
+                    write.target,
+                    8,
+                    xlib::PropModeReplace,
+                    chunk.as_ptr(),
+                    chunk.len() as c_int,
+                );
+            }
+            if chunk.is_empty() {
+                let write = self.writes.remove(index);
+                unsafe {
+                    (self.xlib.XSelectInput)(self.display, write.requestor, xlib::NoEventMask);
+                }
+            } else {
+                write.offset = end;
+                write.deadline = Instant::now() + TIMEOUT;
+            }
+            unsafe {
+                (self.xlib.XFlush)(self.display);
+            }
+        }
+
+        fn serve(&mut self, request: xlib::XSelectionRequestEvent) {
+            // Obsolete clients leave the property unset and expect the target to be used instead.
+            let property = if request.property == 0 {
+                request.target
+            } else {
+                request.property
+            };
+            let max_size = self.max_size();
+            let served = if request.selection != self.clipboard {
+                false
+            } else if request.target == self.targets {
+                let mut targets = vec![self.targets];
+                for (mime, _) in &self.owned {
+                    if mime == TEXT {
+                        targets.extend(&self.text_targets);
+                    } else {
+                        targets.push(self.atom(mime));
+                    }
+                }
+                unsafe {
+                    (self.xlib.XChangeProperty)(
+                        self.display,
+                        request.requestor,
+                        property,
+                        xlib::XA_ATOM,
+                        32,
+                        xlib::PropModeReplace,
+                        targets.as_ptr() as *const c_uchar,
+                        targets.len() as c_int,
+                    );
+                }
+                true
+            } else {
+                // Data exceeding the maximum request size is sent through an incremental transfer.
+                match self.owned_data(request.target) {
+                    Some(data) if data.len() >= max_size => {
+                        self.begin_write(request.requestor, property, request.target);
+                        true
+                    }
+                    Some(data) => {
+                        unsafe {
+                            (self.xlib.XChangeProperty)(
+                                self.display,
+                                request.requestor,
+                                property,
+                                request.target,
+                                8,
+                                xlib::PropModeReplace,
+                                data.as_ptr(),
+                                data.len() as c_int,
+                            );
+                        }
+                        true
+                    }
+                    _ => false,
+                }
+            };
+            let mut reply = xlib::XEvent {
+                selection: xlib::XSelectionEvent {
+                    type_: xlib::SelectionNotify,
+                    serial: 0,
+                    send_event: xlib::True,
+                    display: self.display,
+                    requestor: request.requestor,
+                    selection: request.selection,
+                    target: request.target,
+                    property: if served { property } else { 0 },
+                    time: request.time,
+                },
+            };
+            unsafe {
+                (self.xlib.XSendEvent)(self.display, request.requestor, xlib::False, 0, &mut reply);
+                (self.xlib.XFlush)(self.display);
+            }
+        }
+    }
+}
@@ -1,11 +1,18 @@
+use super::pure2d::{
+    pixel_to_rgba, premultiply, rgba_to_pixel, unpremultiply, CairoImage, CairoSurface,
+};
 use crate::graphics::{
     canvas::Rasterizable,
-    path::{Fill, GradientStop, Path, Texture},
-    LDRColor,
+    color::{ColorProfile, RenderingIntent, SoftProof},
+    path::Texture,
+    Color, ColorSpace, HDRColor, LDRColor,
+};
+use lcms2::{
+    CIExyY, CIExyYTRIPLE, DisallowCache, Flags, Intent, PixelFormat, ThreadContext, ToneCurve,
+    Transform,
 };
-use lcms2::{Intent, PixelFormat, Transform};
 use std::collections::{HashMap, VecDeque};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use glutin::Window;
 
@@ -76,14 +83,417 @@ mod cm_backing {
     }
 }
 
+type LcmsProfile = lcms2::Profile<ThreadContext>;
+
+fn linear_profile(context: &ThreadContext, space: ColorSpace) -> LcmsProfile {
+    let chromaticity = |(x, y): (f64, f64)| CIExyY { x, y, Y: 1. };
+    let [red, green, blue] = space.primaries();
+    let linear = ToneCurve::new(1.);
+    LcmsProfile::new_rgb_context(
+        context,
+        &chromaticity(space.white_point()),
+        &CIExyYTRIPLE {
+            Red: chromaticity(red),
+            Green: chromaticity(green),
+            Blue: chromaticity(blue),
+        },
+        &[&linear, &linear, &linear],
+    )
+    .unwrap()
+}
+
+#[cfg(target_os = "linux")]
+mod cm_backing {
+    use libc::{c_int, c_long, c_uchar, c_ulong, c_void};
+    use std::{ffi::CString, ptr, slice};
+    use x11_dl::xlib;
+    pub(crate) fn get_profile_data(display: *mut c_void, screen: c_int) -> Result<Vec<u8>, ()> {
+        let xlib = xlib::Xlib::open().map_err(|_| ())?;
+        let display = display as *mut xlib::Display;
+        // Display profiles are published on the root window per the X Color Management spec,
+        // with screens other than the first suffixed by their index.
+        let atom_name = if screen == 0 {
+            CString::new("_ICC_PROFILE")
+        } else {
+            CString::new(format!("_ICC_PROFILE_{}", screen))
+        }
+        .unwrap();
+        unsafe {
+            let atom = (xlib.XInternAtom)(display, atom_name.as_ptr(), xlib::True);
+            if atom == 0 {
+                return Err(());
+            }
+            let mut actual_type: c_ulong = 0;
+            let mut actual_format: c_int = 0;
+            let mut item_count: c_ulong = 0;
+            let mut bytes_after: c_ulong = 0;
+            let mut data: *mut c_uchar = ptr::null_mut();
+            let status = (xlib.XGetWindowProperty)(
+                display,
+                (xlib.XRootWindow)(display, screen),
+                atom,
+                0,
+                c_long::max_value() / 4,
+                xlib::False,
+                xlib::AnyPropertyType as c_ulong,
+                &mut actual_type,
+                &mut actual_format,
+                &mut item_count,
+                &mut bytes_after,
+                &mut data,
+            );
+            if data.is_null() {
+                return Err(());
+            }
+            let profile = if status == c_int::from(xlib::Success) && actual_format == 8 {
+                Ok(slice::from_raw_parts(data, item_count as usize).to_vec())
+            } else {
+                Err(())
+            };
+            (xlib.XFree)(data as *mut c_void);
+            profile
+        }
+    }
+}
+
+fn lcms_intent(intent: RenderingIntent) -> Intent {
+    match intent {
+        RenderingIntent::Perceptual => Intent::Perceptual,
+        RenderingIntent::RelativeColorimetric => Intent::RelativeColorimetric,
+        RenderingIntent::Saturation => Intent::Saturation,
+        RenderingIntent::AbsoluteColorimetric => Intent::AbsoluteColorimetric,
+    }
+}
+
+type PixelTransform = Transform<[u8; 4], [u8; 4], ThreadContext, DisallowCache>;
+type HDRTransform = Transform<[f32; 4], [u8; 4], ThreadContext, DisallowCache>;
+
+pub(crate) const DEFAULT_CACHE_SIZE: usize = 512;
+const IMAGE_CACHE_SIZE: usize = 10;
+const COLOR_SPACES: [ColorSpace; 3] =
+    [ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::Rec2020];
+
+struct Proof {
+    profile: LcmsProfile,
+    intent: Intent,
+    gamut_warning: Option<LDRColor>,
+}
+
+// Transforms are built without the lcms 1-pixel cache so they can be shared across threads.
+fn build_transform<I: Copy, O: Copy>(
+    context: &ThreadContext,
+    source: &LcmsProfile,
+    source_format: PixelFormat,
+    destination: &LcmsProfile,
+    destination_format: PixelFormat,
+    intent: Intent,
+    proof: Option<&Proof>,
+) -> Result<Transform<I, O, ThreadContext, DisallowCache>, ()> {
+    match proof {
+        Some(proof) => Transform::new_proofing_context(
+            context,
+            source,
+            source_format,
+            destination,
+            destination_format,
+            &proof.profile,
+            intent,
+            proof.intent,
+            if proof.gamut_warning.is_some() {
+                Flags::NO_CACHE | Flags::SOFT_PROOFING | Flags::GAMUT_CHECK
+            } else {
+                Flags::NO_CACHE | Flags::SOFT_PROOFING
+            },
+        ),
+        None => Transform::new_flags_context(
+            context,
+            source,
+            source_format,
+            destination,
+            destination_format,
+            intent,
+            Flags::NO_CACHE,
+        ),
+    }
+    .map_err(|_| ())
+}
+
+fn transform_surface(image: &CairoImage, transform: &PixelTransform) {
+    image.with_pixels(|data| {
+        // lcms works on straight alpha, while cairo surfaces are premultiplied.
+        let mut pixels: Vec<[u8; 4]> = data
+            .iter()
+            .map(|pixel| unpremultiply(pixel_to_rgba(*pixel)))
+            .collect();
+        transform.transform_in_place(&mut pixels);
+        for (pixel, transformed) in data.iter_mut().zip(pixels.iter()) {
+            *pixel = rgba_to_pixel(premultiply(*transformed));
+        }
+    });
+}
+
+fn visit_ldr<F: FnMut(&mut Color)>(color: &mut LDRColor, visit: &mut F) {
+    let mut wrapped = Color::LDR(*color);
+    visit(&mut wrapped);
+    if let Color::LDR(transformed) = wrapped {
+        *color = transformed;
+    }
+}
+
+fn for_each_texture<F: FnMut(&mut Texture)>(content: &mut Rasterizable, visit: &mut F) {
+    let (stroke, fill) = match content {
+        Rasterizable::Text(text) => (text.stroke.as_mut(), None),
+        Rasterizable::Path(path) => (path.stroke.as_mut(), path.fill.as_mut()),
+    };
+    if let Some(stroke) = stroke {
+        visit(&mut stroke.content);
+    }
+    if let Some(fill) = fill {
+        visit(&mut fill.content);
+    }
+}
+
+fn for_each_color<F: FnMut(&mut Color)>(content: &mut Rasterizable, visit: &mut F) {
+    match content {
+        Rasterizable::Text(text) => {
+            visit(&mut text.color);
+            for decoration in &mut text.decorations {
+                if let Some(color) = &mut decoration.color {
+                    visit_ldr(color, visit);
+                }
+            }
+            for shadow in &mut text.shadows {
+                visit_ldr(&mut shadow.color, visit);
+            }
+        }
+        Rasterizable::Path(path) => {
+            for shadow in &mut path.shadows {
+                visit_ldr(&mut shadow.color, visit);
+            }
+        }
+    }
+    for_each_texture(content, &mut |texture| match texture {
+        Texture::Solid(color) => visit(color),
+        Texture::LinearGradient(gradient) => gradient
+            .stops
+            .iter_mut()
+            .for_each(|stop| visit(&mut stop.color)),
+        Texture::RadialGradient(gradient) => gradient
+            .stops
+            .iter_mut()
+            .for_each(|stop| visit(&mut stop.color)),
+        Texture::Image(_) => {}
+    });
+}
+
+/// A direct-mapped cache of converted colors, in which each color can only occupy the slot its hash selects.
+/// Lookups and insertions cost a multiplication and a comparison, far less than converting a color.
+struct ColorCache {
+    slots: Vec<Option<(LDRColor, LDRColor)>>,
+}
+
+impl ColorCache {
+    fn new(capacity: usize) -> ColorCache {
+        ColorCache {
+            slots: vec![None; capacity],
+        }
+    }
+    fn slot(&self, color: &LDRColor) -> usize {
+        let key = u64::from(u32::from_le_bytes([color.r, color.g, color.b, color.a]));
+        // Fibonacci hashing spreads nearby colors, e.g. gradient stops, across slots.
+        (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32) as usize % self.slots.len()
+    }
+    fn get(&self, color: &LDRColor) -> Option<LDRColor> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        match self.slots[self.slot(color)] {
+            Some((cached, transformed_color)) if cached == *color => Some(transformed_color),
+            _ => None,
+        }
+    }
+    fn insert(&mut self, color: LDRColor, transformed_color: LDRColor) {
+        if self.slots.is_empty() {
+            return;
+        }
+        let slot = self.slot(&color);
+        self.slots[slot] = Some((color, transformed_color));
+    }
+    fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+    }
+}
+
 unsafe impl Sync for Profile {}
 unsafe impl Send for Profile {}
 
 struct ProfileState {
-    display_profile: lcms2::Profile,
-    srgb_profile: lcms2::Profile,
-    color_cache: HashMap<LDRColor, LDRColor>,
-    color_cache_queue: VecDeque<LDRColor>,
+    display_profile: LcmsProfile,
+    srgb_profile: LcmsProfile,
+    proof: Option<Proof>,
+    transform: PixelTransform,
+    hdr_transforms: HashMap<ColorSpace, HDRTransform>,
+    color_cache: Mutex<ColorCache>,
+    image_cache: Mutex<VecDeque<(Weak<Mutex<CairoSurface>>, CairoImage)>>,
+    intent: Intent,
+    // Every profile and transform above is allocated in this context, so it must be dropped last.
+    context: ThreadContext,
+}
+
+// Builds the sRGB transform and one transform per linear HDR color space into the display profile.
+fn build_transforms(
+    context: &ThreadContext,
+    srgb_profile: &LcmsProfile,
+    display_profile: &LcmsProfile,
+    intent: Intent,
+    proof: Option<&Proof>,
+) -> Result<(PixelTransform, HashMap<ColorSpace, HDRTransform>), ()> {
+    let transform = build_transform(
+        context,
+        srgb_profile,
+        PixelFormat::RGBA_8,
+        display_profile,
+        PixelFormat::RGBA_8,
+        intent,
+        proof,
+    )?;
+    let mut hdr_transforms = HashMap::with_capacity(COLOR_SPACES.len());
+    for space in COLOR_SPACES.iter() {
+        hdr_transforms.insert(
+            *space,
+            build_transform(
+                context,
+                &linear_profile(context, *space),
+                PixelFormat::RGBA_FLT,
+                display_profile,
+                PixelFormat::RGBA_8,
+                intent,
+                proof,
+            )?,
+        );
+    }
+    Ok((transform, hdr_transforms))
+}
+
+impl ProfileState {
+    fn build_transforms(&mut self) -> Result<(), ()> {
+        if let Some(LDRColor { r, g, b, .. }) =
+            self.proof.as_ref().and_then(|proof| proof.gamut_warning)
+        {
+            let mut codes = [0; 16];
+            codes[..3].copy_from_slice(&[
+                u16::from(r) * 257,
+                u16::from(g) * 257,
+                u16::from(b) * 257,
+            ]);
+            self.context.set_alarm_codes(codes);
+        }
+        let (transform, hdr_transforms) = build_transforms(
+            &self.context,
+            &self.srgb_profile,
+            &self.display_profile,
+            self.intent,
+            self.proof.as_ref(),
+        )?;
+        self.transform = transform;
+        self.hdr_transforms = hdr_transforms;
+        self.color_cache.lock().unwrap().clear();
+        self.image_cache.lock().unwrap().clear();
+        Ok(())
+    }
+    fn transform_hdr(&self, color: HDRColor) -> LDRColor {
+        let alpha = color.a.clamp(0., 1.);
+        let transformed_pixels = &mut [[0; 4]];
+        self.hdr_transforms[&color.space]
+            .transform_pixels(&[[color.r, color.g, color.b, alpha]], transformed_pixels);
+        // lcms leaves the extra alpha channel untouched unless asked to copy it.
+        let [r, g, b, _] = transformed_pixels[0];
+        LDRColor::rgba(r, g, b, (alpha * 255.).round() as u8)
+    }
+    fn transform_image(&self, image: &CairoImage) -> CairoImage {
+        let cached = self
+            .image_cache
+            .lock()
+            .unwrap()
+            .iter()
+            .find_map(|(source, transformed)| {
+                source
+                    .upgrade()
+                    .filter(|source| Arc::ptr_eq(source, image.surface()))
+                    .map(|_| transformed.clone())
+            });
+        if let Some(transformed_image) = cached {
+            return transformed_image;
+        }
+        let transformed_image = image.duplicate();
+        let embedded_transform = image
+            .embedded_profile()
+            .and_then(|profile| LcmsProfile::new_icc_context(&self.context, profile.data()).ok())
+            .and_then(|embedded_profile| {
+                build_transform(
+                    &self.context,
+                    &embedded_profile,
+                    PixelFormat::RGBA_8,
+                    &self.display_profile,
+                    PixelFormat::RGBA_8,
+                    self.intent,
+                    self.proof.as_ref(),
+                )
+                .ok()
+            });
+        transform_surface(
+            &transformed_image,
+            embedded_transform.as_ref().unwrap_or(&self.transform),
+        );
+        let mut cache = self.image_cache.lock().unwrap();
+        cache.retain(|(source, _)| source.upgrade().is_some());
+        if cache.len() >= IMAGE_CACHE_SIZE {
+            cache.pop_front();
+        }
+        cache.push_back((Arc::downgrade(image.surface()), transformed_image.clone()));
+        transformed_image
+    }
+    fn transform_texture(&self, texture: &mut Texture) {
+        if let Texture::Image(image) = texture {
+            if let Ok(image) = image.as_any().downcast::<CairoImage>() {
+                *texture = Texture::Image(Box::new(self.transform_image(&image)));
+            }
+        }
+    }
+    fn transform_contents(&self, contents: &mut [&mut Rasterizable]) {
+        // Gather every uncached LDR color in the trees so they convert in a single lcms call.
+        let mut cache = self.color_cache.lock().unwrap();
+        let mut pixels = vec![];
+        let mut slots = vec![];
+        for content in contents.iter_mut() {
+            for_each_color(content, &mut |color| match color {
+                Color::LDR(color) => match cache.get(color) {
+                    Some(transformed_color) => slots.push(Ok(transformed_color)),
+                    None => {
+                        slots.push(Err(*color));
+                        pixels.push([color.r, color.g, color.b, color.a]);
+                    }
+                },
+                Color::HDR(color) => slots.push(Ok(self.transform_hdr(*color))),
+            });
+        }
+        self.transform.transform_in_place(&mut pixels);
+        let mut pixels = pixels.into_iter();
+        let mut slots = slots.into_iter().map(|slot| {
+            slot.unwrap_or_else(|color| {
+                let [r, g, b, a] = pixels.next().unwrap();
+                let transformed_color = LDRColor::rgba(r, g, b, a);
+                cache.insert(color, transformed_color);
+                transformed_color
+            })
+        });
+        for content in contents.iter_mut() {
+            for_each_color(content, &mut |color| {
+                *color = Color::LDR(slots.next().unwrap())
+            });
+            for_each_texture(content, &mut |texture| self.transform_texture(texture));
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -92,131 +502,284 @@ pub(crate) struct Profile {
 }
 
 impl Profile {
+    fn new(
+        context: ThreadContext,
+        display_profile: LcmsProfile,
+        cache_size: usize,
+    ) -> Result<Profile, ()> {
+        let srgb_profile = LcmsProfile::new_srgb_context(&context);
+        let intent = Intent::Perceptual;
+        let (transform, hdr_transforms) =
+            build_transforms(&context, &srgb_profile, &display_profile, intent, None)?;
+        let state = ProfileState {
+            transform,
+            display_profile,
+            srgb_profile,
+            proof: None,
+            hdr_transforms,
+            color_cache: Mutex::new(ColorCache::new(cache_size)),
+            image_cache: Mutex::new(VecDeque::with_capacity(IMAGE_CACHE_SIZE)),
+            intent,
+            context,
+        };
+        Ok(Profile {
+            state: Arc::new(RwLock::new(state)),
+        })
+    }
+    pub(crate) fn srgb() -> Profile {
+        let context = ThreadContext::new();
+        let display_profile = LcmsProfile::new_srgb_context(&context);
+        Profile::new(context, display_profile, DEFAULT_CACHE_SIZE).unwrap()
+    }
+    pub(crate) fn from_icc(data: &'_ [u8]) -> Result<Profile, ()> {
+        let context = ThreadContext::new();
+        let display_profile = LcmsProfile::new_icc_context(&context, data).map_err(|_| ())?;
+        Profile::new(context, display_profile, DEFAULT_CACHE_SIZE)
+    }
     #[cfg(target_os = "macos")]
     fn from_window_macos(window: &Window) -> Result<Profile, ()> {
         use glutin::os::macos::WindowExt;
         let os_window = window.get_nswindow();
-        let display_profile =
-            lcms2::Profile::new_icc(cm_backing::get_profile_data(os_window as *const c_void)?)
-                .map_err(|_| ())?;
-        Ok(Profile {
-            state: Arc::new(RwLock::new(ProfileState {
-                display_profile,
-                srgb_profile: lcms2::Profile::new_srgb(),
-                color_cache: HashMap::with_capacity(10),
-                color_cache_queue: VecDeque::with_capacity(10),
-            })),
-        })
+        Profile::from_icc(cm_backing::get_profile_data(os_window as *const c_void)?)
     }
     #[cfg(target_os = "windows")]
     fn from_window_windows(window: &Window) -> Result<Profile, ()> {
         use glutin::os::windows::WindowExt;
         let os_window = window.get_hwnd();
-        let display_profile =
-            lcms2::Profile::new_icc(&cm_backing::get_profile_data(os_window)?).map_err(|_| ())?;
-        Ok(Profile {
-            state: Arc::new(RwLock::new(ProfileState {
-                display_profile,
-                srgb_profile: lcms2::Profile::new_srgb(),
-                color_cache: HashMap::with_capacity(10),
-                color_cache_queue: VecDeque::with_capacity(10),
-            })),
-        })
+        Profile::from_icc(&cm_backing::get_profile_data(os_window)?)
+    }
+    #[cfg(target_os = "linux")]
+    fn from_window_linux(window: &Window) -> Result<Profile, ()> {
+        use glutin::os::unix::WindowExt;
+        let display = window.get_xlib_display().ok_or(())?;
+        let screen = window.get_xlib_screen_id().ok_or(())?;
+        Profile::from_icc(&cm_backing::get_profile_data(
+            display as *mut c_void,
+            screen,
+        )?)
     }
     pub(crate) fn from_window(window: &Window) -> Result<Profile, ()> {
         #[cfg(target_os = "macos")]
         return Profile::from_window_macos(window);
         #[cfg(target_os = "windows")]
         return Profile::from_window_windows(window);
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        #[cfg(target_os = "linux")]
+        return Profile::from_window_linux(window);
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
         Err(())
     }
-    pub(crate) fn transform(&self, color: LDRColor) -> LDRColor {
+    pub(crate) fn set_intent(&self, intent: RenderingIntent) -> Result<(), ()> {
+        let mut state = self.state.write().unwrap();
+        let previous_intent = std::mem::replace(&mut state.intent, lcms_intent(intent));
+        if state.build_transforms().is_err() {
+            state.intent = previous_intent;
+            state.build_transforms()?;
+            return Err(());
+        }
+        Ok(())
+    }
+    pub(crate) fn set_cache_size(&self, size: usize) {
         let state = self.state.read().unwrap();
-        if let Some(transformed_color) = state.color_cache.get(&color) {
-            return transformed_color.clone();
+        *state.color_cache.lock().unwrap() = ColorCache::new(size);
+    }
+    pub(crate) fn set_soft_proof(&self, proof: Option<&SoftProof>) -> Result<(), ()> {
+        let mut state = self.state.write().unwrap();
+        let proof = match proof {
+            Some(proof) => Some(Proof {
+                profile: LcmsProfile::new_icc_context(&state.context, proof.profile.data())
+                    .map_err(|_| ())?,
+                intent: lcms_intent(proof.intent),
+                gamut_warning: proof.gamut_warning,
+            }),
+            None => None,
+        };
+        let previous_proof = std::mem::replace(&mut state.proof, proof);
+        if state.build_transforms().is_err() {
+            state.proof = previous_proof;
+            state.build_transforms()?;
+            return Err(());
         }
-        let t = Transform::new(
-            &state.srgb_profile,
-            PixelFormat::RGBA_8,
+        Ok(())
+    }
+    pub(crate) fn export_image(
+        &self,
+        image: &CairoImage,
+        profile: &ColorProfile,
+    ) -> Result<CairoImage, ()> {
+        let state = self.state.read().unwrap();
+        let destination_profile =
+            LcmsProfile::new_icc_context(&state.context, profile.data()).map_err(|_| ())?;
+        let t = build_transform(
+            &state.context,
             &state.display_profile,
             PixelFormat::RGBA_8,
-            Intent::Perceptual,
-        )
-        .unwrap();
-        let source_pixels: &mut [[u8; 4]] = &mut [[color.r, color.g, color.b, color.a]];
-        t.transform_in_place(source_pixels);
-        let transformed_color = LDRColor::rgba(
-            source_pixels[0][0],
-            source_pixels[0][1],
-            source_pixels[0][2],
-            source_pixels[0][3],
-        );
-        drop(state);
-        let mut state = self.state.write().unwrap();
-        if state.color_cache_queue.len() >= state.color_cache_queue.capacity() {
-            let rm_color = state.color_cache_queue.pop_front().unwrap();
-            state.color_cache.remove(&rm_color).unwrap();
-        }
-        state.color_cache_queue.push_back(color);
-        state.color_cache.insert(color, transformed_color);
-        transformed_color
-    }
-    pub(crate) fn transform_texture(&self, texture: Texture) -> Texture {
-        match texture {
-            Texture::Solid(color) => Texture::Solid(self.transform(color)),
-            Texture::LinearGradient(mut gradient) => {
-                gradient.stops = gradient
-                    .stops
-                    .iter()
-                    .map(|stop| GradientStop {
-                        offset: stop.offset,
-                        color: self.transform(stop.color),
-                    })
-                    .collect();
-                Texture::LinearGradient(gradient)
-            }
-            Texture::RadialGradient(mut gradient) => {
-                gradient.stops = gradient
-                    .stops
-                    .iter()
-                    .map(|stop| GradientStop {
-                        offset: stop.offset,
-                        color: self.transform(stop.color),
+            &destination_profile,
+            PixelFormat::RGBA_8,
+            state.intent,
+            None,
+        )?;
+        let exported_image = image.duplicate().with_color_profile(Some(profile.clone()));
+        transform_surface(&exported_image, &t);
+        Ok(exported_image)
+    }
+    /// Converts the colors of every provided tree in a single batch.
+    pub(crate) fn transform_contents(&self, contents: &mut [&mut Rasterizable]) {
+        self.state.read().unwrap().transform_contents(contents);
+    }
+    /// Returns whether both handles refer to the same profile.
+    pub(crate) fn ptr_eq(&self, other: &Profile) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::path::{Fill, GradientStop, LinearGradient, Path};
+
+    fn gradient_scene(paths: usize, stops: usize) -> Vec<Rasterizable> {
+        (0..paths)
+            .map(|path| {
+                let stops = (0..stops)
+                    .map(|stop| {
+                        let value = (path * stops + stop) as u32;
+                        GradientStop::new(
+                            stop as f64 / stops as f64,
+                            LDRColor::rgba(
+                                value as u8,
+                                (value >> 8) as u8,
+                                (value >> 4) as u8,
+                                255,
+                            ),
+                        )
+                        .unwrap()
                     })
                     .collect();
-                Texture::RadialGradient(gradient)
-            }
-            texture => texture,
+                Rasterizable::Path(Box::new(Path {
+                    segments: vec![],
+                    clip_segments: vec![],
+                    stroke: None,
+                    fill: Some(Fill {
+                        content: Texture::LinearGradient(LinearGradient {
+                            stops,
+                            start: (0., 0.).into(),
+                            end: (100., 0.).into(),
+                        }),
+                    }),
+                    shadows: vec![],
+                    closed: true,
+                }))
+            })
+            .collect()
+    }
+
+    fn stop_colors(contents: &[Rasterizable]) -> Vec<Color> {
+        let mut colors = vec![];
+        for content in contents {
+            for_each_color(&mut content.clone(), &mut |color| colors.push(*color));
         }
+        colors
     }
-    pub(crate) fn transform_content(&self, content: Rasterizable) -> Rasterizable {
-        match content {
-            Rasterizable::Text(mut text) => Rasterizable::Text({
-                text.color = self.transform(text.color);
-                text
-            }),
-            Rasterizable::Path(path) => Rasterizable::Path(Box::new(Path {
-                segments: path.segments,
-                clip_segments: path.clip_segments,
-                stroke: path.stroke.map(|mut stroke| {
-                    stroke.content = self.transform_texture(stroke.content);
-                    stroke
-                }),
-                fill: path.fill.map(|fill| Fill {
-                    content: self.transform_texture(fill.content),
+
+    fn linear_display(space: ColorSpace, cache_size: usize) -> Profile {
+        let context = ThreadContext::new();
+        let display_profile = linear_profile(&context, space);
+        Profile::new(context, display_profile, cache_size).unwrap()
+    }
+
+    // Converts a color the way the cache-miss path used to, building a transform for it alone.
+    fn legacy_transform(
+        context: &ThreadContext,
+        display: &LcmsProfile,
+        color: LDRColor,
+    ) -> LDRColor {
+        let t: PixelTransform = build_transform(
+            context,
+            &LcmsProfile::new_srgb_context(context),
+            PixelFormat::RGBA_8,
+            display,
+            PixelFormat::RGBA_8,
+            Intent::Perceptual,
+            None,
+        )
+        .unwrap();
+        let pixels = &mut [[color.r, color.g, color.b, color.a]];
+        t.transform_in_place(pixels);
+        let [r, g, b, a] = pixels[0];
+        LDRColor::rgba(r, g, b, a)
+    }
+
+    #[test]
+    fn batched_transform_matches_per_color() {
+        let context = ThreadContext::new();
+        let display = linear_profile(&context, ColorSpace::DisplayP3);
+        let profile = linear_display(ColorSpace::DisplayP3, 16);
+        let mut contents = gradient_scene(8, 8);
+        let expected: Vec<_> = stop_colors(&contents)
+            .into_iter()
+            .map(|color| match color {
+                Color::LDR(color) => Color::LDR(legacy_transform(&context, &display, color)),
+                color => color,
+            })
+            .collect();
+        profile.transform_contents(&mut contents.iter_mut().collect::<Vec<_>>());
+        assert_eq!(stop_colors(&contents), expected);
+        // A second pass is served partly from the cache and must agree.
+        let mut contents = gradient_scene(8, 8);
+        profile.transform_contents(&mut contents.iter_mut().collect::<Vec<_>>());
+        assert_eq!(stop_colors(&contents), expected);
+    }
+
+    #[test]
+    fn soft_proof_gamut_warning() {
+        // A device with primaries pulled in toward white cannot reproduce saturated sRGB colors.
+        let context = ThreadContext::new();
+        let chromaticity = |(x, y): (f64, f64)| CIExyY { x, y, Y: 1. };
+        let linear = ToneCurve::new(1.);
+        let device = LcmsProfile::new_rgb_context(
+            &context,
+            &chromaticity(ColorSpace::Srgb.white_point()),
+            &CIExyYTRIPLE {
+                Red: chromaticity((0.45, 0.33)),
+                Green: chromaticity((0.3, 0.45)),
+                Blue: chromaticity((0.25, 0.25)),
+            },
+            &[&linear, &linear, &linear],
+        )
+        .unwrap();
+        let warning = LDRColor::rgba(0, 255, 0, 255);
+        let proof = SoftProof::new(ColorProfile::from_icc(&device.icc().unwrap()).unwrap())
+            .gamut_warning(warning);
+        let profile = Profile::srgb();
+        let gray = LDRColor::rgba(128, 128, 128, 255);
+        let red = LDRColor::rgba(255, 0, 0, 255);
+        let transform = |profile: &Profile| {
+            let mut contents = vec![Rasterizable::Path(Box::new(Path {
+                segments: vec![],
+                clip_segments: vec![],
+                stroke: None,
+                fill: Some(Fill {
+                    content: Texture::LinearGradient(LinearGradient {
+                        stops: vec![
+                            GradientStop::new(0., gray).unwrap(),
+                            GradientStop::new(1., red).unwrap(),
+                        ],
+                        start: (0., 0.).into(),
+                        end: (1., 0.).into(),
+                    }),
                 }),
-                shadows: path
-                    .shadows
-                    .iter()
-                    .map(|shadow| {
-                        let mut corrected_shadow = shadow.clone();
-                        corrected_shadow.color = self.transform(shadow.color);
-                        corrected_shadow
-                    })
-                    .collect(),
-                closed: path.closed,
-            })),
-        }
+                shadows: vec![],
+                closed: true,
+            }))];
+            profile.transform_contents(&mut contents.iter_mut().collect::<Vec<_>>());
+            stop_colors(&contents)
+        };
+        assert_ne!(transform(&profile)[1], Color::LDR(warning));
+        profile.set_soft_proof(Some(&proof)).unwrap();
+        let proofed = transform(&profile);
+        assert_ne!(proofed[0], Color::LDR(warning));
+        assert_eq!(proofed[1], Color::LDR(warning));
+        profile.set_soft_proof(None).unwrap();
+        assert_eq!(transform(&profile)[1], Color::LDR(red));
     }
 }
@@ -0,0 +1,142 @@
+use crate::graphics::{Image, LDRColor, Texture2, Vector2};
+
+#[cfg(target_os = "linux")]
+mod cursor_linux {
+    use super::super::pure2d::premultiply;
+    use crate::graphics::{Image, LDRColor, Texture2, Vector2};
+    use glutin::os::unix::WindowExt;
+    use x11_dl::{xcursor, xlib};
+
+    thread_local! {
+        static XLIB: Option<xlib::Xlib> = xlib::Xlib::open().ok();
+        static XCURSOR: Option<xcursor::Xcursor> = xcursor::Xcursor::open().ok();
+    }
+
+    // Xcursor expects premultiplied ARGB pixels.
+    pub(super) fn cursor_pixel(color: LDRColor) -> u32 {
+        let [r, g, b, a] = premultiply([color.r, color.g, color.b, color.a]);
+        u32::from_be_bytes([a, r, g, b])
+    }
+
+    pub(crate) struct CustomCursor {
+        display: *mut xlib::Display,
+        cursor: xlib::Cursor,
+    }
+
+    impl CustomCursor {
+        pub(crate) fn new(
+            window: &glutin::Window,
+            image: &Image<LDRColor, Texture2>,
+            hotspot: Vector2,
+        ) -> Option<CustomCursor> {
+            // Windows without an X display, e.g. on Wayland, cannot take X cursors.
+            let display = window.get_xlib_display()? as *mut xlib::Display;
+            let (width, height) = (image.format.width, image.format.height);
+            if width == 0 || height == 0 || image.pixels.len() != (width * height) as usize {
+                return None;
+            }
+            XCURSOR.with(|xcursor| {
+                let xcursor = xcursor.as_ref()?;
+                unsafe {
+                    let cursor_image = (xcursor.XcursorImageCreate)(width as i32, height as i32);
+                    if cursor_image.is_null() {
+                        return None;
+                    }
+                    (*cursor_image).xhot = (hotspot.x.max(0.) as u32).min(width - 1);
+                    (*cursor_image).yhot = (hotspot.y.max(0.) as u32).min(height - 1);
+                    let pixels =
+                        std::slice::from_raw_parts_mut((*cursor_image).pixels, image.pixels.len());
+                    for (pixel, color) in pixels.iter_mut().zip(&image.pixels) {
+                        *pixel = cursor_pixel(*color);
+                    }
+                    let cursor = (xcursor.XcursorImageLoadCursor)(display, cursor_image);
+                    (xcursor.XcursorImageDestroy)(cursor_image);
+                    if cursor == 0 {
+                        None
+                    } else {
+                        Some(CustomCursor { display, cursor })
+                    }
+                }
+            })
+        }
+        pub(crate) fn apply(&self, window: &glutin::Window) {
+            if let Some(window) = window.get_xlib_window() {
+                XLIB.with(|xlib| {
+                    if let Some(xlib) = xlib {
+                        unsafe {
+                            (xlib.XDefineCursor)(self.display, window, self.cursor);
+                            (xlib.XFlush)(self.display);
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    impl Drop for CustomCursor {
+        fn drop(&mut self) {
+            XLIB.with(|xlib| {
+                if let Some(xlib) = xlib {
+                    unsafe {
+                        (xlib.XFreeCursor)(self.display, self.cursor);
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Whether custom cursor images can be shown on this platform. Only X11 is supported, as glutin does not expose custom cursors.
+pub(crate) const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// A custom cursor image loaded into the windowing system.
+pub(crate) struct CustomCursor {
+    #[cfg(target_os = "linux")]
+    cursor: cursor_linux::CustomCursor,
+}
+
+impl CustomCursor {
+    /// Loads the provided image as a cursor for the window, or returns `None` if the windowing system cannot show it.
+    pub(crate) fn new(
+        window: &glutin::Window,
+        image: &Image<LDRColor, Texture2>,
+        hotspot: Vector2,
+    ) -> Option<CustomCursor> {
+        #[cfg(target_os = "linux")]
+        return cursor_linux::CustomCursor::new(window, image, hotspot)
+            .map(|cursor| CustomCursor { cursor });
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (window, image, hotspot);
+            None
+        }
+    }
+    /// Shows the cursor over the window, replacing the cursor set through glutin.
+    pub(crate) fn apply(&self, window: &glutin::Window) {
+        #[cfg(target_os = "linux")]
+        self.cursor.apply(window);
+
+        #[cfg(not(target_os = "linux"))]
+        let _ = window;
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::cursor_linux::cursor_pixel;
+    use crate::graphics::LDRColor;
+
+    #[test]
+    fn cursor_pixels() {
+        assert_eq!(
+            cursor_pixel(LDRColor::rgba(255, 128, 0, 255)),
+            0xff_ff_80_00
+        );
+        assert_eq!(
+            cursor_pixel(LDRColor::rgba(255, 128, 0, 128)),
+            0x80_80_40_00
+        );
+        assert_eq!(cursor_pixel(LDRColor::rgba(255, 255, 255, 0)), 0);
+    }
+}
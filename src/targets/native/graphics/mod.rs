@@ -1,8 +1,17 @@
-use crate::graphics::canvas::InteractiveCanvas;
+use crate::graphics::{canvas::InteractiveCanvas, Image, LDRColor, Texture2};
 
 mod cm;
+mod cursor;
 mod pure2d;
 
 pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
     pure2d::new()
 }
+
+pub(crate) fn encode_png(image: Image<LDRColor, Texture2>) -> Option<Vec<u8>> {
+    pure2d::encode_png(image)
+}
+
+pub(crate) fn decode_png(data: &[u8]) -> Option<Image<LDRColor, Texture2>> {
+    pure2d::decode_png(data)
+}
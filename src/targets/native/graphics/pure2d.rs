@@ -1,18 +1,28 @@
-use super::cm::Profile;
-use crate::graphics::path::{Path, Segment, StrokeCapType, StrokeJoinType, Texture};
-use crate::graphics::text::{Origin, Text, Weight, Wrap};
+use super::cm::{Profile, DEFAULT_CACHE_SIZE};
+use super::cursor::{self, CustomCursor};
+use crate::errors::Error;
+use crate::graphics::color::{ColorProfile, RenderingIntent, SoftProof};
+use crate::graphics::path::{
+    Path, Segment, Shadow, Stroke, StrokeCapType, StrokeJoinType, Texture,
+};
+use crate::graphics::text::{
+    Align, DecorationLine, Direction, FontMetrics, Origin, Stretch, Text, Wrap,
+};
 use crate::graphics::{
     canvas::{
-        ActiveCanvas, Canvas, CanvasContext, Content, Frame, InactiveCanvas, InteractiveCanvas,
-        Object, Rasterizable, Rasterizer, Ticker,
+        timing::TickDispatcher, ActiveCanvas, Canvas, CanvasContext, Content, Cursor, CursorGrab,
+        CursorIcon, Frame, FrameStats, InactiveCanvas, InteractiveCanvas, Object, Rasterizable,
+        Rasterizer, Ticker, Window, WindowConfig,
     },
-    Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
+    Color, Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
 };
 use crate::input::{
-    keyboard::{self, Event as KeyboardEvent},
+    keyboard::{self, Event as KeyboardEvent, Modifiers},
     mouse::{self, Event as MouseEvent},
-    windowing::Event as WindowingEvent,
-    Event, Input, Provider,
+    pointer::{self, Event as PointerEvent},
+    text,
+    windowing::{Event as WindowingEvent, WindowId},
+    Event, Input, Kind, Provider,
 };
 use crate::targets::native;
 use crate::util::ObserverCell;
@@ -21,20 +31,26 @@ use futures::{task::AtomicTask, Async, Poll, Stream};
 
 use std::{
     any::Any,
+    collections::{HashMap, HashSet},
     ffi::{c_void, CString},
     ops::Deref,
-    sync::{Arc, Mutex, RwLock},
-    time::SystemTime,
+    sync::{Arc, Mutex, RwLock, RwLockWriteGuard},
+    time::Instant,
+};
+
+use glutin::{
+    dpi::{LogicalPosition, LogicalSize},
+    ContextTrait,
 };
 
-use glutin::{dpi::LogicalSize, ContextTrait};
+use glib::translate::{from_glib, from_glib_full, ToGlibPtr};
 
 use cairo::{
     Antialias, FontOptions, Format, Gradient, HintStyle, ImageSurface, LineCap, LineJoin,
     LinearGradient, Matrix, Operator, Pattern, RadialGradient, Status, SubpixelOrder,
 };
 
-use pango::{FontDescription, Layout, LayoutExt};
+use pango::{ContextExt, FontDescription, Layout, LayoutExt};
 
 use gl::types::*;
 
@@ -47,7 +63,7 @@ static SYSTEM_FONT: &str = "San Francisco";
 #[cfg(target_os = "linux")]
 static SYSTEM_FONT: &str = "DejaVu Sans";
 
-struct CairoSurface(ImageSurface);
+pub(super) struct CairoSurface(ImageSurface);
 
 struct CairoContext(cairo::Context);
 
@@ -71,7 +87,7 @@ impl Deref for CairoContext {
     }
 }
 
-struct CairoImage(Arc<Mutex<CairoSurface>>);
+pub(super) struct CairoImage(Arc<Mutex<CairoSurface>>, Option<ColorProfile>);
 
 fn boxes_for_gauss(sigma: f64, n: u32) -> Vec<u32> {
     let nf = f64::from(n);
@@ -92,7 +108,46 @@ fn boxes_for_gauss(sigma: f64, n: u32) -> Vec<u32> {
 
 impl CairoImage {
     fn new(surface: CairoSurface) -> CairoImage {
-        CairoImage(Arc::new(Mutex::new(surface)))
+        CairoImage(Arc::new(Mutex::new(surface)), None)
+    }
+    pub(super) fn with_color_profile(mut self, profile: Option<ColorProfile>) -> CairoImage {
+        self.1 = profile;
+        self
+    }
+    pub(super) fn surface(&self) -> &Arc<Mutex<CairoSurface>> {
+        &self.0
+    }
+    pub(super) fn embedded_profile(&self) -> Option<&ColorProfile> {
+        self.1.as_ref()
+    }
+    pub(super) fn duplicate(&self) -> CairoImage {
+        let source = &self.0.lock().unwrap().0;
+        let surface =
+            ImageSurface::create(Format::ARgb32, source.get_width(), source.get_height()).unwrap();
+        {
+            let context = cairo::Context::new(&surface);
+            context.set_source_surface(source, 0., 0.);
+            context.set_operator(Operator::Source);
+            context.paint();
+        }
+        CairoImage::new(CairoSurface(surface)).with_color_profile(self.1.clone())
+    }
+    pub(super) fn with_pixels<F>(&self, modify: F)
+    where
+        F: FnOnce(&mut [[u8; 4]]),
+    {
+        let (width, height) = {
+            let surface = &self.0.lock().unwrap().0;
+            (surface.get_width() as u32, surface.get_height() as u32)
+        };
+        let data: &mut [[u8; 4]] = unsafe {
+            std::slice::from_raw_parts_mut(
+                self.get_data_ptr() as *mut [u8; 4],
+                (width * height) as usize,
+            )
+        };
+        modify(data);
+        unsafe { cairo_sys::cairo_surface_mark_dirty(self.0.lock().unwrap().0.to_raw_none()) };
     }
     fn box_blur(&self, data: &mut [[u8; 4]], width: u32, height: u32, radius: u32, channel: usize) {
         let mut target = vec![[0, 0, 0, 0]; data.len()];
@@ -233,7 +288,7 @@ impl CairoImage {
 
 impl Clone for CairoImage {
     fn clone(&self) -> Self {
-        CairoImage(self.0.clone())
+        CairoImage(self.0.clone(), self.1.clone())
     }
 }
 
@@ -245,31 +300,81 @@ fn pixels_to_pango_pixels(pixels: f64) -> i32 {
     (pixels * f64::from(pango::SCALE)) as i32
 }
 
+// Cairo stores ARGB32 pixels as native-endian 32-bit words.
+#[cfg(target_endian = "little")]
+pub(super) fn pixel_to_rgba(pixel: [u8; 4]) -> [u8; 4] {
+    [pixel[2], pixel[1], pixel[0], pixel[3]]
+}
+
+#[cfg(target_endian = "big")]
+pub(super) fn pixel_to_rgba(pixel: [u8; 4]) -> [u8; 4] {
+    [pixel[1], pixel[2], pixel[3], pixel[0]]
+}
+
+#[cfg(target_endian = "little")]
+pub(super) fn rgba_to_pixel(color: [u8; 4]) -> [u8; 4] {
+    [color[2], color[1], color[0], color[3]]
+}
+
+#[cfg(target_endian = "big")]
+pub(super) fn rgba_to_pixel(color: [u8; 4]) -> [u8; 4] {
+    [color[3], color[0], color[1], color[2]]
+}
+
+pub(super) fn premultiply(color: [u8; 4]) -> [u8; 4] {
+    let alpha = u32::from(color[3]);
+    let scale = |channel: u8| ((u32::from(channel) * alpha + 127) / 255) as u8;
+    [scale(color[0]), scale(color[1]), scale(color[2]), color[3]]
+}
+
+pub(super) fn unpremultiply(color: [u8; 4]) -> [u8; 4] {
+    let alpha = u32::from(color[3]);
+    if alpha == 0 {
+        return [0, 0, 0, 0];
+    }
+    let scale = |channel: u8| ((u32::from(channel) * 255 + alpha / 2) / alpha).min(255) as u8;
+    [scale(color[0]), scale(color[1]), scale(color[2]), color[3]]
+}
+
 impl ImageRepresentation for CairoImage {
     fn get_size(&self) -> Vector2 {
+        let surface = self.0.lock().unwrap();
         (
-            f64::from(self.0.lock().unwrap().get_width()),
-            f64::from(self.0.lock().unwrap().get_height()),
+            f64::from(surface.get_width()),
+            f64::from(surface.get_height()),
         )
             .into()
     }
 
     fn box_clone(&self) -> Box<dyn ImageRepresentation> {
-        Box::new(CairoImage(self.0.clone()))
+        Box::new(self.clone())
     }
 
     fn as_texture(&self) -> Image<LDRColor, Texture2> {
+        let size = self.get_size();
+        let mut pixels = vec![];
+        self.with_pixels(|data| {
+            pixels = data
+                .iter()
+                .map(|pixel| {
+                    let [r, g, b, a] = unpremultiply(pixel_to_rgba(*pixel));
+                    LDRColor::rgba(r, g, b, a)
+                })
+                .collect();
+        });
         Image {
-            pixels: vec![],
+            pixels,
             format: Texture2 {
-                height: 0,
-                width: 0,
+                width: size.x as u32,
+                height: size.y as u32,
             },
+            color_profile: self.1.clone(),
         }
     }
 
     fn from_texture(texture: Image<LDRColor, Texture2>) -> CairoImage {
-        CairoImage::new(CairoSurface(
+        let pixels = texture.pixels;
+        let image = CairoImage::new(CairoSurface(
             ImageSurface::create(
                 Format::ARgb32,
                 texture.format.width as i32,
@@ -277,10 +382,21 @@ impl ImageRepresentation for CairoImage {
             )
             .unwrap(),
         ))
+        .with_color_profile(texture.color_profile);
+        image.with_pixels(|data| {
+            for (pixel, color) in data.iter_mut().zip(pixels.iter()) {
+                *pixel = rgba_to_pixel(premultiply([color.r, color.g, color.b, color.a]));
+            }
+        });
+        image
+    }
+
+    fn color_profile(&self) -> Option<ColorProfile> {
+        self.1.clone()
     }
 
     fn as_any(&self) -> Box<dyn Any> {
-        Box::new(CairoImage(self.0.clone()))
+        Box::new(self.clone())
     }
 }
 
@@ -289,14 +405,51 @@ struct CairoFrameState {
     contents: Vec<CairoObject>,
     viewport: Rect,
     color_profile: Option<Profile>,
+    color_profile_attached: bool,
+    rendering_intent: RenderingIntent,
+    soft_proof: Option<SoftProof>,
+    color_cache_size: usize,
     size: Vector2,
     pixel_ratio: f64,
 }
 
+impl CairoFrameState {
+    /// Marks every object to be converted again from its source colors, as the display transform has changed.
+    fn invalidate_contents(&self) {
+        for object in &self.contents {
+            let mut object_state = object.state.write().unwrap();
+            object_state.converted = false;
+            *object_state.redraw.lock().unwrap() = true;
+        }
+    }
+}
+
 struct CairoFrame {
     state: Arc<RwLock<CairoFrameState>>,
 }
 
+/// Converts content added or updated since the last draw from its source colors to the display profile in a single batch.
+fn convert_contents(profile: Option<&Profile>, objects: &[CairoObject]) {
+    let mut states: Vec<RwLockWriteGuard<'_, CairoObjectState>> = vec![];
+    for object in objects {
+        let mut object_state = object.state.write().unwrap();
+        if object_state.converted {
+            continue;
+        }
+        object_state.content = object_state.source.clone();
+        object_state.converted = true;
+        states.push(object_state);
+    }
+    if let Some(profile) = profile {
+        profile.transform_contents(
+            &mut states
+                .iter_mut()
+                .map(|state| &mut state.content)
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
 fn composite_clip(context: &CairoContext, entity: &Path) {
     if !entity.clip_segments.is_empty() {
         context.move_to(0., 0.);
@@ -326,6 +479,67 @@ fn composite_clip(context: &CairoContext, entity: &Path) {
     }
 }
 
+fn set_source_color(context: &CairoContext, color: Color) {
+    let [r, g, b, a] = color.to_srgb_encoded();
+    context.set_source_rgba(r, g, b, a);
+}
+
+fn set_source_texture(context: &CairoContext, texture: &Texture) {
+    match texture {
+        Texture::Solid(color) => {
+            set_source_color(context, *color);
+        }
+        Texture::LinearGradient(gradient) => {
+            let canvas_gradient = LinearGradient::new(
+                gradient.start.x,
+                gradient.start.y,
+                gradient.end.x,
+                gradient.end.y,
+            );
+            gradient.stops.iter().for_each(|stop| {
+                let [r, g, b, a] = stop.color.to_srgb_encoded();
+                canvas_gradient.add_color_stop_rgba(stop.offset, r, g, b, a)
+            });
+            context.set_source(&Pattern::LinearGradient(canvas_gradient));
+        }
+        Texture::Image(image) => {
+            let pattern = image.as_any().downcast::<CairoImage>().unwrap();
+            let surface = &pattern.0.lock().unwrap().0;
+            //TODO: coordinates here probd shouldn't be 0, 0
+            context.set_source_surface(surface, 0.0, 0.0);
+        }
+        Texture::RadialGradient(gradient) => {
+            let canvas_gradient = RadialGradient::new(
+                gradient.start.x,
+                gradient.start.y,
+                gradient.start_radius,
+                gradient.end.x,
+                gradient.end.y,
+                gradient.end_radius,
+            );
+            gradient.stops.iter().for_each(|stop| {
+                let [r, g, b, a] = stop.color.to_srgb_encoded();
+                canvas_gradient.add_color_stop_rgba(stop.offset, r, g, b, a);
+            });
+            context.set_source(&Pattern::RadialGradient(canvas_gradient));
+        }
+    }
+}
+
+fn set_stroke_style(context: &CairoContext, stroke: &Stroke) {
+    context.set_line_cap(match &stroke.cap {
+        StrokeCapType::Butt => LineCap::Butt,
+        StrokeCapType::Round => LineCap::Round,
+    });
+    context.set_line_join(match &stroke.join {
+        StrokeJoinType::Miter => LineJoin::Miter,
+        StrokeJoinType::Round => LineJoin::Round,
+        StrokeJoinType::Bevel => LineJoin::Bevel,
+    });
+    set_source_texture(context, &stroke.content);
+    context.set_line_width(f64::from(stroke.width));
+}
+
 fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
     context.move_to(0., 0.);
     entity.segments.iter().for_each(|segment| match segment {
@@ -349,70 +563,7 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
     }
     match &entity.stroke {
         Some(stroke) => {
-            context.set_line_cap(match &stroke.cap {
-                StrokeCapType::Butt => LineCap::Butt,
-                StrokeCapType::Round => LineCap::Round,
-            });
-            context.set_line_join(match &stroke.join {
-                StrokeJoinType::Miter => LineJoin::Miter,
-                StrokeJoinType::Round => LineJoin::Round,
-                StrokeJoinType::Bevel => LineJoin::Bevel,
-            });
-            match &stroke.content {
-                Texture::Solid(color) => {
-                    context.set_source_rgba(
-                        f64::from(color.r) / 255.,
-                        f64::from(color.g) / 255.,
-                        f64::from(color.b) / 255.,
-                        f64::from(color.a) / 255.,
-                    );
-                }
-                Texture::LinearGradient(gradient) => {
-                    let canvas_gradient = LinearGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.end.x,
-                        gradient.end.y,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        )
-                    });
-                    context.set_source(&Pattern::LinearGradient(canvas_gradient));
-                }
-                Texture::Image(image) => {
-                    let pattern = image.as_any().downcast::<CairoImage>().unwrap();
-                    let surface = &pattern.0.lock().unwrap().0;
-                    //TODO: coordinates here probd shouldn't be 0, 0
-                    context.set_source_surface(surface, 0.0, 0.0);
-                }
-                Texture::RadialGradient(gradient) => {
-                    let canvas_gradient = RadialGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.start_radius,
-                        gradient.end.x,
-                        gradient.end.y,
-                        gradient.end_radius,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        );
-                    });;
-                    context.set_source(&Pattern::RadialGradient(canvas_gradient));
-                }
-            }
-            context.set_line_width(f64::from(stroke.width));
+            set_stroke_style(context, stroke);
             if entity.fill.is_some() {
                 context.stroke_preserve();
             } else {
@@ -426,60 +577,7 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
     }
     match &entity.fill {
         Some(fill) => {
-            match &fill.content {
-                Texture::Solid(color) => {
-                    context.set_source_rgba(
-                        f64::from(color.r) / 255.,
-                        f64::from(color.g) / 255.,
-                        f64::from(color.b) / 255.,
-                        f64::from(color.a) / 255.,
-                    );
-                }
-                Texture::Image(image) => {
-                    let pattern = image.as_any().downcast::<CairoImage>().unwrap();
-                    let surface = &pattern.0.lock().unwrap().0;
-                    //TODO: coordinates here probd shouldn't be 0, 0
-                    context.set_source_surface(surface, 0.0, 0.0);
-                }
-                Texture::LinearGradient(gradient) => {
-                    let canvas_gradient = LinearGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.end.x,
-                        gradient.end.y,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        )
-                    });
-                    context.set_source(&Pattern::LinearGradient(canvas_gradient));
-                }
-                Texture::RadialGradient(gradient) => {
-                    let canvas_gradient = RadialGradient::new(
-                        gradient.start.x,
-                        gradient.start.y,
-                        gradient.start_radius,
-                        gradient.end.x,
-                        gradient.end.y,
-                        gradient.end_radius,
-                    );
-                    gradient.stops.iter().for_each(|stop| {
-                        canvas_gradient.add_color_stop_rgba(
-                            stop.offset,
-                            f64::from(stop.color.r) / 255.,
-                            f64::from(stop.color.g) / 255.,
-                            f64::from(stop.color.b) / 255.,
-                            f64::from(stop.color.a) / 255.,
-                        );
-                    });
-                    context.set_source(&Pattern::RadialGradient(canvas_gradient));
-                }
-            }
+            set_source_texture(context, &fill.content);
             context.fill();
             if let Texture::Image(_image) = &fill.content {
                 context.scale(pixel_ratio, pixel_ratio);
@@ -489,6 +587,97 @@ fn draw_path(context: &CairoContext, entity: &Path, pixel_ratio: f64) {
     }
 }
 
+fn draw_text_shadow(context: &CairoContext, layout: &Layout, shadow: &Shadow, pixel_ratio: f64) {
+    let (_, extents) = layout.get_pixel_extents();
+    let margin = shadow.blur * 2. + shadow.spread;
+    let surface = ImageSurface::create(
+        Format::ARgb32,
+        ((f64::from(extents.width) + margin * 2.) * pixel_ratio) as i32,
+        ((f64::from(extents.height) + margin * 2.) * pixel_ratio) as i32,
+    )
+    .unwrap();
+    let shadow_context = CairoContext(cairo::Context::new(&surface));
+    shadow_context.scale(pixel_ratio, pixel_ratio);
+    shadow_context.translate(margin - f64::from(extents.x), margin - f64::from(extents.y));
+    pangocairo::functions::update_layout(&shadow_context, layout);
+    pangocairo::functions::layout_path(&shadow_context, layout);
+    set_source_color(&shadow_context, shadow.color.into());
+    if shadow.spread > 0. {
+        shadow_context.set_line_join(LineJoin::Round);
+        shadow_context.set_line_width(shadow.spread * 2.);
+        shadow_context.stroke_preserve();
+    }
+    shadow_context.fill();
+    let image = CairoImage::new(CairoSurface(surface));
+    if shadow.blur != 0. {
+        image.blur(shadow.blur);
+    }
+    context.save();
+    context.translate(
+        shadow.offset.x - margin + f64::from(extents.x),
+        shadow.offset.y - margin + f64::from(extents.y),
+    );
+    context.scale(1. / pixel_ratio, 1. / pixel_ratio);
+    context.set_source_surface(&image.0.lock().unwrap().0, 0., 0.);
+    context.paint();
+    context.restore();
+    pangocairo::functions::update_layout(context, layout);
+}
+
+fn draw_text_decorations(context: &CairoContext, layout: &Layout, entity: &Text) {
+    if entity.decorations.is_empty() {
+        return;
+    }
+    let metrics = match layout.get_context().and_then(|pango_context| {
+        pango_context.get_metrics(
+            layout.get_font_description().as_ref(),
+            None::<&pango::Language>,
+        )
+    }) {
+        Some(metrics) => metrics,
+        None => return,
+    };
+    let mut iter = match layout.get_iter() {
+        Some(iter) => iter,
+        None => return,
+    };
+    let scale = f64::from(pango::SCALE);
+    loop {
+        let (_, extents) = iter.get_line_extents();
+        let baseline = f64::from(iter.get_baseline()) / scale;
+        for decoration in &entity.decorations {
+            let (position, font_thickness) = match decoration.line {
+                DecorationLine::Underline => (
+                    metrics.get_underline_position(),
+                    metrics.get_underline_thickness(),
+                ),
+                DecorationLine::Strikethrough => (
+                    metrics.get_strikethrough_position(),
+                    metrics.get_strikethrough_thickness(),
+                ),
+                DecorationLine::Overline => {
+                    (metrics.get_ascent(), metrics.get_underline_thickness())
+                }
+            };
+            let font_thickness = f64::from(font_thickness) / scale;
+            let thickness = decoration.thickness.unwrap_or(font_thickness);
+            let center = baseline - f64::from(position) / scale + font_thickness / 2.;
+            let color = decoration.color.map(Color::from).unwrap_or(entity.color);
+            context.rectangle(
+                f64::from(extents.x) / scale,
+                center - thickness / 2.,
+                f64::from(extents.width) / scale,
+                thickness,
+            );
+            set_source_color(context, color);
+            context.fill();
+        }
+        if !iter.next_line() {
+            break;
+        }
+    }
+}
+
 impl CairoFrame {
     fn new() -> Box<CairoFrame> {
         let size = Vector2::default();
@@ -499,6 +688,10 @@ impl CairoFrame {
                 contents: vec![],
                 size,
                 color_profile: None,
+                color_profile_attached: false,
+                rendering_intent: RenderingIntent::default(),
+                soft_proof: None,
+                color_cache_size: DEFAULT_CACHE_SIZE,
                 viewport: Rect {
                     size: Vector2::default(),
                     position: (0., 0.).into(),
@@ -522,9 +715,21 @@ impl CairoFrame {
             .unwrap(),
         )))
     }
-    fn set_color_profile(&self, profile: Profile) {
+    fn set_window_color_profile(&self, profile: Profile) {
         let mut state = self.state.write().unwrap();
+        if state.color_profile_attached {
+            return;
+        }
+        // The display profile is attached even when it cannot apply the requested intent or proof, which are dropped instead.
+        if profile.set_intent(state.rendering_intent).is_err() {
+            state.rendering_intent = RenderingIntent::default();
+        }
+        if profile.set_soft_proof(state.soft_proof.as_ref()).is_err() {
+            state.soft_proof = None;
+        }
+        profile.set_cache_size(state.color_cache_size);
         state.color_profile = Some(profile);
+        state.invalidate_contents();
     }
     fn layout_text(&self, entity: &Text) -> Layout {
         let state = self.state.read().unwrap();
@@ -540,17 +745,21 @@ impl CairoFrame {
         let mut font = FontDescription::new();
         font.set_absolute_size(f64::from(pixels_to_pango_pixels(entity.size)));
         font.set_family(SYSTEM_FONT);
-        font.set_weight(match entity.weight {
-            Weight::Bold => pango::Weight::Bold,
-            Weight::Hairline => pango::Weight::Ultralight,
-            Weight::Normal => pango::Weight::Normal,
-            Weight::Heavy => pango::Weight::Heavy,
-            Weight::Thin => pango::Weight::Semilight,
-            Weight::Light => pango::Weight::Light,
-            Weight::Medium => pango::Weight::Medium,
-            Weight::ExtraBold => pango::Weight::Ultrabold,
-            Weight::SemiBold => pango::Weight::Semibold,
+        font.set_weight(from_glib(i32::from(entity.weight.value())));
+        font.set_stretch(match entity.stretch {
+            Stretch::UltraCondensed => pango::Stretch::UltraCondensed,
+            Stretch::ExtraCondensed => pango::Stretch::ExtraCondensed,
+            Stretch::Condensed => pango::Stretch::Condensed,
+            Stretch::SemiCondensed => pango::Stretch::SemiCondensed,
+            Stretch::Normal => pango::Stretch::Normal,
+            Stretch::SemiExpanded => pango::Stretch::SemiExpanded,
+            Stretch::Expanded => pango::Stretch::Expanded,
+            Stretch::ExtraExpanded => pango::Stretch::ExtraExpanded,
+            Stretch::UltraExpanded => pango::Stretch::UltraExpanded,
         });
+        if entity.small_caps {
+            font.set_variant(pango::Variant::SmallCaps);
+        }
         layout.set_font_description(&font);
         if entity.max_width.is_some() {
             layout.set_width(pixels_to_pango_pixels(entity.max_width.unwrap()));
@@ -558,19 +767,57 @@ impl CairoFrame {
         if let Wrap::Normal = entity.wrap {
             layout.set_wrap(pango::WrapMode::Word);
         }
+        // pango only mirrors left and right alignment for RTL paragraphs when auto-dir is enabled,
+        // so explicit right-to-left text is mirrored here instead.
+        let mirrored = match entity.direction {
+            Direction::Auto => {
+                layout.set_auto_dir(true);
+                false
+            }
+            direction => {
+                layout.set_auto_dir(false);
+                if let Some(pango_context) = layout.get_context() {
+                    pango_context.set_base_dir(match direction {
+                        Direction::Rtl => pango::Direction::Rtl,
+                        _ => pango::Direction::Ltr,
+                    });
+                    layout.context_changed();
+                }
+                direction == Direction::Rtl
+            }
+        };
+        layout.set_justify(entity.align == Align::Justify);
+        layout.set_alignment(match (entity.align, mirrored) {
+            (Align::Center, _) => pango::Alignment::Center,
+            (Align::Start, false) | (Align::Justify, false) | (Align::End, true) => {
+                pango::Alignment::Left
+            }
+            (Align::End, false) | (Align::Start, true) | (Align::Justify, true) => {
+                pango::Alignment::Right
+            }
+        });
         layout.set_spacing(pixels_to_pango_pixels(entity.line_height - entity.size));
         let attribute_list = pango::AttrList::new();
         attribute_list.insert(
             pango::Attribute::new_letter_spacing(pixels_to_pango_points(entity.letter_spacing))
                 .unwrap(),
         );
+        if !entity.features.is_empty() {
+            let features = entity
+                .features
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let features: Option<pango::Attribute> = unsafe {
+                from_glib_full(pango_sys::pango_attr_font_features_new(
+                    features.to_glib_none().0,
+                ))
+            };
+            attribute_list.insert(features.unwrap());
+        }
         layout.set_attributes(&attribute_list);
-        context.set_source_rgba(
-            f64::from(entity.color.r) / 255.,
-            f64::from(entity.color.g) / 255.,
-            f64::from(entity.color.b) / 255.,
-            f64::from(entity.color.a) / 255.,
-        );
+        set_source_color(&context, entity.color);
         pangocairo::functions::update_layout(&context, &layout);
         layout
     }
@@ -579,6 +826,85 @@ impl CairoFrame {
         let size = layout.get_pixel_size();
         (f64::from(size.0), f64::from(size.1)).into()
     }
+    fn measure_font(&self, entity: &Text) -> FontMetrics {
+        let layout = self.layout_text(entity);
+        let scale = f64::from(pango::SCALE);
+        let glyph_height = |glyph: &str| {
+            layout.set_text(glyph);
+            let (ink, _) = layout.get_extents();
+            f64::from(layout.get_baseline() - ink.y) / scale
+        };
+        let cap_height = glyph_height("H");
+        let x_height = glyph_height("x");
+        match layout.get_context().and_then(|pango_context| {
+            pango_context.get_metrics(
+                layout.get_font_description().as_ref(),
+                None::<&pango::Language>,
+            )
+        }) {
+            Some(metrics) => FontMetrics {
+                ascent: f64::from(metrics.get_ascent()) / scale,
+                descent: f64::from(metrics.get_descent()) / scale,
+                cap_height,
+                x_height,
+                underline_position: f64::from(-metrics.get_underline_position()) / scale,
+                underline_thickness: f64::from(metrics.get_underline_thickness()) / scale,
+                average_character_width: f64::from(metrics.get_approximate_char_width()) / scale,
+            },
+            None => FontMetrics {
+                cap_height,
+                x_height,
+                ..FontMetrics::default()
+            },
+        }
+    }
+    fn outline_text(&self, entity: &Text) -> Path {
+        let layout = self.layout_text(entity);
+        // A scratch context is used as cairo discards paths on the empty surface of an unsized frame.
+        let surface = ImageSurface::create(Format::ARgb32, 1, 1).unwrap();
+        let context = cairo::Context::new(&surface);
+        pangocairo::functions::update_layout(&context, &layout);
+        pangocairo::functions::layout_path(&context, &layout);
+        let glyph_path = context.copy_path();
+        let mut segments = vec![];
+        let mut subpath_start = Vector2::default();
+        for segment in glyph_path.iter() {
+            match segment {
+                cairo::PathSegment::MoveTo(point) => {
+                    subpath_start = point.into();
+                    segments.push(Segment::MoveTo(subpath_start));
+                }
+                cairo::PathSegment::LineTo(point) => {
+                    segments.push(Segment::LineTo(point.into()));
+                }
+                cairo::PathSegment::CurveTo(handle_1, handle_2, point) => {
+                    segments.push(Segment::CubicTo(
+                        point.into(),
+                        handle_1.into(),
+                        handle_2.into(),
+                    ));
+                }
+                // Segment has no close variant, so subpaths are closed with an explicit line.
+                cairo::PathSegment::ClosePath => {
+                    segments.push(Segment::LineTo(subpath_start));
+                }
+            }
+        }
+        let offset = match entity.origin {
+            Origin::Baseline => f64::from(-layout.get_baseline() / pango::SCALE),
+            Origin::Middle => -(f64::from(layout.get_pixel_size().1) / 2.),
+            Origin::Top => 0.,
+        };
+        Path {
+            segments,
+            clip_segments: vec![],
+            stroke: entity.stroke.clone(),
+            fill: Some(entity.color.into()),
+            shadows: entity.shadows.clone(),
+            closed: false,
+        }
+        .with_offset((0., offset))
+    }
     fn draw_text(&self, matrix: [f64; 6], entity: &Text) {
         {
             let state = self.state.read().unwrap();
@@ -608,7 +934,18 @@ impl CairoFrame {
             }
             _ => {}
         }
+        for shadow in &entity.shadows {
+            draw_text_shadow(&context, &layout, shadow, state.pixel_ratio);
+        }
+        set_source_color(&context, entity.color);
         pangocairo::functions::show_layout(&context, &layout);
+        if let Some(stroke) = &entity.stroke {
+            context.new_path();
+            pangocairo::functions::layout_path(&context, &layout);
+            set_stroke_style(&context, stroke);
+            context.stroke();
+        }
+        draw_text_decorations(&context, &layout, entity);
     }
 
     fn draw_path(&self, matrix: [f64; 6], entity: &Path) {
@@ -652,17 +989,16 @@ impl Frame for CairoFrame {
     }
 
     fn add(&mut self, content: Content) -> Box<dyn Object> {
-        let object = CairoObject::new(
-            content.content,
-            content.transform,
-            content.depth,
-            self.state.read().unwrap().color_profile.clone(),
-        );
+        let object = CairoObject::new(content.content, content.transform, content.depth);
         let mut state = self.state.write().unwrap();
         state.contents.push(object.clone());
         Box::new(object)
     }
 
+    fn get_viewport(&self) -> Rect {
+        self.state.read().unwrap().viewport
+    }
+
     fn set_viewport(&self, viewport: Rect) {
         let mut state = self.state.write().unwrap();
         state.viewport = viewport;
@@ -684,6 +1020,24 @@ impl Frame for CairoFrame {
         self.surface()
     }
 
+    fn to_image_with_profile(
+        &self,
+        profile: ColorProfile,
+    ) -> Result<Box<dyn ImageRepresentation>, Error> {
+        let image = self.surface();
+        let source_profile = self
+            .state
+            .read()
+            .unwrap()
+            .color_profile
+            .clone()
+            .unwrap_or_else(Profile::srgb);
+        source_profile
+            .export_image(&image, &profile)
+            .map(|image| Box::new(image) as Box<dyn ImageRepresentation>)
+            .map_err(|_| Error::color_profile())
+    }
+
     fn measure(&self, input: Rasterizable) -> Vector2 {
         match input {
             Rasterizable::Text(input) => {
@@ -697,6 +1051,69 @@ impl Frame for CairoFrame {
         }
     }
 
+    fn font_metrics(&self, input: Text) -> FontMetrics {
+        self.measure_font(&input)
+    }
+    fn set_color_profile(&self, profile: ColorProfile) -> Result<(), Error> {
+        let profile = Profile::from_icc(profile.data()).map_err(|_| Error::color_profile())?;
+        let mut state = self.state.write().unwrap();
+        profile
+            .set_intent(state.rendering_intent)
+            .and_then(|_| profile.set_soft_proof(state.soft_proof.as_ref()))
+            .map_err(|_| Error::color_profile())?;
+        profile.set_cache_size(state.color_cache_size);
+        state.color_profile = Some(profile);
+        state.color_profile_attached = true;
+        state.invalidate_contents();
+        Ok(())
+    }
+    fn set_rendering_intent(&self, intent: RenderingIntent) -> Result<(), Error> {
+        let mut state = self.state.write().unwrap();
+        if let Some(profile) = &state.color_profile {
+            profile
+                .set_intent(intent)
+                .map_err(|_| Error::color_profile())?;
+        }
+        state.rendering_intent = intent;
+        state.invalidate_contents();
+        Ok(())
+    }
+    fn set_soft_proof(&self, proof: Option<SoftProof>) -> Result<(), Error> {
+        let mut state = self.state.write().unwrap();
+        if state.color_profile.is_none() {
+            if proof.is_none() {
+                return Ok(());
+            }
+            // Offscreen frames without a display profile are proofed back to sRGB.
+            let profile = Profile::srgb();
+            profile
+                .set_intent(state.rendering_intent)
+                .map_err(|_| Error::color_profile())?;
+            profile.set_cache_size(state.color_cache_size);
+            state.color_profile = Some(profile);
+        }
+        if let Some(profile) = &state.color_profile {
+            profile
+                .set_soft_proof(proof.as_ref())
+                .map_err(|_| Error::color_profile())?;
+        }
+        state.soft_proof = proof;
+        state.invalidate_contents();
+        Ok(())
+    }
+
+    fn set_color_cache_size(&self, size: usize) {
+        let mut state = self.state.write().unwrap();
+        state.color_cache_size = size;
+        if let Some(profile) = &state.color_profile {
+            profile.set_cache_size(size);
+        }
+    }
+
+    fn outline(&self, input: Text) -> Option<Path> {
+        Some(self.outline_text(&input))
+    }
+
     fn box_clone(&self) -> Box<dyn Frame> {
         Box::new(CairoFrame {
             state: self.state.clone(),
@@ -707,6 +1124,7 @@ impl Frame for CairoFrame {
 
     fn draw(&self) {
         let state = self.state.read().unwrap();
+        convert_contents(state.color_profile.as_ref(), &state.contents);
         {
             let context = state.context.lock().unwrap();
             context.set_source_rgb(1., 1., 1.);
@@ -761,7 +1179,12 @@ impl Frame for CairoFrame {
 
 struct CairoObjectState {
     orientation: Transform2,
+    // The content as provided, kept so it can be converted again when the display transform changes.
+    source: Rasterizable,
+    // The content with its colors converted to the display profile of the frame.
     content: Rasterizable,
+    // Whether the content is up to date with the source and the display profile.
+    converted: bool,
     depth: u32,
     redraw: Mutex<bool>,
 }
@@ -769,28 +1192,20 @@ struct CairoObjectState {
 #[derive(Clone)]
 struct CairoObject {
     state: Arc<RwLock<CairoObjectState>>,
-    color_profile: Option<Profile>,
     cache_surface: Arc<Mutex<Option<(CairoContext, Vector2)>>>,
 }
 
 impl CairoObject {
-    fn new(
-        content: Rasterizable,
-        orientation: Transform2,
-        depth: u32,
-        color_profile: Option<Profile>,
-    ) -> CairoObject {
+    fn new(content: Rasterizable, orientation: Transform2, depth: u32) -> CairoObject {
         CairoObject {
             state: Arc::new(RwLock::new(CairoObjectState {
                 orientation,
-                content: match color_profile.clone() {
-                    Some(color_profile) => color_profile.transform_content(content),
-                    None => content,
-                },
+                source: content.clone(),
+                content,
+                converted: false,
                 depth,
                 redraw: Mutex::new(true),
             })),
-            color_profile,
             cache_surface: Arc::new(Mutex::new(None)),
         }
     }
@@ -878,12 +1293,7 @@ impl CairoObject {
                     if path.closed {
                         context.close_path();
                     }
-                    context.set_source_rgba(
-                        f64::from(shadow.color.r) / 255.,
-                        f64::from(shadow.color.g) / 255.,
-                        f64::from(shadow.color.b) / 255.,
-                        f64::from(shadow.color.a) / 255.,
-                    );
+                    set_source_color(&context, shadow.color.into());
                     context.fill();
                     let image = CairoImage::new(CairoSurface(surface));
                     if shadow.blur != 0. {
@@ -926,7 +1336,7 @@ impl Object for CairoObject {
     fn update(&mut self, input: Rasterizable) {
         let mut state = self.state.write().unwrap();
         *state.redraw.lock().unwrap() = if let Rasterizable::Path(path) = &input {
-            if let Rasterizable::Path(current_path) = &self.state.read().unwrap().content {
+            if let Rasterizable::Path(current_path) = &state.source {
                 current_path.shadows != path.shadows
                     || current_path.segments != path.segments
                     || !(current_path.clip_segments.is_empty()
@@ -937,10 +1347,8 @@ impl Object for CairoObject {
         } else {
             false
         };
-        state.content = match self.color_profile.clone() {
-            Some(color_profile) => color_profile.transform_content(input),
-            None => input,
-        };
+        state.source = input;
+        state.converted = false;
     }
     fn get_depth(&self) -> u32 {
         self.state.read().unwrap().depth
@@ -956,6 +1364,7 @@ impl Object for CairoObject {
 #[derive(Clone)]
 struct Cairo {
     state: Arc<RwLock<CairoState>>,
+    window: CairoWindow,
 }
 
 fn new_shader(source: &str, kind: GLenum) -> GLuint {
@@ -968,64 +1377,151 @@ fn new_shader(source: &str, kind: GLenum) -> GLuint {
     }
 }
 
-struct CairoState {
-    root_frame: Option<Box<dyn Frame>>,
-    event_sender: Sender<Event>,
-    event_stream: Receiver<Event>,
-    event_task: Arc<AtomicTask>,
-    tick_handlers: Vec<Box<dyn FnMut(f64) + Send + Sync>>,
-    size: ObserverCell<Vector2>,
+enum WindowCommand {
+    Title(String),
+    Size(Vector2),
+    Fullscreen(bool),
+    Cursor(CursorIcon),
+    CustomCursor {
+        image: Image<LDRColor, Texture2>,
+        hotspot: Vector2,
+    },
+    CursorMode {
+        visible: bool,
+        grab: CursorGrab,
+    },
+    ImePosition(Vector2),
+    Close,
 }
 
-#[derive(Clone)]
-struct CairoInput {
-    event_stream: Receiver<Event>,
-    event_task: Arc<AtomicTask>,
+/// Maps a logical position within a window of the provided logical size to viewport coordinates.
+fn logical_to_viewport(position: LogicalPosition, size: LogicalSize, viewport: Rect) -> Vector2 {
+    mouse::to_viewport(
+        (position.x, position.y).into(),
+        (size.width, size.height).into(),
+        viewport,
+    )
 }
 
-impl Input for CairoInput {
-    fn box_clone(&self) -> Box<dyn Input> {
-        Box::new(self.clone())
+/// Maps a position in viewport coordinates to a logical position within a window of the provided logical size.
+fn viewport_to_logical(position: Vector2, size: LogicalSize, viewport: Rect) -> LogicalPosition {
+    let position = mouse::from_viewport(position, (size.width, size.height).into(), viewport);
+    LogicalPosition::new(position.x, position.y)
+}
+
+fn touch_phase(phase: glutin::TouchPhase) -> pointer::Phase {
+    match phase {
+        glutin::TouchPhase::Started => pointer::Phase::Begin,
+        glutin::TouchPhase::Moved => pointer::Phase::Move,
+        glutin::TouchPhase::Ended => pointer::Phase::End,
+        glutin::TouchPhase::Cancelled => pointer::Phase::Cancel,
     }
 }
 
-impl Ticker for Cairo {
-    fn bind(&mut self, handler: Box<dyn FnMut(f64) + 'static + Send + Sync>) {
-        self.state.write().unwrap().tick_handlers.push(handler);
+fn mouse_cursor(icon: CursorIcon) -> glutin::MouseCursor {
+    match icon {
+        CursorIcon::Default => glutin::MouseCursor::Default,
+        CursorIcon::Pointer => glutin::MouseCursor::Hand,
+        CursorIcon::Text => glutin::MouseCursor::Text,
+        CursorIcon::VerticalText => glutin::MouseCursor::VerticalText,
+        CursorIcon::Crosshair => glutin::MouseCursor::Crosshair,
+        CursorIcon::Move => glutin::MouseCursor::Move,
+        CursorIcon::Grab => glutin::MouseCursor::Grab,
+        CursorIcon::Grabbing => glutin::MouseCursor::Grabbing,
+        CursorIcon::NotAllowed => glutin::MouseCursor::NotAllowed,
+        CursorIcon::NoDrop => glutin::MouseCursor::NoDrop,
+        CursorIcon::Wait => glutin::MouseCursor::Wait,
+        CursorIcon::Progress => glutin::MouseCursor::Progress,
+        CursorIcon::Help => glutin::MouseCursor::Help,
+        CursorIcon::ContextMenu => glutin::MouseCursor::ContextMenu,
+        CursorIcon::Cell => glutin::MouseCursor::Cell,
+        CursorIcon::Alias => glutin::MouseCursor::Alias,
+        CursorIcon::Copy => glutin::MouseCursor::Copy,
+        CursorIcon::AllScroll => glutin::MouseCursor::AllScroll,
+        CursorIcon::ZoomIn => glutin::MouseCursor::ZoomIn,
+        CursorIcon::ZoomOut => glutin::MouseCursor::ZoomOut,
+        CursorIcon::EResize => glutin::MouseCursor::EResize,
+        CursorIcon::NResize => glutin::MouseCursor::NResize,
+        CursorIcon::NeResize => glutin::MouseCursor::NeResize,
+        CursorIcon::NwResize => glutin::MouseCursor::NwResize,
+        CursorIcon::SResize => glutin::MouseCursor::SResize,
+        CursorIcon::SeResize => glutin::MouseCursor::SeResize,
+        CursorIcon::SwResize => glutin::MouseCursor::SwResize,
+        CursorIcon::WResize => glutin::MouseCursor::WResize,
+        CursorIcon::EwResize => glutin::MouseCursor::EwResize,
+        CursorIcon::NsResize => glutin::MouseCursor::NsResize,
+        CursorIcon::NeswResize => glutin::MouseCursor::NeswResize,
+        CursorIcon::NwseResize => glutin::MouseCursor::NwseResize,
+        CursorIcon::ColResize => glutin::MouseCursor::ColResize,
+        CursorIcon::RowResize => glutin::MouseCursor::RowResize,
     }
 }
 
-impl Rasterizer for Cairo {
-    fn rasterize(&self, input: Rasterizable, size: Vector2) -> Box<dyn ImageRepresentation> {
-        //this is probably wrong, just temp
-        let mut frame = CairoFrame::new();
-        frame.resize(size);
-        frame.set_viewport(Rect::new(Vector2::default(), size));
-        frame.add(input.into());
-        frame.draw();
-        frame.surface()
+struct CairoState {
+    ticker: TickDispatcher,
+    opening: Vec<CairoWindow>,
+    next_window_id: u64,
+}
+
+struct CairoWindowState {
+    id: WindowId,
+    root_frame: Option<Box<dyn Frame>>,
+    config: WindowConfig,
+    commands: Vec<WindowCommand>,
+    event_sender: Sender<Event>,
+    event_stream: Receiver<Event>,
+    event_task: Arc<AtomicTask>,
+    size: ObserverCell<Vector2>,
+    cursor_visible: bool,
+    cursor_grab: CursorGrab,
+}
+
+impl CairoWindowState {
+    fn send(&self, kind: Kind) {
+        if Arc::strong_count(&self.event_task) != 1 {
+            self.event_sender
+                .send(Event {
+                    window: self.id,
+                    time: native::input::now(),
+                    kind,
+                })
+                .unwrap();
+            self.event_task.notify()
+        }
+    }
+    fn push_cursor_mode(&mut self) {
+        let (visible, grab) = (self.cursor_visible, self.cursor_grab);
+        self.commands
+            .push(WindowCommand::CursorMode { visible, grab });
     }
 }
 
-impl Stream for CairoInput {
-    type Item = Event;
-    type Error = ();
+#[derive(Clone)]
+struct CairoWindow {
+    state: Arc<RwLock<CairoWindowState>>,
+}
 
-    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
-        match self.event_stream.try_recv() {
-            Ok(event) => Ok(Async::Ready(Some(event))),
-            Err(err) => match err {
-                TryRecvError::Disconnected => panic!("Input channel disconnected!"),
-                TryRecvError::Empty => {
-                    self.event_task.register();
-                    Ok(Async::NotReady)
-                }
-            },
+impl CairoWindow {
+    fn new(id: WindowId, root_frame: Option<Box<dyn Frame>>, config: WindowConfig) -> CairoWindow {
+        let (event_sender, event_stream) = unbounded();
+        CairoWindow {
+            state: Arc::new(RwLock::new(CairoWindowState {
+                id,
+                size: ObserverCell::new(config.size),
+                root_frame,
+                config,
+                commands: vec![],
+                event_task: Arc::new(AtomicTask::new()),
+                event_stream,
+                event_sender,
+                cursor_visible: true,
+                cursor_grab: CursorGrab::None,
+            })),
         }
     }
 }
 
-impl Provider for Cairo {
+impl Provider for CairoWindow {
     fn input(&self) -> Box<dyn Input> {
         let state = self.state.read().unwrap();
         Box::new(CairoInput {
@@ -1035,35 +1531,122 @@ impl Provider for Cairo {
     }
 }
 
-impl CanvasContext for Cairo {}
-
-impl ActiveCanvas for Cairo {
-    fn box_clone(&self) -> Box<dyn ActiveCanvas> {
-        Box::new(self.clone())
+impl Window for CairoWindow {
+    fn id(&self) -> WindowId {
+        self.state.read().unwrap().id
     }
-}
-
-impl InactiveCanvas for Cairo {
-    fn run(self: Box<Self>) {
-        self.run_with(Box::new(|_| {}));
+    fn set_title(&self, title: &str) {
+        let mut state = self.state.write().unwrap();
+        state.commands.push(WindowCommand::Title(title.to_owned()));
     }
-    fn run_with(self: Box<Self>, mut cb: Box<dyn FnMut(Box<dyn ActiveCanvas>) + Send + 'static>) {
-        let (mut el, frame, size, windowed_context) = {
-            let state = self.state.read().unwrap();
-            let size = state.size.get();
-            let size = LogicalSize::new(size.x, size.y);
-            let el = glutin::EventsLoop::new();
-            let wb = glutin::WindowBuilder::new().with_dimensions(size);
-            let windowed_context = glutin::ContextBuilder::new()
-                .with_vsync(true)
-                .build_windowed(wb, &el)
-                .unwrap();
-            let dpi_factor = windowed_context.get_hidpi_factor();
-            let frame = state.root_frame.clone().unwrap();
+    fn set_size(&self, size: Vector2) {
+        let mut state = self.state.write().unwrap();
+        state.commands.push(WindowCommand::Size(size));
+    }
+    fn set_fullscreen(&self, fullscreen: bool) {
+        let mut state = self.state.write().unwrap();
+        state.commands.push(WindowCommand::Fullscreen(fullscreen));
+    }
+    fn close(&self) {
+        let mut state = self.state.write().unwrap();
+        state.commands.push(WindowCommand::Close);
+    }
+    fn set_intercept_close(&self, intercept: bool) {
+        self.state.write().unwrap().config.intercept_close = intercept;
+    }
+    fn set_cursor(&self, cursor: Cursor) -> Result<(), Error> {
+        match cursor {
+            Cursor::Icon(icon) => {
+                let mut state = self.state.write().unwrap();
+                state.commands.push(WindowCommand::Cursor(icon));
+                Ok(())
+            }
+            Cursor::Custom { image, hotspot } => {
+                if !cursor::SUPPORTED {
+                    return Err(Error::unsupported());
+                }
+                let mut state = self.state.write().unwrap();
+                state
+                    .commands
+                    .push(WindowCommand::CustomCursor { image, hotspot });
+                Ok(())
+            }
+        }
+    }
+    fn set_cursor_visible(&self, visible: bool) {
+        let mut state = self.state.write().unwrap();
+        state.cursor_visible = visible;
+        state.push_cursor_mode();
+    }
+    fn set_cursor_grab(&self, grab: CursorGrab) -> Result<(), Error> {
+        let mut state = self.state.write().unwrap();
+        state.cursor_grab = grab;
+        state.push_cursor_mode();
+        Ok(())
+    }
+    fn set_ime_position(&self, position: Vector2) {
+        let mut state = self.state.write().unwrap();
+        state.commands.push(WindowCommand::ImePosition(position));
+    }
+}
+
+struct WindowSurface {
+    window: CairoWindow,
+    frame: Box<dyn Frame>,
+    // Declared ahead of the context so the cursor is freed while the display is still open.
+    custom_cursor: Option<CustomCursor>,
+    windowed_context: glutin::WindowedContext,
+    texture_id: GLuint,
+    program: GLuint,
+    vao: GLuint,
+    vbo: GLuint,
+    surface_pointer: *const c_void,
+    minimized: bool,
+    focused: bool,
+    pending_key: Option<KeyboardEvent>,
+    held_keys: HashSet<u32>,
+    cursor_grab: CursorGrab,
+}
+
+impl WindowSurface {
+    fn new(window: CairoWindow, el: &glutin::EventsLoop) -> WindowSurface {
+        let (frame, size, windowed_context) = {
+            let state = window.state.read().unwrap();
+            let config = &state.config;
+            let size = state.size.get();
+            let size = LogicalSize::new(size.x, size.y);
+            let mut wb = glutin::WindowBuilder::new()
+                .with_title(config.title.clone())
+                .with_dimensions(size)
+                .with_resizable(config.resizable)
+                .with_decorations(config.decorations)
+                .with_always_on_top(config.always_on_top)
+                .with_transparency(config.transparent);
+            if let Some(min_size) = config.min_size {
+                wb = wb.with_min_dimensions(LogicalSize::new(min_size.x, min_size.y));
+            }
+            if let Some(max_size) = config.max_size {
+                wb = wb.with_max_dimensions(LogicalSize::new(max_size.x, max_size.y));
+            }
+            if config.fullscreen {
+                wb = wb.with_fullscreen(Some(el.get_primary_monitor()));
+            }
+            let windowed_context = glutin::ContextBuilder::new()
+                .with_vsync(config.vsync)
+                .build_windowed(wb, el)
+                .unwrap();
+            if let Some(position) = config.position {
+                windowed_context
+                    .window()
+                    .set_position(LogicalPosition::new(position.x, position.y));
+            }
+            let dpi_factor = windowed_context.get_hidpi_factor();
+            let frame = state.root_frame.clone().unwrap();
             frame.set_pixel_ratio(dpi_factor);
             let size = size.to_physical(dpi_factor);
-            let size = (size.width, size.height).into();
-            (el, frame, size, windowed_context)
+            let size: Vector2 = (size.width, size.height).into();
+            state.size.set(size);
+            (frame, size, windowed_context)
         };
 
         let profile = Profile::from_window(windowed_context.window());
@@ -1073,7 +1656,7 @@ impl InactiveCanvas for Cairo {
                 .as_any()
                 .downcast::<CairoFrame>()
                 .unwrap()
-                .set_color_profile(profile);
+                .set_window_color_profile(profile);
         }
 
         frame.resize(size);
@@ -1089,9 +1672,7 @@ impl InactiveCanvas for Cairo {
             gl::GenTextures(1, &mut texture_id);
         }
 
-        let mut surface_pointer: *const c_void;
-
-        surface_pointer = frame
+        let surface_pointer = frame
             .to_image()
             .as_any()
             .downcast::<CairoImage>()
@@ -1115,7 +1696,7 @@ void main()
         let frag_id = new_shader(
             r#"#version 330 core
 out vec4 FragLDRColor;
-  
+
 in vec2 coord;
 
 uniform sampler2D tex;
@@ -1132,6 +1713,9 @@ void main()
             gl::AttachShader(id, vert_id);
             gl::AttachShader(id, frag_id);
             gl::LinkProgram(id);
+            // The linked program keeps the compiled code, so the shaders are released right away.
+            gl::DeleteShader(vert_id);
+            gl::DeleteShader(frag_id);
             id
         };
 
@@ -1167,140 +1751,594 @@ void main()
             gl::BindVertexArray(0);
         }
 
+        WindowSurface {
+            window,
+            frame,
+            windowed_context,
+            texture_id,
+            program,
+            vao,
+            vbo,
+            surface_pointer,
+            minimized: false,
+            focused: true,
+            pending_key: None,
+            held_keys: HashSet::new(),
+            cursor_grab: CursorGrab::None,
+            custom_cursor: None,
+        }
+    }
+    /// Releases a key press still awaiting its printable character, as not every key produces one.
+    fn flush_key(&mut self) {
+        if let Some(event) = self.pending_key.take() {
+            self.window
+                .state
+                .read()
+                .unwrap()
+                .send(Kind::Keyboard(event));
+        }
+    }
+    fn handle(&mut self, event: glutin::WindowEvent) -> Vec<Kind> {
+        let state = self.window.state.read().unwrap();
+        let kind = match event {
+            glutin::WindowEvent::Resized(logical_size) => {
+                // Minimizing is reported by some platforms as a resize to nothing.
+                if logical_size.width == 0. || logical_size.height == 0. {
+                    if self.minimized {
+                        return vec![];
+                    }
+                    self.minimized = true;
+                    return vec![
+                        Kind::Windowing(WindowingEvent::Minimize),
+                        Kind::Windowing(WindowingEvent::Visibility(false)),
+                    ];
+                }
+                let dpi_factor = self.windowed_context.get_hidpi_factor();
+                let true_size = logical_size.to_physical(dpi_factor);
+                self.windowed_context.resize(true_size);
+                state.size.set((true_size.width, true_size.height).into());
+                let resize = Kind::Windowing(WindowingEvent::Resize {
+                    logical: (logical_size.width, logical_size.height).into(),
+                    physical: (true_size.width, true_size.height).into(),
+                });
+                if self.minimized {
+                    self.minimized = false;
+                    return vec![
+                        Kind::Windowing(WindowingEvent::Restore),
+                        Kind::Windowing(WindowingEvent::Visibility(true)),
+                        resize,
+                    ];
+                }
+                Some(resize)
+            }
+            glutin::WindowEvent::Moved(p) => {
+                Some(Kind::Windowing(WindowingEvent::Move((p.x, p.y).into())))
+            }
+            glutin::WindowEvent::Focused(focused) => {
+                self.focused = focused;
+                // Keys released while the window is unfocused are never reported.
+                self.held_keys.clear();
+                Some(Kind::Windowing(if focused {
+                    WindowingEvent::Focus
+                } else {
+                    WindowingEvent::Blur
+                }))
+            }
+            glutin::WindowEvent::HiDpiFactorChanged(dpi_factor) => {
+                self.frame.set_pixel_ratio(dpi_factor);
+                Some(Kind::Windowing(WindowingEvent::ScaleFactor(dpi_factor)))
+            }
+            glutin::WindowEvent::DroppedFile(path) => {
+                Some(Kind::Windowing(WindowingEvent::DroppedFile(path)))
+            }
+            glutin::WindowEvent::HoveredFile(path) => {
+                Some(Kind::Windowing(WindowingEvent::HoveredFile(path)))
+            }
+            glutin::WindowEvent::HoveredFileCancelled => {
+                Some(Kind::Windowing(WindowingEvent::HoveredFileCancelled))
+            }
+            glutin::WindowEvent::CursorMoved { position, .. } => {
+                let window = self.windowed_context.window();
+                window.get_inner_size().and_then(|size| {
+                    if self.cursor_grab == CursorGrab::Lock {
+                        // glutin can only confine the cursor, so it is locked by returning it to the center after it moves.
+                        let center = LogicalPosition::new(size.width / 2., size.height / 2.);
+                        if (position.x - center.x).abs() >= 1.
+                            || (position.y - center.y).abs() >= 1.
+                        {
+                            let _ = window.set_cursor_position(center);
+                        }
+                        return None;
+                    }
+                    Some(Kind::Mouse(MouseEvent::Move(logical_to_viewport(
+                        position,
+                        size,
+                        self.frame.get_viewport(),
+                    ))))
+                })
+            }
+            glutin::WindowEvent::Touch(touch) => {
+                self.windowed_context.window().get_inner_size().map(|size| {
+                    Kind::Pointer(PointerEvent {
+                        id: touch.id,
+                        phase: touch_phase(touch.phase),
+                        device: pointer::Device::Touch,
+                        position: logical_to_viewport(
+                            touch.location,
+                            size,
+                            self.frame.get_viewport(),
+                        ),
+                        pressure: None,
+                        contact_size: None,
+                    })
+                })
+            }
+            glutin::WindowEvent::CursorEntered { .. } => Some(Kind::Mouse(MouseEvent::Enter)),
+            glutin::WindowEvent::CursorLeft { .. } => Some(Kind::Mouse(MouseEvent::Leave)),
+            glutin::WindowEvent::MouseInput {
+                state: element_state,
+                button,
+                ..
+            } => Some(Kind::Mouse(match element_state {
+                glutin::ElementState::Pressed => MouseEvent::Down(match button {
+                    glutin::MouseButton::Left => mouse::Button::Left,
+                    glutin::MouseButton::Right => mouse::Button::Right,
+                    glutin::MouseButton::Middle => mouse::Button::Middle,
+                    glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
+                }),
+                glutin::ElementState::Released => MouseEvent::Up(match button {
+                    glutin::MouseButton::Left => mouse::Button::Left,
+                    glutin::MouseButton::Right => mouse::Button::Right,
+                    glutin::MouseButton::Middle => mouse::Button::Middle,
+                    glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
+                }),
+            })),
+            glutin::WindowEvent::MouseWheel { delta, phase, .. } => {
+                // glutin reports positive vertical deltas when scrolling towards the top of content.
+                let (raw_delta, unit): (Vector2, _) = match delta {
+                    glutin::MouseScrollDelta::LineDelta(x, y) => (
+                        (f64::from(x), -f64::from(y)).into(),
+                        mouse::ScrollUnit::Line,
+                    ),
+                    glutin::MouseScrollDelta::PixelDelta(p) => {
+                        ((p.x, -p.y).into(), mouse::ScrollUnit::Pixel)
+                    }
+                };
+                let page_size = self
+                    .windowed_context
+                    .window()
+                    .get_inner_size()
+                    .map(|size| (size.width, size.height).into())
+                    .unwrap_or_default();
+                Some(Kind::Mouse(MouseEvent::Scroll(mouse::Scroll::new(
+                    raw_delta,
+                    unit,
+                    self.window.state.read().unwrap().config.scroll_line_height,
+                    page_size,
+                    Some(touch_phase(phase)),
+                ))))
+            }
+            glutin::WindowEvent::KeyboardInput { input, .. } => {
+                let key = native::input::keyboard::parse_code(input.scancode);
+                let previous = self.pending_key.take().map(Kind::Keyboard);
+                let mut modifiers =
+                    native::input::keyboard::lock_modifiers(self.windowed_context.window());
+                modifiers.set(Modifiers::SHIFT, input.modifiers.shift);
+                modifiers.set(Modifiers::CONTROL, input.modifiers.ctrl);
+                modifiers.set(Modifiers::ALT, input.modifiers.alt);
+                modifiers.set(Modifiers::META, input.modifiers.logo);
+                match input.state {
+                    // The printable character of a pressed key is reported separately, so the press is held until it arrives.
+                    glutin::ElementState::Pressed => {
+                        // glutin reports held keys as repeated presses without marking them.
+                        let is_repeat = !self.held_keys.insert(input.scancode);
+                        self.pending_key = Some(KeyboardEvent {
+                            action: keyboard::Action::Down(key),
+                            printable: None,
+                            modifiers,
+                            is_repeat,
+                        });
+                        return previous.into_iter().collect();
+                    }
+                    glutin::ElementState::Released => {
+                        self.held_keys.remove(&input.scancode);
+                        return previous
+                            .into_iter()
+                            .chain(Some(Kind::Keyboard(KeyboardEvent {
+                                action: keyboard::Action::Up(key),
+                                printable: None,
+                                modifiers,
+                                is_repeat: false,
+                            })))
+                            .collect();
+                    }
+                }
+            }
+            // glutin does not report preedit text, so input methods draw compositions themselves and commit the result as characters.
+            glutin::WindowEvent::ReceivedCharacter(character) => {
+                let commit = text::commit(character);
+                let previous = self.pending_key.take().map(|mut event| {
+                    if commit.is_some() {
+                        event.printable = Some(character);
+                    }
+                    Kind::Keyboard(event)
+                });
+                return previous.into_iter().chain(commit.map(Kind::Text)).collect();
+            }
+            _ => None,
+        };
+        kind.into_iter().collect()
+    }
+    /// Applies queued window commands, returning whether the window was asked to close.
+    fn apply_commands(&mut self) -> bool {
+        let mut state = self.window.state.write().unwrap();
+        let window = self.windowed_context.window();
+        let mut close = false;
+        let commands = std::mem::take(&mut state.commands);
+        for command in commands {
+            match command {
+                WindowCommand::Title(title) => window.set_title(&title),
+                WindowCommand::Size(size) => {
+                    window.set_inner_size(LogicalSize::new(size.x, size.y))
+                }
+                WindowCommand::Fullscreen(fullscreen) => window.set_fullscreen(if fullscreen {
+                    Some(window.get_current_monitor())
+                } else {
+                    None
+                }),
+                WindowCommand::Cursor(icon) => {
+                    window.set_cursor(mouse_cursor(icon));
+                    self.custom_cursor = None;
+                }
+                WindowCommand::CustomCursor { image, hotspot } => {
+                    self.custom_cursor = CustomCursor::new(window, &image, hotspot);
+                    match &self.custom_cursor {
+                        Some(custom_cursor) => custom_cursor.apply(window),
+                        // Images the windowing system cannot show fall back to the default cursor.
+                        None => window.set_cursor(glutin::MouseCursor::Default),
+                    }
+                }
+                WindowCommand::CursorMode { visible, grab } => {
+                    // Grabbing can be refused by the platform, e.g. while the window is unfocused, leaving the cursor free.
+                    let grab = match window.grab_cursor(grab != CursorGrab::None) {
+                        Ok(()) => grab,
+                        Err(_) => {
+                            state.cursor_grab = CursorGrab::None;
+                            CursorGrab::None
+                        }
+                    };
+                    // A locked cursor is confined and hidden, with movement reported as raw motion.
+                    window.hide_cursor(!visible || grab == CursorGrab::Lock);
+                    // Showing the cursor restores the one last set through glutin.
+                    if let Some(custom_cursor) = &self.custom_cursor {
+                        if visible && grab != CursorGrab::Lock {
+                            custom_cursor.apply(window);
+                        }
+                    }
+                    if grab != self.cursor_grab {
+                        self.cursor_grab = grab;
+                        state.send(Kind::Windowing(WindowingEvent::CursorGrab(grab)));
+                    }
+                }
+                WindowCommand::ImePosition(position) => {
+                    if let Some(size) = window.get_inner_size() {
+                        window.set_ime_spot(viewport_to_logical(
+                            position,
+                            size,
+                            self.frame.get_viewport(),
+                        ));
+                    }
+                }
+                WindowCommand::Close => close = true,
+            }
+        }
+        close
+    }
+    fn draw(&mut self) {
+        unsafe {
+            self.windowed_context.make_current().unwrap();
+        }
+
+        let state = self.window.state.read().unwrap();
+        if state.size.is_dirty() {
+            let size = state.size.get();
+            self.frame.set_viewport(Rect::new((0., 0.), size));
+            self.frame.resize(size);
+            self.surface_pointer = self
+                .frame
+                .to_image()
+                .as_any()
+                .downcast::<CairoImage>()
+                .unwrap()
+                .get_data_ptr();
+        }
+
+        self.frame.draw();
+
+        let size = state.size.get();
+
+        unsafe {
+            gl::Viewport(0, 0, size.x as i32, size.y as i32);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            gl::BindTexture(gl::TEXTURE_2D, self.texture_id);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as i32,
+                size.x as i32,
+                size.y as i32,
+                0,
+                gl::BGRA,
+                gl::UNSIGNED_BYTE,
+                self.surface_pointer,
+            );
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+        }
+        self.windowed_context.swap_buffers().unwrap();
+    }
+}
+
+impl Drop for WindowSurface {
+    fn drop(&mut self) {
+        // Other windows may have made their context current since, and the objects belong to this one.
+        unsafe {
+            if self.windowed_context.make_current().is_err() {
+                return;
+            }
+            gl::DeleteTextures(1, &self.texture_id);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteProgram(self.program);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CairoInput {
+    event_stream: Receiver<Event>,
+    event_task: Arc<AtomicTask>,
+}
+
+impl Input for CairoInput {
+    fn box_clone(&self) -> Box<dyn Input> {
+        Box::new(self.clone())
+    }
+}
+
+impl Ticker for Cairo {
+    fn bind(&mut self, handler: Box<dyn FnMut(f64) + 'static + Send + Sync>) {
+        self.state.write().unwrap().ticker.bind(handler);
+    }
+    fn bind_fixed(
+        &mut self,
+        step: f64,
+        update: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+        interpolate: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+    ) {
+        self.state
+            .write()
+            .unwrap()
+            .ticker
+            .bind_fixed(step, update, interpolate);
+    }
+    fn frame_stats(&self) -> FrameStats {
+        self.state.read().unwrap().ticker.stats()
+    }
+}
+
+impl Rasterizer for Cairo {
+    fn rasterize(&self, input: Rasterizable, size: Vector2) -> Box<dyn ImageRepresentation> {
+        //this is probably wrong, just temp
+        let mut frame = CairoFrame::new();
+        frame.resize(size);
+        frame.set_viewport(Rect::new(Vector2::default(), size));
+        frame.add(input.into());
+        frame.draw();
+        frame.surface()
+    }
+}
+
+impl Stream for CairoInput {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.event_stream.try_recv() {
+            Ok(event) => Ok(Async::Ready(Some(event))),
+            Err(err) => match err {
+                TryRecvError::Disconnected => panic!("Input channel disconnected!"),
+                TryRecvError::Empty => {
+                    self.event_task.register();
+                    Ok(Async::NotReady)
+                }
+            },
+        }
+    }
+}
+
+impl Provider for Cairo {
+    fn input(&self) -> Box<dyn Input> {
+        self.window.input()
+    }
+}
+
+impl CanvasContext for Cairo {}
+
+impl Window for Cairo {
+    fn id(&self) -> WindowId {
+        self.window.id()
+    }
+    fn set_title(&self, title: &str) {
+        self.window.set_title(title);
+    }
+    fn set_size(&self, size: Vector2) {
+        self.window.set_size(size);
+    }
+    fn set_fullscreen(&self, fullscreen: bool) {
+        self.window.set_fullscreen(fullscreen);
+    }
+    fn close(&self) {
+        self.window.close();
+    }
+    fn set_intercept_close(&self, intercept: bool) {
+        self.window.set_intercept_close(intercept);
+    }
+    fn set_cursor(&self, cursor: Cursor) -> Result<(), Error> {
+        self.window.set_cursor(cursor)
+    }
+    fn set_cursor_visible(&self, visible: bool) {
+        self.window.set_cursor_visible(visible);
+    }
+    fn set_cursor_grab(&self, grab: CursorGrab) -> Result<(), Error> {
+        self.window.set_cursor_grab(grab)
+    }
+    fn set_ime_position(&self, position: Vector2) {
+        self.window.set_ime_position(position);
+    }
+}
+
+impl ActiveCanvas for Cairo {
+    fn open_window(
+        &self,
+        root: Box<dyn Frame>,
+        config: WindowConfig,
+    ) -> Result<Box<dyn Window>, Error> {
+        let mut state = self.state.write().unwrap();
+        state.next_window_id += 1;
+        let window = CairoWindow::new(WindowId(state.next_window_id), Some(root), config);
+        state.opening.push(window.clone());
+        Ok(Box::new(window))
+    }
+    fn box_clone(&self) -> Box<dyn ActiveCanvas> {
+        Box::new(self.clone())
+    }
+}
+
+impl InactiveCanvas for Cairo {
+    fn run(self: Box<Self>) {
+        self.run_with(Box::new(|_| {}));
+    }
+    fn run_with(self: Box<Self>, mut cb: Box<dyn FnMut(Box<dyn ActiveCanvas>) + Send + 'static>) {
+        let mut el = glutin::EventsLoop::new();
+        let mut surfaces = HashMap::new();
+        let surface = WindowSurface::new(self.window.clone(), &el);
+        surfaces.insert(surface.windowed_context.window().id(), surface);
+
         let mut running = true;
-        let mut last_time = SystemTime::now();
+        let clock = Instant::now();
+        let gamepad_window = self.window.clone();
+        let dead_zone = self.window.state.read().unwrap().config.gamepad_dead_zone;
+        native::input::gamepad::spawn(dead_zone, move |event| {
+            gamepad_window
+                .state
+                .read()
+                .unwrap()
+                .send(Kind::Gamepad(event))
+        });
         let ctx = self.clone();
         std::thread::spawn(move || cb(ctx));
         while running {
-            el.poll_events(|event| {
-                let state = self.state.read().unwrap();
-                let e = if let glutin::Event::WindowEvent { event, .. } = event.clone() {
-                    match event {
-                        glutin::WindowEvent::CloseRequested => {
-                            running = false;
-                            None
-                        }
-                        glutin::WindowEvent::Resized(logical_size) => {
-                            let dpi_factor = windowed_context.get_hidpi_factor();
-                            let true_size = logical_size.to_physical(dpi_factor);
-                            windowed_context.resize(true_size);
-                            state.size.set((true_size.width, true_size.height).into());
-                            Some(Event::Windowing(WindowingEvent::Resize))
-                        }
-                        glutin::WindowEvent::Moved(p) => {
-                            Some(Event::Windowing(WindowingEvent::Move((p.x, p.y).into())))
+            let mut closed = vec![];
+            el.poll_events(|event| match event {
+                glutin::Event::WindowEvent { window_id, event } => {
+                    if let Some(surface) = surfaces.get_mut(&window_id) {
+                        if let glutin::WindowEvent::CloseRequested = event {
+                            let state = surface.window.state.read().unwrap();
+                            state.send(Kind::Windowing(WindowingEvent::CloseRequested));
+                            if !state.config.intercept_close {
+                                closed.push(window_id);
+                            }
+                        } else {
+                            for kind in surface.handle(event) {
+                                surface.window.state.read().unwrap().send(kind);
+                            }
                         }
-                        glutin::WindowEvent::CursorMoved { position, .. } => Some(Event::Mouse(
-                            MouseEvent::Move((position.x, position.y).into()),
-                        )),
-                        glutin::WindowEvent::MouseInput {
-                            state: element_state,
-                            button,
-                            ..
-                        } => Some(Event::Mouse(match element_state {
-                            glutin::ElementState::Pressed => MouseEvent::Down(match button {
-                                glutin::MouseButton::Left => mouse::Button::Left,
-                                glutin::MouseButton::Right => mouse::Button::Right,
-                                glutin::MouseButton::Middle => mouse::Button::Middle,
-                                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
-                            }),
-                            glutin::ElementState::Released => MouseEvent::Up(match button {
-                                glutin::MouseButton::Left => mouse::Button::Left,
-                                glutin::MouseButton::Right => mouse::Button::Right,
-                                glutin::MouseButton::Middle => mouse::Button::Middle,
-                                glutin::MouseButton::Other(x) => mouse::Button::Auxiliary(x),
-                            }),
-                        })),
-                        glutin::WindowEvent::MouseWheel { delta, .. } => {
-                            let pixel_delta: Vector2 = match delta {
-                                glutin::MouseScrollDelta::LineDelta(_x, _y) => {
-                                    println!("LineDelta is not handled");
-                                    (0., 0.).into()
-                                }
-                                glutin::MouseScrollDelta::PixelDelta(p) => (p.x, p.y).into(),
-                            };
-                            Some(Event::Mouse(MouseEvent::Scroll(pixel_delta)))
-                        }
-                        glutin::WindowEvent::KeyboardInput { input, .. } => {
-                            let key = native::input::keyboard::parse_code(input.scancode);
-                            Some(Event::Keyboard(KeyboardEvent {
-                                action: match input.state {
-                                    glutin::ElementState::Pressed => keyboard::Action::Down(key),
-                                    glutin::ElementState::Released => keyboard::Action::Up(key),
-                                },
-                                // TODO
-                                printable: None,
-                            }))
-                        }
-                        _ => None,
                     }
-                } else {
-                    None
-                };
-                e.map(|e| {
-                    if Arc::strong_count(&state.event_task) != 1 {
-                        state.event_sender.send(e).unwrap();
-                        state.event_task.notify()
+                }
+                // Raw motion is not associated with a window and is delivered to the focused window.
+                glutin::Event::DeviceEvent {
+                    event: glutin::DeviceEvent::MouseMotion { delta },
+                    ..
+                } => {
+                    for surface in surfaces.values().filter(|surface| surface.focused) {
+                        surface
+                            .window
+                            .state
+                            .read()
+                            .unwrap()
+                            .send(Kind::Mouse(MouseEvent::Motion(delta.into())));
                     }
-                });
+                }
+                glutin::Event::Suspended(suspended) => {
+                    for surface in surfaces.values() {
+                        surface
+                            .window
+                            .state
+                            .read()
+                            .unwrap()
+                            .send(Kind::Windowing(if suspended {
+                                WindowingEvent::Suspend
+                            } else {
+                                WindowingEvent::Resume
+                            }));
+                    }
+                }
+                _ => {}
             });
 
-            {
-                let mut state = self.state.write().unwrap();
-                let now = SystemTime::now();
-                state.tick_handlers.iter_mut().for_each(|handler| {
-                    (handler)(now.duration_since(last_time).unwrap().as_nanos() as f64 / 1_000_000.)
-                });
-                last_time = now;
+            for surface in surfaces.values_mut() {
+                surface.flush_key();
             }
 
-            let state = self.state.read().unwrap();
-            if state.size.is_dirty() {
-                let size = state.size.get();
-                frame.set_viewport(Rect::new((0., 0.), size));
-                frame.resize(size);
-                surface_pointer = frame
-                    .to_image()
-                    .as_any()
-                    .downcast::<CairoImage>()
-                    .unwrap()
-                    .get_data_ptr();
+            let opening = std::mem::take(&mut self.state.write().unwrap().opening);
+            for window in opening {
+                let surface = WindowSurface::new(window, &el);
+                surfaces.insert(surface.windowed_context.window().id(), surface);
             }
 
-            frame.draw();
+            for (window_id, surface) in &mut surfaces {
+                if surface.apply_commands() {
+                    closed.push(*window_id);
+                }
+            }
 
-            let size = state.size.get();
+            for window_id in closed {
+                if let Some(surface) = surfaces.remove(&window_id) {
+                    let state = surface.window.state.read().unwrap();
+                    if state.id == WindowId::MAIN {
+                        running = false;
+                    }
+                    state.send(Kind::Windowing(WindowingEvent::Close));
+                }
+            }
 
-            unsafe {
-                gl::Viewport(0, 0, size.x as i32, size.y as i32);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
-                gl::BindTexture(gl::TEXTURE_2D, texture_id);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0);
-                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0);
-                gl::TexImage2D(
-                    gl::TEXTURE_2D,
-                    0,
-                    gl::RGBA as i32,
-                    size.x as i32,
-                    size.y as i32,
-                    0,
-                    gl::BGRA,
-                    gl::UNSIGNED_BYTE,
-                    surface_pointer,
-                );
-                gl::UseProgram(program);
-                gl::BindVertexArray(vao);
-                gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
+            self.state
+                .write()
+                .unwrap()
+                .ticker
+                .tick(clock.elapsed().as_secs_f64() * 1000.);
+
+            for surface in surfaces.values_mut().filter(|surface| !surface.minimized) {
+                surface.draw();
             }
-            windowed_context.swap_buffers().unwrap();
         }
     }
 }
 
 impl InteractiveCanvas for Cairo {
-    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
+    fn start_with_config(
+        self: Box<Self>,
+        root: Box<dyn Frame>,
+        config: WindowConfig,
+    ) -> Box<dyn InactiveCanvas> {
         {
-            let mut state = self.state.write().unwrap();
+            let mut state = self.window.state.write().unwrap();
+            state.size.set(config.size);
+            state.config = config;
             state.root_frame = Some(root);
         }
         self
@@ -1313,19 +2351,237 @@ impl Canvas for Cairo {
     }
 }
 
+// Reads the ICC profile from the iCCP chunk, which holds a profile name, a compression method and the zlib-compressed profile.
+fn png_color_profile(data: &[u8]) -> Option<ColorProfile> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !data.starts_with(SIGNATURE) {
+        return None;
+    }
+    let mut chunks = &data[SIGNATURE.len()..];
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        let kind = &chunks[4..8];
+        let body = chunks.get(8..8 + length)?;
+        match kind {
+            b"iCCP" => {
+                let name_end = body.iter().position(|byte| *byte == 0)?;
+                // Deflate is the only defined compression method.
+                if body.get(name_end + 1) != Some(&0) {
+                    return None;
+                }
+                let profile =
+                    miniz_oxide::inflate::decompress_to_vec_zlib(&body[name_end + 2..]).ok()?;
+                return ColorProfile::from_icc(&profile).ok();
+            }
+            // The profile must precede the image data.
+            b"IDAT" | b"IEND" => return None,
+            _ => {}
+        }
+        chunks = chunks.get(12 + length..)?;
+    }
+    None
+}
+
+pub(crate) fn encode_png(image: Image<LDRColor, Texture2>) -> Option<Vec<u8>> {
+    let image = CairoImage::from_texture(image);
+    let mut data = vec![];
+    image
+        .surface()
+        .lock()
+        .unwrap()
+        .0
+        .write_to_png(&mut data)
+        .ok()?;
+    Some(data)
+}
+
+pub(crate) fn decode_png(data: &[u8]) -> Option<Image<LDRColor, Texture2>> {
+    let profile = png_color_profile(data);
+    let decoded = ImageSurface::create_from_png(&mut &data[..]).ok()?;
+    // Decoded surfaces may use other formats, so they are painted onto an ARGB surface.
+    let surface =
+        ImageSurface::create(Format::ARgb32, decoded.get_width(), decoded.get_height()).ok()?;
+    {
+        let context = cairo::Context::new(&surface);
+        context.set_source_surface(&decoded, 0., 0.);
+        context.paint();
+    }
+    Some(
+        CairoImage::new(CairoSurface(surface))
+            .with_color_profile(profile)
+            .as_texture(),
+    )
+}
+
 pub(crate) fn new() -> Box<dyn InteractiveCanvas> {
-    let (event_sender, event_stream) = unbounded();
     let window = Cairo {
         state: Arc::new(RwLock::new(CairoState {
-            //need to figure out how to select size, temp default
-            size: ObserverCell::new((700., 700.).into()),
-            root_frame: None,
-            event_task: Arc::new(AtomicTask::new()),
-            event_stream,
-            event_sender,
-            tick_handlers: vec![],
+            ticker: TickDispatcher::new(),
+            opening: vec![],
+            next_window_id: 0,
         })),
+        window: CairoWindow::new(WindowId::MAIN, None, WindowConfig::default()),
     };
 
     Box::new(window)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{path::Fill, ColorSpace};
+
+    fn crc(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in data {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn embedded_png_profile() {
+        let profile = lcms2::Profile::new_srgb().icc().unwrap();
+        let mut png = vec![];
+        ImageSurface::create(Format::ARgb32, 2, 2)
+            .unwrap()
+            .write_to_png(&mut png)
+            .unwrap();
+        assert_eq!(decode_png(&png).unwrap().color_profile, None);
+
+        let mut chunk = b"iCCP".to_vec();
+        chunk.extend_from_slice(b"sRGB\0\0");
+        chunk.extend(miniz_oxide::deflate::compress_to_vec_zlib(&profile, 6));
+        // The iCCP chunk follows the signature and the IHDR chunk.
+        let mut data = png[..33].to_vec();
+        data.extend_from_slice(&(chunk.len() as u32 - 4).to_be_bytes());
+        data.extend_from_slice(&chunk);
+        data.extend_from_slice(&crc(&chunk).to_be_bytes());
+        data.extend_from_slice(&png[33..]);
+        let decoded = decode_png(&data).unwrap();
+        assert_eq!(decoded.format.width, 2);
+        assert_eq!(
+            decoded.color_profile,
+            Some(ColorProfile::from_icc(&profile).unwrap())
+        );
+    }
+
+    #[test]
+    fn text_outline() {
+        let frame = CairoFrame::new();
+        let text = Text::new("H").with_size(40.).with_baseline_origin();
+        let metrics = frame.font_metrics(text.clone());
+        let outline = frame.outline(text.clone()).unwrap();
+        assert!(!outline.segments.is_empty());
+        let bounds = outline.bounds();
+        let width = frame.measure(Rasterizable::Text(Box::new(text.clone()))).x;
+        assert!(bounds.size.x > 0. && bounds.size.x <= width);
+        // The glyph stands on the baseline and rises to the cap height.
+        assert!((bounds.position.y + metrics.cap_height).abs() <= 1.);
+        assert!((bounds.position.y + bounds.size.y).abs() <= 1.);
+        let mut top = text;
+        top.origin = Origin::Top;
+        let top_bounds = frame.outline(top).unwrap().bounds();
+        assert!((top_bounds.position.y - (metrics.ascent - metrics.cap_height)).abs() <= 1.);
+    }
+
+    #[test]
+    fn proof_changes_reconvert_source_colors() {
+        // A device with primaries pulled in toward white cannot reproduce saturated sRGB colors.
+        let context = lcms2::ThreadContext::new();
+        let chromaticity = |(x, y): (f64, f64)| lcms2::CIExyY { x, y, Y: 1. };
+        let linear = lcms2::ToneCurve::new(1.);
+        let device = lcms2::Profile::new_rgb_context(
+            &context,
+            &chromaticity(ColorSpace::Srgb.white_point()),
+            &lcms2::CIExyYTRIPLE {
+                Red: chromaticity((0.45, 0.33)),
+                Green: chromaticity((0.3, 0.45)),
+                Blue: chromaticity((0.25, 0.25)),
+            },
+            &[&linear, &linear, &linear],
+        )
+        .unwrap();
+        let warning = LDRColor::rgba(0, 255, 0, 255);
+        let red = LDRColor::rgba(255, 0, 0, 255);
+        let mut frame = CairoFrame::new();
+        frame.resize((10., 10.).into());
+        frame.set_viewport(Rect {
+            size: (10., 10.).into(),
+            position: (0., 0.).into(),
+        });
+        frame.add(
+            Path {
+                segments: vec![],
+                clip_segments: vec![],
+                stroke: None,
+                fill: Some(Fill {
+                    content: red.into(),
+                }),
+                shadows: vec![],
+                closed: true,
+            }
+            .into(),
+        );
+        let fill = |frame: &CairoFrame| {
+            frame.draw();
+            let state = frame.state.read().unwrap();
+            let object_state = state.contents[0].state.read().unwrap();
+            match &object_state.content {
+                Rasterizable::Path(path) => match &path.fill.as_ref().unwrap().content {
+                    Texture::Solid(color) => *color,
+                    _ => unreachable!(),
+                },
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(fill(&frame), Color::LDR(red));
+        frame
+            .set_soft_proof(Some(
+                SoftProof::new(ColorProfile::from_icc(&device.icc().unwrap()).unwrap())
+                    .gamut_warning(warning),
+            ))
+            .unwrap();
+        assert_eq!(fill(&frame), Color::LDR(warning));
+        frame.set_soft_proof(None).unwrap();
+        assert_eq!(fill(&frame), Color::LDR(red));
+    }
+
+    #[test]
+    fn window_event_mapping() {
+        let viewport = Rect::new((50., -20.), (400., 200.));
+        let size = LogicalSize::new(200., 100.);
+        assert_eq!(
+            logical_to_viewport(LogicalPosition::new(100., 50.), size, viewport),
+            (250., 80.).into()
+        );
+        assert_eq!(
+            viewport_to_logical((250., 80.).into(), size, viewport),
+            LogicalPosition::new(100., 50.)
+        );
+        // Minimized windows report a zero size.
+        assert_eq!(
+            logical_to_viewport(
+                LogicalPosition::new(100., 50.),
+                LogicalSize::new(0., 0.),
+                viewport
+            ),
+            (50., -20.).into()
+        );
+        assert_eq!(
+            touch_phase(glutin::TouchPhase::Started),
+            pointer::Phase::Begin
+        );
+        assert_eq!(
+            touch_phase(glutin::TouchPhase::Cancelled),
+            pointer::Phase::Cancel
+        );
+    }
+}
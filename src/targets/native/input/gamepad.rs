@@ -0,0 +1,653 @@
+use crate::errors::Error;
+use crate::input::gamepad::{GamepadId, Rumble};
+
+#[cfg(target_os = "linux")]
+pub(crate) use self::linux::{rumble, spawn};
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn spawn<F>(_dead_zone: f64, _sink: F)
+where
+    F: Fn(crate::input::gamepad::Event) + Send + 'static,
+{
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn rumble(_gamepad: GamepadId, _effect: Rumble) -> Result<(), Error> {
+    Err(Error::unsupported())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+    use crate::input::gamepad::{Axis, Button, Event, Tracker};
+
+    use lazy_static::lazy_static;
+    use std::{
+        collections::HashMap,
+        fs::{self, File, OpenOptions},
+        io::{ErrorKind, Read, Write},
+        mem::size_of,
+        os::unix::{fs::OpenOptionsExt, io::AsRawFd},
+        sync::Mutex,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const EV_KEY: u16 = 0x01;
+    const EV_ABS: u16 = 0x03;
+    const EV_FF: u16 = 0x15;
+
+    const ABS_HAT0X: u16 = 0x10;
+    const ABS_HAT0Y: u16 = 0x11;
+    const ABS_CNT: usize = 0x40;
+    const BTN_MISC: u16 = 0x100;
+    const BTN_JOYSTICK: u16 = 0x120;
+    const BTN_GAMEPAD: u16 = 0x130;
+    const KEY_CNT: usize = 0x300;
+
+    const JS_EVENT_BUTTON: u8 = 0x01;
+    const JS_EVENT_AXIS: u8 = 0x02;
+    const JS_EVENT_INIT: u8 = 0x80;
+
+    const FF_RUMBLE: u16 = 0x50;
+
+    const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+    const POLL_TIMEOUT: i32 = 250;
+
+    /// The size of the timestamp preceding the type, code and value of an evdev event.
+    const EVDEV_TIME_SIZE: usize = 2 * size_of::<libc::c_long>();
+    const EVDEV_EVENT_SIZE: usize = EVDEV_TIME_SIZE + 8;
+    const JOYDEV_EVENT_SIZE: usize = 8;
+
+    const fn ioc(dir: u32, ty: u8, nr: u8, size: usize) -> libc::c_ulong {
+        ((dir << 30) | ((size as u32) << 16) | ((ty as u32) << 8) | nr as u32) as libc::c_ulong
+    }
+
+    const IOC_WRITE: u32 = 1;
+    const IOC_READ: u32 = 2;
+
+    const fn eviocgabs(axis: u8) -> libc::c_ulong {
+        ioc(IOC_READ, b'E', 0x40 + axis, size_of::<AbsInfo>())
+    }
+    const EVIOCSFF: libc::c_ulong = ioc(IOC_WRITE, b'E', 0x80, size_of::<FfEffect>());
+    const JSIOCGAXES: libc::c_ulong = ioc(IOC_READ, b'j', 0x11, 1);
+    const JSIOCGBUTTONS: libc::c_ulong = ioc(IOC_READ, b'j', 0x12, 1);
+    const JSIOCGAXMAP: libc::c_ulong = ioc(IOC_READ, b'j', 0x32, ABS_CNT);
+    const JSIOCGBTNMAP: libc::c_ulong =
+        ioc(IOC_READ, b'j', 0x34, (KEY_CNT - BTN_MISC as usize) * 2);
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct AbsInfo {
+        value: i32,
+        minimum: i32,
+        maximum: i32,
+        fuzz: i32,
+        flat: i32,
+        resolution: i32,
+    }
+
+    #[repr(C)]
+    struct FfEffect {
+        kind: u16,
+        id: i16,
+        direction: u16,
+        trigger: [u16; 2],
+        replay: [u16; 2],
+        // The kernel's effect union is aligned to its pointer member.
+        _union_padding: u16,
+        strong_magnitude: u16,
+        weak_magnitude: u16,
+        // Pads the rumble parameters to the size of the largest member of the kernel's effect union.
+        _padding: [u8; 20 + size_of::<usize>()],
+        _align: [usize; 0],
+    }
+
+    /// A raw input event in evdev terms, as read from either evdev or joydev.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct RawEvent {
+        kind: u16,
+        code: u16,
+        value: i32,
+    }
+
+    fn parse_evdev(buffer: &[u8]) -> Vec<RawEvent> {
+        buffer
+            .chunks_exact(EVDEV_EVENT_SIZE)
+            .map(|record| {
+                let data = &record[EVDEV_TIME_SIZE..];
+                RawEvent {
+                    kind: u16::from_ne_bytes([data[0], data[1]]),
+                    code: u16::from_ne_bytes([data[2], data[3]]),
+                    value: i32::from_ne_bytes([data[4], data[5], data[6], data[7]]),
+                }
+            })
+            .collect()
+    }
+
+    /// Parses joydev events, translating axis and button numbers to evdev codes using the maps reported by the driver.
+    fn parse_joydev(buffer: &[u8], axes: &[u16], buttons: &[u16]) -> Vec<RawEvent> {
+        buffer
+            .chunks_exact(JOYDEV_EVENT_SIZE)
+            .filter_map(|record| {
+                let value = i32::from(i16::from_ne_bytes([record[4], record[5]]));
+                let number = usize::from(record[7]);
+                match record[6] & !JS_EVENT_INIT {
+                    JS_EVENT_BUTTON => buttons.get(number).map(|&code| RawEvent {
+                        kind: EV_KEY,
+                        code,
+                        value,
+                    }),
+                    JS_EVENT_AXIS => axes.get(number).map(|&code| RawEvent {
+                        kind: EV_ABS,
+                        code,
+                        value,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn button(code: u16) -> Option<Button> {
+        Some(match code {
+            0x130 => Button::South,
+            0x131 => Button::East,
+            0x133 => Button::North,
+            0x134 => Button::West,
+            0x136 => Button::LeftBumper,
+            0x137 => Button::RightBumper,
+            0x138 => Button::LeftTrigger,
+            0x139 => Button::RightTrigger,
+            0x13a => Button::Select,
+            0x13b => Button::Start,
+            0x13c => Button::Mode,
+            0x13d => Button::LeftStick,
+            0x13e => Button::RightStick,
+            0x220 => Button::DPadUp,
+            0x221 => Button::DPadDown,
+            0x222 => Button::DPadLeft,
+            0x223 => Button::DPadRight,
+            0x120..=0x2ff => Button::Other(code),
+            _ => return None,
+        })
+    }
+
+    fn axis(code: u16) -> Option<Axis> {
+        Some(match code {
+            0x00 => Axis::LeftStickX,
+            0x01 => Axis::LeftStickY,
+            0x03 => Axis::RightStickX,
+            0x04 => Axis::RightStickY,
+            0x02 | 0x0a => Axis::LeftTrigger,
+            0x05 | 0x09 => Axis::RightTrigger,
+            code if usize::from(code) < ABS_CNT => Axis::Other(code),
+            _ => return None,
+        })
+    }
+
+    /// Maps raw events of a device onto the standard gamepad layout.
+    #[derive(Debug)]
+    struct Mapper {
+        tracker: Tracker,
+        ranges: HashMap<u16, (i32, i32)>,
+    }
+
+    impl Mapper {
+        fn new(tracker: Tracker, ranges: HashMap<u16, (i32, i32)>) -> Mapper {
+            Mapper { tracker, ranges }
+        }
+
+        fn handle(&mut self, event: RawEvent) -> Vec<Event> {
+            match event.kind {
+                EV_KEY => button(event.code)
+                    .and_then(|button| self.tracker.button(button, event.value != 0))
+                    .into_iter()
+                    .collect(),
+                EV_ABS if event.code == ABS_HAT0X => vec![
+                    self.tracker.button(Button::DPadLeft, event.value < 0),
+                    self.tracker.button(Button::DPadRight, event.value > 0),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+                EV_ABS if event.code == ABS_HAT0Y => vec![
+                    self.tracker.button(Button::DPadUp, event.value < 0),
+                    self.tracker.button(Button::DPadDown, event.value > 0),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+                EV_ABS => {
+                    let (min, max) = self
+                        .ranges
+                        .get(&event.code)
+                        .cloned()
+                        .unwrap_or((-32767, 32767));
+                    if max <= min {
+                        return vec![];
+                    }
+                    let position = f64::from(event.value - min) / f64::from(max - min);
+                    axis(event.code)
+                        .and_then(|axis| {
+                            let value = match axis {
+                                Axis::LeftTrigger | Axis::RightTrigger => position,
+                                _ => position * 2. - 1.,
+                            };
+                            self.tracker.axis(axis, value)
+                        })
+                        .into_iter()
+                        .collect()
+                }
+                _ => vec![],
+            }
+        }
+    }
+
+    enum Protocol {
+        Evdev,
+        Joydev { axes: Vec<u16>, buttons: Vec<u16> },
+    }
+
+    struct Device {
+        node: String,
+        file: File,
+        protocol: Protocol,
+        mapper: Mapper,
+        effect: Option<i16>,
+    }
+
+    impl Device {
+        /// Opens the device behind an event node, falling back to its joydev node if the event node cannot be read.
+        fn open(node: &str, dead_zone: f64, id: GamepadId) -> Option<Device> {
+            let path = format!("/dev/input/{}", node);
+            let open = |write| {
+                OpenOptions::new()
+                    .read(true)
+                    .write(write)
+                    .custom_flags(libc::O_NONBLOCK)
+                    .open(&path)
+            };
+            let evdev = open(true).or_else(|_| open(false)).ok();
+            let (file, protocol, ranges) = match evdev {
+                Some(file) => {
+                    let mut ranges = HashMap::new();
+                    for code in 0..ABS_CNT as u8 {
+                        let mut info = AbsInfo::default();
+                        if unsafe { libc::ioctl(file.as_raw_fd(), eviocgabs(code), &mut info) } >= 0
+                            && info.maximum > info.minimum
+                        {
+                            ranges.insert(u16::from(code), (info.minimum, info.maximum));
+                        }
+                    }
+                    (file, Protocol::Evdev, ranges)
+                }
+                // Event nodes are commonly restricted to the input group, while joydev nodes are readable by all users.
+                None => {
+                    let sysfs = format!("/sys/class/input/{}/device", node);
+                    let joydev_node = fs::read_dir(&sysfs).ok()?.find_map(|entry| {
+                        let name = entry.ok()?.file_name().into_string().ok()?;
+                        if name.starts_with("js") {
+                            Some(name)
+                        } else {
+                            None
+                        }
+                    })?;
+                    let file = OpenOptions::new()
+                        .read(true)
+                        .custom_flags(libc::O_NONBLOCK)
+                        .open(format!("/dev/input/{}", joydev_node))
+                        .ok()?;
+                    let fd = file.as_raw_fd();
+                    let (mut axis_count, mut button_count) = (0u8, 0u8);
+                    let mut axis_map = [0u8; ABS_CNT];
+                    let mut button_map = [0u16; KEY_CNT - BTN_MISC as usize];
+                    unsafe {
+                        if libc::ioctl(fd, JSIOCGAXES, &mut axis_count) < 0
+                            || libc::ioctl(fd, JSIOCGBUTTONS, &mut button_count) < 0
+                            || libc::ioctl(fd, JSIOCGAXMAP, axis_map.as_mut_ptr()) < 0
+                            || libc::ioctl(fd, JSIOCGBTNMAP, button_map.as_mut_ptr()) < 0
+                        {
+                            return None;
+                        }
+                    }
+                    let protocol = Protocol::Joydev {
+                        axes: axis_map[..usize::from(axis_count)]
+                            .iter()
+                            .map(|&code| u16::from(code))
+                            .collect(),
+                        buttons: button_map[..usize::from(button_count)].to_vec(),
+                    };
+                    // joydev scales all axes to a fixed range.
+                    (file, protocol, HashMap::new())
+                }
+            };
+            Some(Device {
+                node: node.to_owned(),
+                file,
+                protocol,
+                mapper: Mapper::new(Tracker::new(id, dead_zone), ranges),
+                effect: None,
+            })
+        }
+
+        fn name(&self) -> String {
+            fs::read_to_string(format!("/sys/class/input/{}/device/name", self.node))
+                .map(|name| name.trim().to_owned())
+                .unwrap_or_default()
+        }
+
+        /// Reads all pending events, returning `None` once the device is gone.
+        fn read(&mut self) -> Option<Vec<Event>> {
+            let mut buffer = [0u8; EVDEV_EVENT_SIZE * 64];
+            let mut events = vec![];
+            loop {
+                match self.file.read(&mut buffer) {
+                    Ok(0) => return None,
+                    Ok(length) => {
+                        let raw = match &self.protocol {
+                            Protocol::Evdev => parse_evdev(&buffer[..length]),
+                            Protocol::Joydev { axes, buttons } => {
+                                parse_joydev(&buffer[..length], axes, buttons)
+                            }
+                        };
+                        for event in raw {
+                            events.extend(self.mapper.handle(event));
+                        }
+                    }
+                    Err(ref e) if e.kind() == ErrorKind::WouldBlock => return Some(events),
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(_) => return None,
+                }
+            }
+        }
+
+        fn rumble(&mut self, effect: Rumble) -> Result<(), Error> {
+            if let Protocol::Joydev { .. } = self.protocol {
+                return Err(Error::unsupported());
+            }
+            // Float to integer casts saturate, which clamps magnitudes to the valid range.
+            let mut ff = FfEffect {
+                kind: FF_RUMBLE,
+                id: self.effect.unwrap_or(-1),
+                direction: 0,
+                trigger: [0; 2],
+                replay: [effect.duration.min(u32::from(u16::MAX)) as u16, 0],
+                _union_padding: 0,
+                strong_magnitude: (effect.strong * f64::from(u16::MAX)) as u16,
+                weak_magnitude: (effect.weak * f64::from(u16::MAX)) as u16,
+                _padding: [0; 20 + size_of::<usize>()],
+                _align: [],
+            };
+            if unsafe { libc::ioctl(self.file.as_raw_fd(), EVIOCSFF, &mut ff) } < 0 {
+                return Err(Error::unsupported());
+            }
+            self.effect = Some(ff.id);
+            let mut play = vec![0u8; EVDEV_TIME_SIZE];
+            play.extend_from_slice(&EV_FF.to_ne_bytes());
+            play.extend_from_slice(&(ff.id as u16).to_ne_bytes());
+            play.extend_from_slice(&1i32.to_ne_bytes());
+            self.file.write_all(&play).map_err(|_| Error::unsupported())
+        }
+    }
+
+    lazy_static! {
+        static ref DEVICES: Mutex<HashMap<GamepadId, Device>> = Mutex::new(HashMap::new());
+    }
+
+    /// Returns whether a key capability bitmap, as reported by sysfs in words of the native long size with the most significant word first, contains the provided code.
+    fn has_key(bitmap: &str, code: u16) -> bool {
+        let bits = libc::c_long::BITS as usize;
+        let words: Vec<&str> = bitmap.split_whitespace().collect();
+        let index = usize::from(code) / bits;
+        index < words.len()
+            && u64::from_str_radix(words[words.len() - 1 - index], 16)
+                .map(|word| word & (1 << (usize::from(code) % bits)) != 0)
+                .unwrap_or(false)
+    }
+
+    /// Returns whether the device behind an event node reports joystick or gamepad buttons.
+    fn is_gamepad(node: &str) -> bool {
+        fs::read_to_string(format!("/sys/class/input/{}/device/capabilities/key", node))
+            .map(|bitmap| has_key(&bitmap, BTN_JOYSTICK) || has_key(&bitmap, BTN_GAMEPAD))
+            .unwrap_or(false)
+    }
+
+    fn scan<F: Fn(Event)>(dead_zone: f64, next_id: &mut u64, sink: &F) {
+        let nodes: Vec<String> = match fs::read_dir("/sys/class/input") {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|name| name.starts_with("event") && is_gamepad(name))
+                .collect(),
+            Err(_) => return,
+        };
+        let mut devices = DEVICES.lock().unwrap();
+        for node in nodes {
+            if devices.values().any(|device| device.node == node) {
+                continue;
+            }
+            let id = GamepadId(*next_id);
+            if let Some(device) = Device::open(&node, dead_zone, id) {
+                *next_id += 1;
+                sink(Event::Connect {
+                    gamepad: id,
+                    name: device.name(),
+                });
+                devices.insert(id, device);
+            }
+        }
+    }
+
+    /// Spawns a thread that discovers gamepads and delivers their events to the provided sink.
+    pub(crate) fn spawn<F>(dead_zone: f64, sink: F)
+    where
+        F: Fn(Event) + Send + 'static,
+    {
+        thread::spawn(move || {
+            let mut next_id = 0;
+            scan(dead_zone, &mut next_id, &sink);
+            let mut last_scan = Instant::now();
+            loop {
+                if last_scan.elapsed() >= RESCAN_INTERVAL {
+                    scan(dead_zone, &mut next_id, &sink);
+                    last_scan = Instant::now();
+                }
+                // Devices are only closed on this thread, so descriptors stay valid while polling without the lock.
+                let mut fds: Vec<(GamepadId, libc::pollfd)> = DEVICES
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(id, device)| {
+                        (
+                            *id,
+                            libc::pollfd {
+                                fd: device.file.as_raw_fd(),
+                                events: libc::POLLIN,
+                                revents: 0,
+                            },
+                        )
+                    })
+                    .collect();
+                if fds.is_empty() {
+                    thread::sleep(RESCAN_INTERVAL);
+                    continue;
+                }
+                let mut pollfds: Vec<libc::pollfd> = fds.iter().map(|(_, fd)| *fd).collect();
+                if unsafe {
+                    libc::poll(
+                        pollfds.as_mut_ptr(),
+                        pollfds.len() as libc::nfds_t,
+                        POLL_TIMEOUT,
+                    )
+                } <= 0
+                {
+                    continue;
+                }
+                for ((_, fd), polled) in fds.iter_mut().zip(pollfds) {
+                    fd.revents = polled.revents;
+                }
+                let mut devices = DEVICES.lock().unwrap();
+                for (id, _) in fds.into_iter().filter(|(_, fd)| fd.revents != 0) {
+                    let events = devices.get_mut(&id).and_then(Device::read);
+                    match events {
+                        Some(events) => events.into_iter().for_each(&sink),
+                        None => {
+                            devices.remove(&id);
+                            sink(Event::Disconnect(id));
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    pub(crate) fn rumble(gamepad: GamepadId, effect: Rumble) -> Result<(), Error> {
+        DEVICES
+            .lock()
+            .unwrap()
+            .get_mut(&gamepad)
+            .ok_or_else(Error::unsupported)
+            .and_then(|device| device.rumble(effect))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn evdev(events: &[(u16, u16, i32)]) -> Vec<u8> {
+            let mut buffer = vec![];
+            for &(kind, code, value) in events {
+                buffer.extend(vec![0u8; EVDEV_TIME_SIZE]);
+                buffer.extend_from_slice(&kind.to_ne_bytes());
+                buffer.extend_from_slice(&code.to_ne_bytes());
+                buffer.extend_from_slice(&value.to_ne_bytes());
+            }
+            buffer
+        }
+
+        fn replay(mapper: &mut Mapper, events: Vec<RawEvent>) -> Vec<Event> {
+            events
+                .into_iter()
+                .flat_map(|event| mapper.handle(event))
+                .collect()
+        }
+
+        #[test]
+        fn evdev_replay() {
+            let gamepad = GamepadId(0);
+            let mut ranges = HashMap::new();
+            ranges.insert(0x00, (-32768, 32767));
+            ranges.insert(0x02, (0, 255));
+            let mut mapper = Mapper::new(Tracker::new(gamepad, 0.1), ranges);
+            // A press and release of A, a full trigger pull, the stick at rest and a hat press, each followed by a sync report.
+            let buffer = evdev(&[
+                (EV_KEY, 0x130, 1),
+                (0x00, 0x00, 0),
+                (EV_KEY, 0x130, 0),
+                (0x00, 0x00, 0),
+                (EV_ABS, 0x02, 255),
+                (EV_ABS, 0x00, 100),
+                (0x00, 0x00, 0),
+                (EV_ABS, ABS_HAT0X, -1),
+                (0x00, 0x00, 0),
+                (EV_ABS, ABS_HAT0X, 1),
+                (0x00, 0x00, 0),
+            ]);
+            assert_eq!(
+                replay(&mut mapper, parse_evdev(&buffer)),
+                vec![
+                    Event::Down {
+                        gamepad,
+                        button: Button::South
+                    },
+                    Event::Up {
+                        gamepad,
+                        button: Button::South
+                    },
+                    Event::Axis {
+                        gamepad,
+                        axis: Axis::LeftTrigger,
+                        value: 1.
+                    },
+                    Event::Down {
+                        gamepad,
+                        button: Button::DPadLeft
+                    },
+                    Event::Up {
+                        gamepad,
+                        button: Button::DPadLeft
+                    },
+                    Event::Down {
+                        gamepad,
+                        button: Button::DPadRight
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn effect_layout() {
+            let effect = FfEffect {
+                kind: FF_RUMBLE,
+                id: -1,
+                direction: 0,
+                trigger: [0; 2],
+                replay: [0; 2],
+                _union_padding: 0,
+                strong_magnitude: 0,
+                weak_magnitude: 0,
+                _padding: [0; 20 + size_of::<usize>()],
+                _align: [],
+            };
+            let base = &effect as *const FfEffect as usize;
+            assert_eq!(&effect.strong_magnitude as *const u16 as usize - base, 16);
+            assert_eq!(&effect.weak_magnitude as *const u16 as usize - base, 18);
+            assert_eq!(size_of::<FfEffect>(), 40 + size_of::<usize>());
+        }
+
+        #[test]
+        fn key_capabilities() {
+            let bits = libc::c_long::BITS as usize;
+            let gamepad = if bits == 64 {
+                "7fdb000000000000 0 0 0 0"
+            } else {
+                "7fdb0000 0 0 0 0 0 0 0 0 0"
+            };
+            assert!(has_key(gamepad, BTN_GAMEPAD));
+            assert!(!has_key(gamepad, BTN_JOYSTICK));
+            assert!(!has_key("0", BTN_GAMEPAD));
+            assert!(!has_key("", BTN_GAMEPAD));
+        }
+
+        #[test]
+        fn joydev_replay() {
+            let gamepad = GamepadId(1);
+            let mut mapper = Mapper::new(Tracker::new(gamepad, 0.), HashMap::new());
+            let record = |value: i16, kind: u8, number: u8| {
+                let mut record = vec![0u8; 4];
+                record.extend_from_slice(&value.to_ne_bytes());
+                record.push(kind);
+                record.push(number);
+                record
+            };
+            let mut buffer = record(0, JS_EVENT_BUTTON | JS_EVENT_INIT, 0);
+            buffer.extend(record(1, JS_EVENT_BUTTON, 1));
+            buffer.extend(record(32767, JS_EVENT_AXIS, 1));
+            let events = parse_joydev(&buffer, &[0x00, 0x01], &[0x130, 0x131]);
+            assert_eq!(
+                replay(&mut mapper, events),
+                vec![
+                    Event::Down {
+                        gamepad,
+                        button: Button::East
+                    },
+                    Event::Axis {
+                        gamepad,
+                        axis: Axis::LeftStickY,
+                        value: 1.
+                    },
+                ]
+            );
+        }
+    }
+}
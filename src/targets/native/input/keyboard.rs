@@ -1,4 +1,4 @@
-use crate::input::keyboard::Key;
+use crate::input::keyboard::{Key, Modifiers};
 
 #[cfg(target_os = "macos")]
 mod scancode_macos {
@@ -421,3 +421,53 @@ pub(crate) fn parse_code(code: u32) -> Key {
         Key::Unknown
     }
 }
+
+#[cfg(target_os = "linux")]
+mod locks_linux {
+    use crate::input::keyboard::Modifiers;
+    use glutin::os::unix::WindowExt;
+    use libc::{c_int, c_uint};
+    use x11_dl::xlib;
+
+    const XKB_USE_CORE_KBD: c_uint = 0x0100;
+    // The core keyboard conventionally maps its first two indicators to caps lock and num lock.
+    const CAPS_LOCK_INDICATOR: c_uint = 1;
+    const NUM_LOCK_INDICATOR: c_uint = 1 << 1;
+
+    thread_local! {
+        static XLIB: Option<xlib::Xlib> = xlib::Xlib::open().ok();
+    }
+
+    pub(crate) fn lock_modifiers(window: &glutin::Window) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        // Windows without an X display, e.g. on Wayland, do not expose their lock state.
+        let display = match window.get_xlib_display() {
+            Some(display) => display as *mut xlib::Display,
+            None => return modifiers,
+        };
+        XLIB.with(|xlib| {
+            if let Some(xlib) = xlib {
+                let mut state: c_uint = 0;
+                if unsafe { (xlib.XkbGetIndicatorState)(display, XKB_USE_CORE_KBD, &mut state) }
+                    == xlib::Success as c_int
+                {
+                    modifiers.set(Modifiers::CAPS_LOCK, state & CAPS_LOCK_INDICATOR != 0);
+                    modifiers.set(Modifiers::NUM_LOCK, state & NUM_LOCK_INDICATOR != 0);
+                }
+            }
+        });
+        modifiers
+    }
+}
+
+/// Returns the caps lock and num lock states, which glutin does not report.
+pub(crate) fn lock_modifiers(window: &glutin::Window) -> Modifiers {
+    #[cfg(target_os = "linux")]
+    return locks_linux::lock_modifiers(window);
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = window;
+        Modifiers::empty()
+    }
+}
@@ -1 +1,57 @@
+pub(crate) mod gamepad;
 pub(crate) mod keyboard;
+
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use futures::task::Task;
+use lazy_static::lazy_static;
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+lazy_static! {
+    static ref EPOCH: Instant = Instant::now();
+    static ref TIMERS: Sender<(Instant, Task)> = {
+        let (sender, receiver) = unbounded();
+        thread::spawn(move || serve_timers(receiver));
+        sender
+    };
+}
+
+pub(crate) fn now() -> f64 {
+    EPOCH.elapsed().as_secs_f64() * 1000.
+}
+
+// A single thread serves every timer, sleeping until the earliest deadline or the arrival of a new timer.
+fn serve_timers(receiver: Receiver<(Instant, Task)>) {
+    let mut timers: Vec<(Instant, Task)> = vec![];
+    loop {
+        let now = Instant::now();
+        timers.retain(|(deadline, task)| {
+            if *deadline <= now {
+                task.notify();
+                false
+            } else {
+                true
+            }
+        });
+        let timer = match timers.iter().map(|timer| timer.0).min() {
+            Some(deadline) => match receiver.recv_timeout(deadline - now) {
+                Ok(timer) => Some(timer),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            },
+            None => match receiver.recv() {
+                Ok(timer) => Some(timer),
+                Err(_) => return,
+            },
+        };
+        timers.extend(timer);
+    }
+}
+
+/// Notifies the provided task once the provided number of milliseconds has elapsed.
+pub(crate) fn wake_after(delay: f64, task: Task) {
+    let deadline = Instant::now() + Duration::from_secs_f64(delay.max(0.) / 1000.);
+    let _ = TIMERS.send((deadline, task));
+}
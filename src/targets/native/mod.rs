@@ -1,2 +1,3 @@
+pub(crate) mod clipboard;
 pub(crate) mod graphics;
 pub(crate) mod input;
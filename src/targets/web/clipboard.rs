@@ -0,0 +1,199 @@
+use crate::clipboard::{Clipboard, ClipboardFuture, TEXT};
+use crate::errors::Error;
+use crate::graphics::{Image, LDRColor, Texture2};
+
+use futures::{sync::oneshot, Future};
+
+use stdweb::web::TypedArray;
+use stdweb::Once;
+
+#[derive(Clone, Copy)]
+struct NavigatorClipboard;
+
+pub(crate) fn new() -> Box<dyn Clipboard> {
+    Box::new(NavigatorClipboard)
+}
+
+fn response<T: Send + 'static>(
+    response: oneshot::Receiver<Result<T, Error>>,
+) -> ClipboardFuture<T> {
+    Box::new(
+        response
+            .map_err(|_| Error::clipboard())
+            .and_then(|result| result),
+    )
+}
+
+// Browsers only accept a few standard types and require custom types to be prefixed with "web ".
+fn web_types(mime: &str) -> Vec<String> {
+    match mime {
+        TEXT => vec!["text/plain".to_owned()],
+        "text/plain" | "text/html" | "image/png" => vec![mime.to_owned()],
+        _ => vec![format!("web {}", mime), mime.to_owned()],
+    }
+}
+
+impl Clipboard for NavigatorClipboard {
+    fn read(&self, mime: &str) -> ClipboardFuture<Option<Vec<u8>>> {
+        let (reply, receiver) = oneshot::channel();
+        let done = Once(move |ok: bool, data: Option<TypedArray<u8>>| {
+            let _ = reply.send(if ok {
+                Ok(data.map(|data| data.to_vec()))
+            } else {
+                Err(Error::clipboard())
+            });
+        });
+        js! { @(no_return)
+            var done = @{done};
+            var types = @{web_types(mime)};
+            var failed = function() { done(false, null); };
+            if (!navigator.clipboard) {
+                failed();
+            } else if (!navigator.clipboard.read) {
+                if (types[0] === "text/plain") {
+                    navigator.clipboard.readText().then(function(text) {
+                        done(true, new TextEncoder().encode(text));
+                    }).catch(failed);
+                } else {
+                    done(true, null);
+                }
+            } else {
+                navigator.clipboard.read().then(function(items) {
+                    for (var i = 0; i < items.length; i++) {
+                        for (var j = 0; j < types.length; j++) {
+                            if (items[i].types.indexOf(types[j]) !== -1) {
+                                return items[i].getType(types[j]).then(function(blob) {
+                                    return new Response(blob).arrayBuffer();
+                                }).then(function(buffer) {
+                                    done(true, new Uint8Array(buffer));
+                                });
+                            }
+                        }
+                    }
+                    done(true, null);
+                }).catch(failed);
+            }
+        }
+        response(receiver)
+    }
+    fn write(&self, data: Vec<(String, Vec<u8>)>) -> ClipboardFuture<()> {
+        let (reply, receiver) = oneshot::channel();
+        let done = Once(move |ok: bool| {
+            let _ = reply.send(if ok { Ok(()) } else { Err(Error::clipboard()) });
+        });
+        let (types, contents): (Vec<String>, Vec<Vec<u8>>) = data
+            .into_iter()
+            .map(|(mime, data)| (web_types(&mime).remove(0), data))
+            .unzip();
+        js! { @(no_return)
+            var done = @{done};
+            var types = @{types};
+            var contents = @{contents};
+            var failed = function() { done(false); };
+            if (!navigator.clipboard) {
+                failed();
+            } else if (typeof ClipboardItem === "undefined") {
+                var index = types.indexOf("text/plain");
+                if (index === -1) {
+                    failed();
+                } else {
+                    var text = new TextDecoder().decode(new Uint8Array(contents[index]));
+                    navigator.clipboard.writeText(text).then(function() { done(true); }).catch(failed);
+                }
+            } else {
+                var representations = {};
+                for (var i = 0; i < types.length; i++) {
+                    representations[types[i]] = new Blob([new Uint8Array(contents[i])], { type: types[i] });
+                }
+                navigator.clipboard.write([new ClipboardItem(representations)]).then(function() {
+                    done(true);
+                }).catch(failed);
+            }
+        }
+        response(receiver)
+    }
+    fn read_image(&self) -> ClipboardFuture<Option<Image<LDRColor, Texture2>>> {
+        let (reply, receiver) = oneshot::channel();
+        let done = Once(
+            move |ok: bool, width: u32, height: u32, data: Option<TypedArray<u8>>| {
+                let _ = reply.send(if ok {
+                    Ok(data.map(|data| Image {
+                        pixels: data
+                            .to_vec()
+                            .chunks_exact(4)
+                            .map(|pixel| LDRColor::rgba(pixel[0], pixel[1], pixel[2], pixel[3]))
+                            .collect(),
+                        format: Texture2 { width, height },
+                        color_profile: None,
+                    }))
+                } else {
+                    Err(Error::clipboard())
+                });
+            },
+        );
+        js! { @(no_return)
+            var done = @{done};
+            var failed = function() { done(false, 0, 0, null); };
+            if (!navigator.clipboard || !navigator.clipboard.read) {
+                failed();
+                return;
+            }
+            navigator.clipboard.read().then(function(items) {
+                for (var i = 0; i < items.length; i++) {
+                    if (items[i].types.indexOf("image/png") !== -1) {
+                        return items[i].getType("image/png").then(createImageBitmap).then(function(bitmap) {
+                            var canvas = document.createElement("canvas");
+                            canvas.width = bitmap.width;
+                            canvas.height = bitmap.height;
+                            var context = canvas.getContext("2d");
+                            context.drawImage(bitmap, 0, 0);
+                            var pixels = context.getImageData(0, 0, bitmap.width, bitmap.height).data;
+                            done(true, bitmap.width, bitmap.height, new Uint8Array(pixels.buffer));
+                        });
+                    }
+                }
+                done(true, 0, 0, null);
+            }).catch(failed);
+        }
+        response(receiver)
+    }
+    fn write_image(&self, image: Image<LDRColor, Texture2>) -> ClipboardFuture<()> {
+        let (reply, receiver) = oneshot::channel();
+        let done = Once(move |ok: bool| {
+            let _ = reply.send(if ok { Ok(()) } else { Err(Error::clipboard()) });
+        });
+        let pixels: Vec<u8> = image
+            .pixels
+            .iter()
+            .flat_map(|pixel| vec![pixel.r, pixel.g, pixel.b, pixel.a])
+            .collect();
+        js! { @(no_return)
+            var done = @{done};
+            var failed = function() { done(false); };
+            if (!navigator.clipboard || typeof ClipboardItem === "undefined") {
+                failed();
+                return;
+            }
+            var canvas = document.createElement("canvas");
+            canvas.width = @{image.format.width};
+            canvas.height = @{image.format.height};
+            var context = canvas.getContext("2d");
+            var data = context.createImageData(canvas.width, canvas.height);
+            data.data.set(@{pixels});
+            context.putImageData(data, 0, 0);
+            canvas.toBlob(function(blob) {
+                if (blob === null) {
+                    failed();
+                    return;
+                }
+                navigator.clipboard.write([new ClipboardItem({ "image/png": blob })]).then(function() {
+                    done(true);
+                }).catch(failed);
+            }, "image/png");
+        }
+        response(receiver)
+    }
+    fn box_clone(&self) -> Box<dyn Clipboard> {
+        Box::new(*self)
+    }
+}
@@ -1,13 +1,18 @@
-use crate::graphics::path::{Path, Segment, StrokeCapType, StrokeJoinType, Texture};
-use crate::graphics::text::{Align, Font, Origin, Text, Weight, Wrap};
+use crate::errors::Error;
+use crate::graphics::color::{ColorProfile, RenderingIntent, SoftProof};
+use crate::graphics::path::{Path, Segment, Stroke, StrokeCapType, StrokeJoinType, Texture};
+use crate::graphics::text::{
+    Align, DecorationLine, Direction, Font, FontMetrics, Origin, Stretch, Text, Wrap,
+};
 use crate::graphics::{
     canvas::{
-        ActiveCanvas, Canvas as VesselsCanvas, CanvasContext, Content, Frame, InactiveCanvas,
-        InteractiveCanvas, Object, Rasterizable, Rasterizer, Ticker,
+        timing::TickDispatcher, ActiveCanvas, Canvas as VesselsCanvas, CanvasContext, Content,
+        Cursor, CursorGrab, CursorIcon, Frame, FrameStats, InactiveCanvas, InteractiveCanvas,
+        Object, Rasterizable, Rasterizer, Ticker, Window, WindowConfig,
     },
-    Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
+    Color, Image, ImageRepresentation, LDRColor, Rect, Texture2, Transform2, Vector2,
 };
-use crate::input::{Input, Provider};
+use crate::input::{mouse, windowing::WindowId, Input, Provider};
 use crate::targets::web;
 use crate::util::ObserverCell;
 
@@ -32,6 +37,101 @@ use std::any::Any;
 
 type CanvasImage = CanvasElement;
 
+const UNDERLINE_OFFSET_RATIO: f64 = 0.1;
+const DECORATION_THICKNESS_RATIO: f64 = 1. / 14.;
+
+fn css_stretch(stretch: Stretch) -> &'static str {
+    match stretch {
+        Stretch::UltraCondensed => "ultra-condensed",
+        Stretch::ExtraCondensed => "extra-condensed",
+        Stretch::Condensed => "condensed",
+        Stretch::SemiCondensed => "semi-condensed",
+        Stretch::Normal => "normal",
+        Stretch::SemiExpanded => "semi-expanded",
+        Stretch::Expanded => "expanded",
+        Stretch::ExtraExpanded => "extra-expanded",
+        Stretch::UltraExpanded => "ultra-expanded",
+    }
+}
+
+fn font_string(input: &Text) -> String {
+    match input.font {
+        Font::SystemFont => format!(
+            r#"{}{}{} {} {}px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple LDRColor Emoji", "Segoe UI Emoji", "Segoe UI Symbol""#,
+            if input.italic { "italic " } else { "" },
+            if input.small_caps { "small-caps " } else { "" },
+            input.weight.value(),
+            css_stretch(input.stretch),
+            input.size
+        ),
+    }
+}
+
+fn css_cursor(icon: CursorIcon) -> &'static str {
+    match icon {
+        CursorIcon::Default => "default",
+        CursorIcon::Pointer => "pointer",
+        CursorIcon::Text => "text",
+        CursorIcon::VerticalText => "vertical-text",
+        CursorIcon::Crosshair => "crosshair",
+        CursorIcon::Move => "move",
+        CursorIcon::Grab => "grab",
+        CursorIcon::Grabbing => "grabbing",
+        CursorIcon::NotAllowed => "not-allowed",
+        CursorIcon::NoDrop => "no-drop",
+        CursorIcon::Wait => "wait",
+        CursorIcon::Progress => "progress",
+        CursorIcon::Help => "help",
+        CursorIcon::ContextMenu => "context-menu",
+        CursorIcon::Cell => "cell",
+        CursorIcon::Alias => "alias",
+        CursorIcon::Copy => "copy",
+        CursorIcon::AllScroll => "all-scroll",
+        CursorIcon::ZoomIn => "zoom-in",
+        CursorIcon::ZoomOut => "zoom-out",
+        CursorIcon::EResize => "e-resize",
+        CursorIcon::NResize => "n-resize",
+        CursorIcon::NeResize => "ne-resize",
+        CursorIcon::NwResize => "nw-resize",
+        CursorIcon::SResize => "s-resize",
+        CursorIcon::SeResize => "se-resize",
+        CursorIcon::SwResize => "sw-resize",
+        CursorIcon::WResize => "w-resize",
+        CursorIcon::EwResize => "ew-resize",
+        CursorIcon::NsResize => "ns-resize",
+        CursorIcon::NeswResize => "nesw-resize",
+        CursorIcon::NwseResize => "nwse-resize",
+        CursorIcon::ColResize => "col-resize",
+        CursorIcon::RowResize => "row-resize",
+    }
+}
+
+fn css_cursor_image(image: &Image<LDRColor, Texture2>, hotspot: Vector2) -> String {
+    let pixels: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| vec![pixel.r, pixel.g, pixel.b, pixel.a])
+        .collect();
+    let url: String = js!(
+        var canvas = document.createElement("canvas");
+        canvas.width = @{image.format.width};
+        canvas.height = @{image.format.height};
+        var context = canvas.getContext("2d");
+        var data = context.createImageData(canvas.width, canvas.height);
+        data.data.set(@{pixels});
+        context.putImageData(data, 0, 0);
+        return canvas.toDataURL();
+    )
+    .try_into()
+    .unwrap();
+    format!(
+        "url({}) {} {}, auto",
+        url,
+        hotspot.x.round(),
+        hotspot.y.round()
+    )
+}
+
 impl ImageRepresentation for CanvasImage {
     fn get_size(&self) -> Vector2 {
         let dpr = window().device_pixel_ratio();
@@ -54,6 +154,7 @@ impl ImageRepresentation for CanvasImage {
                 height: 0,
                 width: 0,
             },
+            color_profile: None,
         }
     }
     fn from_texture(texture: Image<LDRColor, Texture2>) -> CanvasImage {
@@ -502,26 +603,29 @@ impl CanvasFrame {
     }
     fn update_text_style(&self, input: &Text) {
         let state = self.state.read().unwrap();
-        state.context.set_font((match input.font {
-                Font::SystemFont => {
-                    format!(r#"{} {} {}px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple LDRColor Emoji", "Segoe UI Emoji", "Segoe UI Symbol""#, if input.italic { "italic " } else { "" }, match input.weight {
-                        Weight::Normal => "400",
-                        Weight::Medium => "500",
-                        Weight::SemiBold => "600",
-                        Weight::Bold => "700",
-                        Weight::ExtraBold => "800",
-                        Weight::Heavy => "900",
-                        Weight::Thin => "200",
-                        Weight::Light => "300",
-                        Weight::Hairline => "100"
-                    }, input.size)
-                }
-            }).as_str());
+        state.context.set_font(&font_string(input));
+        // The font shorthand covers stretch and small caps where these properties are unavailable.
+        js! { @(no_return)
+            let context = @{&state.context};
+            if ("fontStretch" in context) {
+                context.fontStretch = @{css_stretch(input.stretch)};
+            }
+            if ("fontVariantCaps" in context) {
+                context.fontVariantCaps = @{if input.small_caps { "small-caps" } else { "normal" }};
+            }
+        }
         state.context.set_text_align(match input.align {
             Align::Center => TextAlign::Center,
             Align::End => TextAlign::End,
-            Align::Start => TextAlign::Start,
+            Align::Start | Align::Justify => TextAlign::Start,
         });
+        let direction = match input.direction.resolve(&input.content) {
+            Direction::Rtl => "rtl",
+            _ => "ltr",
+        };
+        js! { @(no_return)
+            @{&state.context}.direction = @{direction};
+        }
         state.context.set_text_baseline(match input.origin {
             Origin::Top => TextBaseline::Top,
             Origin::Baseline => TextBaseline::Alphabetic,
@@ -531,7 +635,46 @@ impl CanvasFrame {
             .context
             .set_fill_style_color(&input.color.to_rgba_color());
     }
-    fn fill_text_with_spacing(&self, text: &'_ str, position: Vector2, spacing: f64) {
+    fn set_word_spacing(&self, spacing: f64) {
+        let state = self.state.read().unwrap();
+        js! { @(no_return)
+            @{&state.context}.wordSpacing = @{spacing} + "px";
+        }
+    }
+    fn render_text(
+        &self,
+        text: &'_ str,
+        position: Vector2,
+        spacing: f64,
+        word_spacing: f64,
+        stroke: bool,
+    ) {
+        if word_spacing != 0. {
+            self.set_word_spacing(word_spacing);
+        }
+        if spacing != 0. {
+            self.render_text_with_spacing(text, position, spacing, stroke);
+        } else {
+            let state = self.state.read().unwrap();
+            if stroke {
+                state
+                    .context
+                    .stroke_text(text, position.x, position.y, None);
+            } else {
+                state.context.fill_text(text, position.x, position.y, None);
+            }
+        }
+        if word_spacing != 0. {
+            self.set_word_spacing(0.);
+        }
+    }
+    fn render_text_with_spacing(
+        &self,
+        text: &'_ str,
+        position: Vector2,
+        spacing: f64,
+        stroke: bool,
+    ) {
         if text == "" {
             return;
         }
@@ -547,9 +690,15 @@ impl CanvasFrame {
                 .collect::<Vec<String>>()
                 .join("");
             text_iter = text.chars();
-            state
-                .context
-                .fill_text(&head.to_string(), position.x, position.y, None);
+            if stroke {
+                state
+                    .context
+                    .stroke_text(&head.to_string(), position.x, position.y, None);
+            } else {
+                state
+                    .context
+                    .fill_text(&head.to_string(), position.x, position.y, None);
+            }
 
             let shorter_width = if text == "" {
                 0.
@@ -593,6 +742,206 @@ impl CanvasFrame {
         } {}
         spaced_width - spacing
     }
+    fn set_text_stroke_style(&self, stroke: &Stroke) {
+        let state = self.state.read().unwrap();
+        state.context.set_line_cap(match &stroke.cap {
+            StrokeCapType::Butt => LineCap::Butt,
+            StrokeCapType::Round => LineCap::Round,
+        });
+        state.context.set_line_join(match &stroke.join {
+            StrokeJoinType::Miter => LineJoin::Miter,
+            StrokeJoinType::Round => LineJoin::Round,
+            StrokeJoinType::Bevel => LineJoin::Bevel,
+        });
+        match &stroke.content {
+            Texture::Solid(color) => {
+                state.context.set_stroke_style_color(&color.to_rgba_color());
+            }
+            Texture::LinearGradient(gradient) => {
+                let canvas_gradient = state.context.create_linear_gradient(
+                    gradient.start.x,
+                    gradient.start.y,
+                    gradient.end.x,
+                    gradient.end.y,
+                );
+                gradient.stops.iter().for_each(|stop| {
+                    canvas_gradient
+                        .add_color_stop(stop.offset, &stop.color.to_rgba_color())
+                        .unwrap();
+                });
+                state.context.set_stroke_style_gradient(&canvas_gradient);
+            }
+            Texture::RadialGradient(gradient) => {
+                let canvas_gradient = state
+                    .context
+                    .create_radial_gradient(
+                        gradient.start.x,
+                        gradient.start.y,
+                        gradient.start_radius,
+                        gradient.end.x,
+                        gradient.end.y,
+                        gradient.end_radius,
+                    )
+                    .unwrap();
+                gradient.stops.iter().for_each(|stop| {
+                    canvas_gradient
+                        .add_color_stop(stop.offset, &stop.color.to_rgba_color())
+                        .unwrap();
+                });
+                state.context.set_stroke_style_gradient(&canvas_gradient);
+            }
+            Texture::Image(image) => {
+                let as_image = match image.as_any().downcast::<CanvasImage>() {
+                    Ok(as_image) => *as_image,
+                    Err(_) => CanvasImage::from_texture(image.box_clone().as_texture()),
+                };
+                let pattern: CanvasPattern = js! {
+                    return @{&state.context}.createPattern(@{as_image}, "no-repeat");
+                }
+                .try_into()
+                .unwrap();
+                state.context.set_stroke_style_pattern(&pattern);
+            }
+        }
+        state.context.set_line_width(f64::from(stroke.width));
+    }
+    fn measure_font(&self, input: &Text) -> FontMetrics {
+        // The style is set on the shared context only for the duration of the measurement.
+        self.state.read().unwrap().context.save();
+        self.update_text_style(input);
+        let state = self.state.read().unwrap();
+        let metrics: Vec<f64> = js! {
+            let context = @{&state.context};
+            let size = @{input.size};
+            context.textBaseline = "alphabetic";
+            let lowercase = context.measureText("x");
+            let uppercase = context.measureText("H");
+            let sample = context.measureText("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ");
+            context.restore();
+            return [
+                lowercase.fontBoundingBoxAscent || size * 0.8,
+                lowercase.fontBoundingBoxDescent || size * 0.2,
+                uppercase.actualBoundingBoxAscent || size * 0.7,
+                lowercase.actualBoundingBoxAscent || size * 0.5,
+                sample.width / 52
+            ];
+        }
+        .try_into()
+        .unwrap();
+        let underline_thickness = input.size * DECORATION_THICKNESS_RATIO;
+        FontMetrics {
+            ascent: metrics[0],
+            descent: metrics[1],
+            cap_height: metrics[2],
+            x_height: metrics[3],
+            underline_position: input.size * UNDERLINE_OFFSET_RATIO - underline_thickness / 2.,
+            underline_thickness,
+            average_character_width: metrics[4],
+        }
+    }
+    fn draw_text_shadows(&self, lines: &[String], word_spacings: &[f64], input: &Text) {
+        let state = self.state.read().unwrap();
+        let offset: Vector2 = (
+            state.viewport.size.x + state.viewport.position.x,
+            state.viewport.size.y + state.viewport.position.y,
+        )
+            .into();
+        for shadow in &input.shadows {
+            state
+                .context
+                .set_shadow_blur(shadow.blur * state.pixel_ratio);
+            state
+                .context
+                .set_shadow_color(&shadow.color.to_rgba_color());
+            state
+                .context
+                .set_shadow_offset_x((shadow.offset.x + offset.x) * state.pixel_ratio);
+            state
+                .context
+                .set_shadow_offset_y((shadow.offset.y + offset.y) * state.pixel_ratio);
+            state.context.set_fill_style_color("rgba(255,255,255,1)");
+            state.context.set_stroke_style_color("rgba(255,255,255,1)");
+            state.context.set_line_join(LineJoin::Round);
+            state.context.set_line_width(shadow.spread * 2.);
+            for (index, line) in lines.iter().enumerate() {
+                let position: Vector2 = (
+                    -offset.x,
+                    input.line_height * f64::from(index as u32) - offset.y,
+                )
+                    .into();
+                self.render_text(
+                    line,
+                    position,
+                    input.letter_spacing,
+                    word_spacings[index],
+                    false,
+                );
+                if shadow.spread > 0. {
+                    self.render_text(
+                        line,
+                        position,
+                        input.letter_spacing,
+                        word_spacings[index],
+                        true,
+                    );
+                }
+            }
+        }
+        state.context.set_shadow_color("rgba(255,255,255,0)");
+        state
+            .context
+            .set_fill_style_color(&input.color.to_rgba_color());
+    }
+    fn draw_text_decorations(&self, lines: &[String], word_spacings: &[f64], input: &Text) {
+        if input.decorations.is_empty() {
+            return;
+        }
+        let metrics = self.measure_font(input);
+        let baseline_offset = match input.origin {
+            Origin::Top => metrics.ascent,
+            Origin::Middle => (metrics.ascent - metrics.descent) / 2.,
+            Origin::Baseline => 0.,
+        };
+        let rtl = input.direction.resolve(&input.content) == Direction::Rtl;
+        let state = self.state.read().unwrap();
+        for (index, line) in lines.iter().enumerate() {
+            let width = if word_spacings[index] != 0. {
+                input.max_width.unwrap()
+            } else {
+                self.measure_text_with_spacing(line, input.letter_spacing)
+            };
+            let start = match (input.align, rtl) {
+                (Align::Center, _) => -width / 2.,
+                (Align::Start, false) | (Align::Justify, false) | (Align::End, true) => 0.,
+                (Align::End, false) | (Align::Start, true) | (Align::Justify, true) => -width,
+            };
+            let baseline = input.line_height * f64::from(index as u32) + baseline_offset;
+            for decoration in &input.decorations {
+                let center = baseline
+                    + match decoration.line {
+                        DecorationLine::Underline => {
+                            metrics.underline_position + metrics.underline_thickness / 2.
+                        }
+                        DecorationLine::Strikethrough => -metrics.x_height / 2.,
+                        DecorationLine::Overline => -metrics.ascent,
+                    };
+                let thickness = decoration.thickness.unwrap_or(metrics.underline_thickness);
+                state.context.set_fill_style_color(
+                    &decoration
+                        .color
+                        .map(Color::from)
+                        .unwrap_or(input.color)
+                        .to_rgba_color(),
+                );
+                state
+                    .context
+                    .fill_rect(start, center - thickness / 2., width, thickness);
+            }
+        }
+        state
+            .context
+            .set_fill_style_color(&input.color.to_rgba_color());
+    }
     fn draw_text(&self, matrix: [f64; 6], input: &Text) {
         let state = self.state.read().unwrap();
         state.context.restore();
@@ -600,52 +949,42 @@ impl CanvasFrame {
         state.context.transform(
             matrix[0], matrix[1], matrix[2], matrix[3], matrix[4], matrix[5],
         );
-        let mut lines: Vec<String> = input
-            .content
-            .split('\n')
-            .map(std::borrow::ToOwned::to_owned)
-            .collect();
         self.update_text_style(&input);
-        if input.max_width.is_some() {
-            lines = self.wrap_text(&input);
+        let paragraphs = self.wrap_paragraphs(&input);
+        let word_spacings = self.justify_paragraphs(&paragraphs, &input);
+        let lines: Vec<String> = paragraphs.into_iter().flatten().collect();
+        if !input.shadows.is_empty() {
+            self.draw_text_shadows(&lines, &word_spacings, &input);
         }
         for (index, line) in lines.iter().enumerate() {
-            if input.letter_spacing != 0. {
-                self.fill_text_with_spacing(
+            self.render_text(
+                line,
+                (0., input.line_height * f64::from(index as u32)).into(),
+                input.letter_spacing,
+                word_spacings[index],
+                false,
+            );
+        }
+        if let Some(stroke) = &input.stroke {
+            self.set_text_stroke_style(stroke);
+            for (index, line) in lines.iter().enumerate() {
+                self.render_text(
                     line,
                     (0., input.line_height * f64::from(index as u32)).into(),
                     input.letter_spacing,
-                );
-            } else {
-                state.context.fill_text(
-                    line,
-                    0.,
-                    input.line_height * f64::from(index as u32),
-                    None,
+                    word_spacings[index],
+                    true,
                 );
             }
         }
+        self.draw_text_decorations(&lines, &word_spacings, &input);
     }
     fn element(&self) -> CanvasElement {
         let state = self.state.read().unwrap();
         state.canvas.clone()
     }
     fn measure_text_height(&self, input: Text) -> f64 {
-        let font = match input.font {
-            Font::SystemFont => {
-                format!(r#"{} {} {}px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif, "Apple LDRColor Emoji", "Segoe UI Emoji", "Segoe UI Symbol""#, if input.italic { "italic " } else { "" }, match input.weight {
-                    Weight::Normal => "400",
-                    Weight::Medium => "500",
-                    Weight::SemiBold => "600",
-                    Weight::Bold => "700",
-                    Weight::ExtraBold => "800",
-                    Weight::Heavy => "900",
-                    Weight::Thin => "200",
-                    Weight::Light => "300",
-                    Weight::Hairline => "100"
-                }, input.size)
-            }
-        };
+        let font = font_string(&input);
         (js! {
             let el = document.createElement("span");
             el.style.position = "fixed";
@@ -662,56 +1001,80 @@ impl CanvasFrame {
         .unwrap()
     }
     fn wrap_text(&self, input: &Text) -> Vec<String> {
-        let mut lines: Vec<String> = input
+        self.wrap_paragraphs(input).into_iter().flatten().collect()
+    }
+    fn wrap_paragraphs(&self, input: &Text) -> Vec<Vec<String>> {
+        input
             .content
             .split('\n')
-            .map(std::borrow::ToOwned::to_owned)
-            .collect();
-        match input.wrap {
-            Wrap::Normal => {
-                let mut test_string = "".to_owned();
-                lines.reverse();
-                let mut wrapped_lines: Vec<String> = vec![];
-                loop {
-                    let line = lines.pop();
-                    match line {
-                        None => {
+            .map(|paragraph| match (input.wrap, input.max_width) {
+                (Wrap::Normal, Some(max_width)) => {
+                    self.wrap_paragraph(paragraph, max_width, input.letter_spacing)
+                }
+                _ => vec![paragraph.to_owned()],
+            })
+            .collect()
+    }
+    fn wrap_paragraph(&self, paragraph: &'_ str, max_width: f64, spacing: f64) -> Vec<String> {
+        let mut lines = vec![paragraph.to_owned()];
+        let mut test_string = "".to_owned();
+        let mut wrapped_lines: Vec<String> = vec![];
+        loop {
+            let line = lines.pop();
+            match line {
+                None => {
+                    break;
+                }
+                Some(line) => {
+                    let words = line.split(' ').collect::<Vec<&str>>();
+                    for (index, word) in words.iter().cloned().enumerate() {
+                        if self.measure_text_with_spacing(&(test_string.clone() + word), spacing)
+                            <= max_width
+                        {
+                            test_string += &format!(" {}", word);
+                        } else {
+                            test_string = test_string.trim().to_owned();
+                            wrapped_lines.push(test_string);
+                            lines.push(
+                                words
+                                    .iter()
+                                    .cloned()
+                                    .skip(index)
+                                    .collect::<Vec<&str>>()
+                                    .join(" "),
+                            );
+                            test_string = "".to_owned();
                             break;
                         }
-                        Some(line) => {
-                            let words = line.split(' ').collect::<Vec<&str>>();
-                            for (index, word) in words.iter().cloned().enumerate() {
-                                if self.measure_text_with_spacing(
-                                    &(test_string.clone() + word),
-                                    input.letter_spacing,
-                                ) <= input.max_width.unwrap()
-                                {
-                                    test_string += &format!(" {}", word);
-                                } else {
-                                    test_string = test_string.trim().to_owned();
-                                    wrapped_lines.push(test_string);
-                                    lines.push(
-                                        words
-                                            .iter()
-                                            .cloned()
-                                            .skip(index)
-                                            .collect::<Vec<&str>>()
-                                            .join(" "),
-                                    );
-                                    test_string = "".to_owned();
-                                    break;
-                                }
-                            }
-                            if test_string != "" {
-                                wrapped_lines.push(test_string.clone().trim().to_owned());
-                            }
-                        }
+                    }
+                    if test_string != "" {
+                        wrapped_lines.push(test_string.clone().trim().to_owned());
                     }
                 }
-                wrapped_lines
             }
-            _ => lines,
         }
+        wrapped_lines
+    }
+    fn justify_paragraphs(&self, paragraphs: &[Vec<String>], input: &Text) -> Vec<f64> {
+        paragraphs
+            .iter()
+            .flat_map(|paragraph| {
+                paragraph.iter().enumerate().map(move |(index, line)| {
+                    let spaces = line.matches(' ').count();
+                    match input.max_width {
+                        Some(max_width)
+                            if input.align == Align::Justify
+                                && index + 1 < paragraph.len()
+                                && spaces > 0 =>
+                        {
+                            let width = self.measure_text_with_spacing(line, input.letter_spacing);
+                            ((max_width - width) / spaces as f64).max(0.)
+                        }
+                        _ => 0.,
+                    }
+                })
+            })
+            .collect()
     }
 }
 
@@ -770,6 +1133,9 @@ impl Frame for CanvasFrame {
         state.contents.push(object.clone());
         Box::new(object)
     }
+    fn get_viewport(&self) -> Rect {
+        self.state.read().unwrap().viewport
+    }
     fn set_viewport(&self, viewport: Rect) {
         let mut state = self.state.write().unwrap();
         state.viewport = viewport;
@@ -795,6 +1161,13 @@ impl Frame for CanvasFrame {
         self.draw();
         Box::new(state.canvas.clone())
     }
+    fn to_image_with_profile(
+        &self,
+        _profile: ColorProfile,
+    ) -> Result<Box<dyn ImageRepresentation>, Error> {
+        // Canvas pixel data is always exposed in sRGB.
+        Err(Error::unsupported())
+    }
     fn measure(&self, input: Rasterizable) -> Vector2 {
         match input {
             Rasterizable::Text(input) => {
@@ -823,6 +1196,25 @@ impl Frame for CanvasFrame {
             Rasterizable::Path(input) => input.bounds().size,
         }
     }
+    fn font_metrics(&self, input: Text) -> FontMetrics {
+        self.measure_font(&input)
+    }
+    fn set_color_profile(&self, _profile: ColorProfile) -> Result<(), Error> {
+        // Browsers color-manage canvas output against the display profile themselves.
+        Ok(())
+    }
+    fn set_rendering_intent(&self, _intent: RenderingIntent) -> Result<(), Error> {
+        Ok(())
+    }
+    fn set_soft_proof(&self, _proof: Option<SoftProof>) -> Result<(), Error> {
+        // Browsers offer no way to run canvas output through an arbitrary device profile.
+        Err(Error::unsupported())
+    }
+    fn set_color_cache_size(&self, _size: usize) {}
+    fn outline(&self, _input: Text) -> Option<Path> {
+        // The 2D canvas API does not expose glyph geometry.
+        None
+    }
     fn box_clone(&self) -> Box<dyn Frame> {
         Box::new(CanvasFrame {
             state: self.state.clone(),
@@ -842,7 +1234,30 @@ struct Canvas {
 struct CanvasState {
     root_frame: Option<Box<dyn Frame>>,
     size: ObserverCell<Vector2>,
-    tick_handlers: Vec<Box<dyn FnMut(f64) + Send + Sync>>,
+    ticker: TickDispatcher,
+    intercept_close: bool,
+    input_settings: web::input::Settings,
+    cursor: String,
+    cursor_visible: bool,
+}
+
+impl CanvasState {
+    fn viewport(&self) -> Rect {
+        match &self.root_frame {
+            Some(frame) => frame.get_viewport(),
+            None => Rect::new((0., 0.), web::input::body_size()),
+        }
+    }
+    fn apply_cursor(&self) {
+        let cursor = if self.cursor_visible {
+            self.cursor.as_str()
+        } else {
+            "none"
+        };
+        js! { @(no_return)
+            document.body.style.cursor = @{cursor};
+        }
+    }
 }
 
 impl Rasterizer for Canvas {
@@ -870,19 +1285,122 @@ impl Rasterizer for Canvas {
 
 impl Provider for Canvas {
     fn input(&self) -> Box<dyn Input> {
-        web::input::Input::new()
+        let (state, settings_state) = (self.state.clone(), self.state.clone());
+        web::input::Input::new(
+            move || state.read().unwrap().viewport(),
+            move || settings_state.read().unwrap().input_settings,
+        )
     }
 }
 
 impl Ticker for Canvas {
     fn bind(&mut self, handler: Box<dyn FnMut(f64) + 'static + Send + Sync>) {
-        self.state.write().unwrap().tick_handlers.push(handler);
+        self.state.write().unwrap().ticker.bind(handler);
+    }
+    fn bind_fixed(
+        &mut self,
+        step: f64,
+        update: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+        interpolate: Box<dyn FnMut(f64) + 'static + Send + Sync>,
+    ) {
+        self.state
+            .write()
+            .unwrap()
+            .ticker
+            .bind_fixed(step, update, interpolate);
+    }
+    fn frame_stats(&self) -> FrameStats {
+        self.state.read().unwrap().ticker.stats()
     }
 }
 
 impl CanvasContext for Canvas {}
 
+impl Window for Canvas {
+    fn id(&self) -> WindowId {
+        WindowId::MAIN
+    }
+    fn set_title(&self, title: &str) {
+        js! { @(no_return)
+            document.title = @{title};
+        }
+    }
+    // The size of the page is controlled by the browser, as documented on the trait.
+    fn set_size(&self, _size: Vector2) {}
+    fn set_fullscreen(&self, fullscreen: bool) {
+        // Browsers may refuse fullscreen requests made outside of a user gesture.
+        js! { @(no_return)
+            if (@{fullscreen}) {
+                if (document.fullscreenElement === null) {
+                    document.documentElement.requestFullscreen().catch(function() {});
+                }
+            } else if (document.fullscreenElement !== null) {
+                document.exitFullscreen();
+            }
+        }
+    }
+    // Only windows opened by script can be closed.
+    fn close(&self) {
+        js! { @(no_return)
+            window.close();
+        }
+    }
+    fn set_intercept_close(&self, intercept: bool) {
+        self.state.write().unwrap().intercept_close = intercept;
+    }
+    fn set_cursor(&self, cursor: Cursor) -> Result<(), Error> {
+        let mut state = self.state.write().unwrap();
+        state.cursor = match cursor {
+            Cursor::Icon(icon) => css_cursor(icon).to_owned(),
+            Cursor::Custom { image, hotspot } => css_cursor_image(&image, hotspot),
+        };
+        state.apply_cursor();
+        Ok(())
+    }
+    fn set_cursor_visible(&self, visible: bool) {
+        let mut state = self.state.write().unwrap();
+        state.cursor_visible = visible;
+        state.apply_cursor();
+    }
+    fn set_cursor_grab(&self, grab: CursorGrab) -> Result<(), Error> {
+        match grab {
+            CursorGrab::None => {
+                js! { @(no_return)
+                    if (document.pointerLockElement !== null) {
+                        document.exitPointerLock();
+                    }
+                }
+                Ok(())
+            }
+            // Pages cannot confine the cursor without locking it.
+            CursorGrab::Confine => Err(Error::unsupported()),
+            // Browsers may refuse pointer lock requests made outside of a user gesture, which is reported as a windowing event.
+            CursorGrab::Lock => {
+                js! { @(no_return)
+                    document.body.requestPointerLock();
+                }
+                Ok(())
+            }
+        }
+    }
+    fn set_ime_position(&self, position: Vector2) {
+        let viewport = self.state.read().unwrap().viewport();
+        web::input::text::set_position(mouse::from_viewport(
+            position,
+            web::input::body_size(),
+            viewport,
+        ));
+    }
+}
+
 impl ActiveCanvas for Canvas {
+    fn open_window(
+        &self,
+        _root: Box<dyn Frame>,
+        _config: WindowConfig,
+    ) -> Result<Box<dyn Window>, Error> {
+        Err(Error::unsupported())
+    }
     fn box_clone(&self) -> Box<dyn ActiveCanvas> {
         Box::new(self.clone())
     }
@@ -898,7 +1416,7 @@ impl InactiveCanvas for Canvas {
             state.root_frame.as_ref().unwrap().show();
             let cloned = self.clone();
             window().request_animation_frame(move |start_time| {
-                cloned.animate(start_time, start_time);
+                cloned.animate(start_time);
             });
         }
         (cb)(self);
@@ -914,9 +1432,24 @@ impl Clone for Canvas {
 }
 
 impl InteractiveCanvas for Canvas {
-    fn start(self: Box<Self>, root: Box<dyn Frame>) -> Box<dyn InactiveCanvas> {
+    fn start_with_config(
+        self: Box<Self>,
+        root: Box<dyn Frame>,
+        config: WindowConfig,
+    ) -> Box<dyn InactiveCanvas> {
+        if !config.title.is_empty() {
+            self.set_title(&config.title);
+        }
+        self.set_intercept_close(config.intercept_close);
+        if config.fullscreen {
+            self.set_fullscreen(true);
+        }
         {
             let mut state = self.state.write().unwrap();
+            state.input_settings = web::input::Settings {
+                scroll_line_height: config.scroll_line_height,
+                gamepad_dead_zone: config.gamepad_dead_zone,
+            };
             let size = state.size.get();
             let frame = root.as_any().downcast::<CanvasFrame>().unwrap();
             frame.set_root();
@@ -937,12 +1470,10 @@ impl VesselsCanvas for Canvas {
 }
 
 impl Canvas {
-    fn animate(&self, start_time: f64, last_start_time: f64) {
+    fn animate(&self, start_time: f64) {
+        // Animation frame timestamps come from the monotonic performance clock.
         let mut state = self.state.write().unwrap();
-        state
-            .tick_handlers
-            .iter_mut()
-            .for_each(|handler| (handler)(start_time - last_start_time));
+        state.ticker.tick(start_time);
         match &state.root_frame {
             Some(frame) => {
                 if state.size.is_dirty() {
@@ -956,7 +1487,7 @@ impl Canvas {
         }
         let cloned = self.clone();
         window().request_animation_frame(move |new_start_time| {
-            cloned.animate(new_start_time, start_time);
+            cloned.animate(new_start_time);
         });
     }
 }
@@ -1002,7 +1533,11 @@ canvas.root {
                 (body.offset_width().into(), body.offset_height().into()).into(),
             ),
             root_frame: None,
-            tick_handlers: vec![],
+            ticker: TickDispatcher::new(),
+            intercept_close: false,
+            input_settings: web::input::Settings::default(),
+            cursor: css_cursor(CursorIcon::Default).to_owned(),
+            cursor_visible: true,
         })),
     };
 
@@ -1016,5 +1551,19 @@ canvas.root {
             .set((body.offset_width().into(), body.offset_height().into()).into());
     });
 
+    let gfx_unload = gfx.clone();
+    let intercept_close = move || gfx_unload.state.read().unwrap().intercept_close;
+
+    // Browsers show their own confirmation prompt when unloading is prevented.
+    js! { @(no_return)
+        let intercept_close = @{intercept_close};
+        window.addEventListener("beforeunload", function(event) {
+            if (intercept_close()) {
+                event.preventDefault();
+                event.returnValue = "";
+            }
+        });
+    }
+
     Box::new(gfx)
 }
@@ -0,0 +1,198 @@
+use super::main_window;
+use crate::errors::Error;
+use crate::input::{
+    gamepad::{Axis, Button, Event as GamepadEvent, GamepadId, Rumble, Tracker},
+    Event, Kind,
+};
+use crossbeam_channel::Sender;
+use futures::task::AtomicTask;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
+
+use stdweb::unstable::TryInto;
+use stdweb::web::{Gamepad, GamepadMappingType};
+use stdweb::Value;
+
+const POLL_INTERVAL: u32 = 16;
+
+struct Listener {
+    sender: Sender<Event>,
+    task: Arc<AtomicTask>,
+    // Released once every handle to the input stream receiving the events has been dropped.
+    input: Weak<()>,
+    dead_zone: Box<dyn Fn() -> f64>,
+    trackers: HashMap<i32, Tracker>,
+}
+
+#[derive(Default)]
+struct Poller {
+    listeners: Vec<Listener>,
+    interval: Option<Value>,
+}
+
+thread_local! {
+    static POLLER: RefCell<Poller> = RefCell::new(Poller::default());
+}
+
+const STANDARD_BUTTONS: [Button; 17] = [
+    Button::South,
+    Button::East,
+    Button::West,
+    Button::North,
+    Button::LeftBumper,
+    Button::RightBumper,
+    Button::LeftTrigger,
+    Button::RightTrigger,
+    Button::Select,
+    Button::Start,
+    Button::LeftStick,
+    Button::RightStick,
+    Button::DPadUp,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::DPadRight,
+    Button::Mode,
+];
+
+const STANDARD_AXES: [Axis; 4] = [
+    Axis::LeftStickX,
+    Axis::LeftStickY,
+    Axis::RightStickX,
+    Axis::RightStickY,
+];
+
+fn poll_gamepads(
+    gamepads: &[Gamepad],
+    trackers: &mut HashMap<i32, Tracker>,
+    dead_zone: f64,
+) -> Vec<GamepadEvent> {
+    let mut events = vec![];
+    trackers.retain(|index, _| {
+        let connected = gamepads.iter().any(|gamepad| gamepad.index() == *index);
+        if !connected {
+            events.push(GamepadEvent::Disconnect(GamepadId(*index as u64)));
+        }
+        connected
+    });
+    for gamepad in gamepads {
+        let id = GamepadId(gamepad.index() as u64);
+        let tracker = trackers.entry(gamepad.index()).or_insert_with(|| {
+            events.push(GamepadEvent::Connect {
+                gamepad: id,
+                name: gamepad.id(),
+            });
+            Tracker::new(id, dead_zone)
+        });
+        let standard = gamepad.mapping() == GamepadMappingType::Standard;
+        for (index, button) in gamepad.buttons().iter().enumerate() {
+            let mapped = match STANDARD_BUTTONS.get(index) {
+                Some(button) if standard => *button,
+                _ => Button::Other(index as u16),
+            };
+            // The standard layout reports analog triggers as buttons with a value.
+            match mapped {
+                Button::LeftTrigger => {
+                    events.extend(tracker.axis(Axis::LeftTrigger, button.value()))
+                }
+                Button::RightTrigger => {
+                    events.extend(tracker.axis(Axis::RightTrigger, button.value()))
+                }
+                _ => {}
+            }
+            events.extend(tracker.button(mapped, button.pressed()));
+        }
+        for (index, value) in gamepad.axes().into_iter().enumerate() {
+            let mapped = match STANDARD_AXES.get(index) {
+                Some(axis) if standard => *axis,
+                _ => Axis::Other(index as u16),
+            };
+            events.extend(tracker.axis(mapped, value));
+        }
+    }
+    events
+}
+
+fn poll() {
+    POLLER.with(|poller| {
+        let mut poller = poller.borrow_mut();
+        poller
+            .listeners
+            .retain(|listener| listener.input.upgrade().is_some());
+        if poller.listeners.is_empty() {
+            if let Some(interval) = poller.interval.take() {
+                js! { @(no_return)
+                    clearInterval(@{interval});
+                }
+            }
+            return;
+        }
+        let gamepads: Vec<Gamepad> = Gamepad::get_all()
+            .into_iter()
+            .flatten()
+            .filter(Gamepad::connected)
+            .collect();
+        for listener in &mut poller.listeners {
+            let events = poll_gamepads(&gamepads, &mut listener.trackers, (listener.dead_zone)());
+            if !events.is_empty() {
+                for event in events {
+                    listener.sender.send(main_window(Kind::Gamepad(event)));
+                }
+                listener.task.notify();
+            }
+        }
+    });
+}
+
+/// Reports gamepad input until the provided input handle is dropped. A single timer polls connected gamepads for every
+/// listener, as the Gamepad API does not report changes in button and axis state through events.
+pub(crate) fn listen<F>(
+    sender: Sender<Event>,
+    task: Arc<AtomicTask>,
+    input: Weak<()>,
+    dead_zone: F,
+) where
+    F: Fn() -> f64 + 'static,
+{
+    POLLER.with(|poller| {
+        let mut poller = poller.borrow_mut();
+        poller.listeners.push(Listener {
+            sender,
+            task,
+            input,
+            dead_zone: Box::new(dead_zone),
+            trackers: HashMap::new(),
+        });
+        if poller.interval.is_none() {
+            poller.interval = Some(js!(
+                var poll = @{poll};
+                return setInterval(function() { poll(); }, @{POLL_INTERVAL});
+            ));
+        }
+    });
+}
+
+pub(crate) fn rumble(gamepad: GamepadId, effect: Rumble) -> Result<(), Error> {
+    let supported: bool = js!(
+        var gamepad = navigator.getGamepads()[@{gamepad.0 as u32}];
+        if (!gamepad || !gamepad.vibrationActuator) {
+            return false;
+        }
+        var clamp = function(magnitude) { return Math.min(Math.max(magnitude, 0), 1); };
+        gamepad.vibrationActuator.playEffect("dual-rumble", {
+            duration: @{effect.duration},
+            strongMagnitude: clamp(@{effect.strong}),
+            weakMagnitude: clamp(@{effect.weak})
+        });
+        return true;
+    )
+    .try_into()
+    .unwrap();
+    if supported {
+        Ok(())
+    } else {
+        Err(Error::unsupported())
+    }
+}
@@ -1,29 +1,191 @@
+use crate::graphics::{
+    canvas::{CursorGrab, WindowConfig},
+    Rect, Vector2,
+};
 use crate::input::{
-    keyboard::{self as keyboard_mod, Event as KeyboardEvent},
+    keyboard::{self as keyboard_mod, Event as KeyboardEvent, Modifiers},
     mouse::{self, Event as MouseEvent},
-    windowing::Event as WindowingEvent,
-    Event, Input as IInput,
+    pointer::{self, Device, Event as PointerEvent, Pen, Phase},
+    windowing::{Event as WindowingEvent, WindowId},
+    Event, Input as IInput, Kind,
+};
+use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
+use futures::{
+    task::{AtomicTask, Task},
+    Async, Poll, Stream,
 };
-use crossbeam_channel::{unbounded, Receiver, TryRecvError};
-use futures::{task::AtomicTask, Async, Poll, Stream};
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 
-use stdweb::traits::{IEvent, IEventTarget, IKeyboardEvent};
+use stdweb::traits::{IEvent, IEventTarget, IHtmlElement, IKeyboardEvent};
+use stdweb::unstable::TryInto;
 use stdweb::web::{
     document,
     event::{
-        IMouseEvent, KeyDownEvent, KeyUpEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
-        MouseUpEvent, MouseWheelEvent, ResizeEvent,
+        BlurEvent, ConcreteEvent, FocusEvent, IMouseEvent, IPointerEvent, ITouchEvent,
+        KeyDownEvent, KeyUpEvent, ModifierKey, MouseButton, MouseDownEvent, MouseEnterEvent,
+        MouseLeaveEvent, MouseMoveEvent, MouseUpEvent, MouseWheelDeltaMode, MouseWheelEvent,
+        PointerCancelEvent, PointerDownEvent, PointerMoveEvent, PointerUpEvent, ResizeEvent,
+        TouchCancel, TouchEnd, TouchMove, TouchStart,
     },
     window,
 };
+use stdweb::Once;
 
+pub(crate) mod gamepad;
 mod keyboard;
+pub(crate) mod text;
+
+fn main_window(kind: Kind) -> Event {
+    Event {
+        window: WindowId::MAIN,
+        time: now(),
+        kind,
+    }
+}
+
+pub(crate) fn now() -> f64 {
+    js!(return performance.now();).try_into().unwrap()
+}
+
+/// Notifies the provided task once the provided number of milliseconds has elapsed.
+pub(crate) fn wake_after(delay: f64, task: Task) {
+    let wake = Once(move || task.notify());
+    js! { @(no_return)
+        setTimeout(@{wake}, @{delay});
+    }
+}
+
+fn printable(key: &str) -> Option<char> {
+    let mut characters = key.chars();
+    match (characters.next(), characters.next()) {
+        (Some(character), None) => Some(character),
+        _ => None,
+    }
+}
+
+fn modifiers(event: &impl IKeyboardEvent) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    modifiers.set(Modifiers::SHIFT, event.shift_key());
+    modifiers.set(Modifiers::CONTROL, event.ctrl_key());
+    modifiers.set(Modifiers::ALT, event.alt_key());
+    modifiers.set(Modifiers::META, event.meta_key());
+    modifiers.set(
+        Modifiers::CAPS_LOCK,
+        event.get_modifier_state(ModifierKey::CapsLock),
+    );
+    modifiers.set(
+        Modifiers::NUM_LOCK,
+        event.get_modifier_state(ModifierKey::NumLock),
+    );
+    modifiers
+}
+
+pub(crate) fn body_size() -> Vector2 {
+    let body = document().body().unwrap();
+    (body.offset_width().into(), body.offset_height().into()).into()
+}
+
+fn listen_pointer<E, F>(
+    target: &impl IEventTarget,
+    sender: &Sender<Event>,
+    task: &Arc<AtomicTask>,
+    viewport: &Rc<F>,
+    phase: Phase,
+) where
+    E: IPointerEvent + ConcreteEvent,
+    F: Fn() -> Rect + 'static,
+{
+    let (sender, task, viewport) = (sender.clone(), task.clone(), viewport.clone());
+    target.add_event_listener(move |event: E| {
+        let (phase, device) = match event.pointer_type().as_str() {
+            "touch" => (phase, Device::Touch),
+            "pen" => {
+                let buttons: u32 = js!(return @{event.as_ref()}.buttons;).try_into().unwrap();
+                // The barrel and eraser buttons of a pen are reported as the second and sixth buttons.
+                (
+                    pointer::pen_phase(phase, event.pressure(), buttons),
+                    Device::Pen(Pen {
+                        tilt: (f64::from(event.tilt_x()), f64::from(event.tilt_y())).into(),
+                        eraser: buttons & 32 != 0,
+                        barrel_button: buttons & 2 != 0,
+                    }),
+                )
+            }
+            // Mouse pointers are reported through mouse events.
+            _ => return,
+        };
+        event.prevent_default();
+        sender.send(main_window(Kind::Pointer(PointerEvent {
+            id: event.pointer_id() as u64,
+            phase,
+            device,
+            position: mouse::to_viewport(
+                (f64::from(event.client_x()), f64::from(event.client_y())).into(),
+                body_size(),
+                viewport(),
+            ),
+            pressure: Some(event.pressure()),
+            contact_size: Some((event.width(), event.height()).into()),
+        })));
+        task.notify();
+    });
+}
+
+fn listen_touch<E, F>(
+    target: &impl IEventTarget,
+    sender: &Sender<Event>,
+    task: &Arc<AtomicTask>,
+    viewport: &Rc<F>,
+    phase: Phase,
+) where
+    E: ITouchEvent + ConcreteEvent,
+    F: Fn() -> Rect + 'static,
+{
+    let (sender, task, viewport) = (sender.clone(), task.clone(), viewport.clone());
+    target.add_event_listener(move |event: E| {
+        event.prevent_default();
+        let (size, viewport) = (body_size(), viewport());
+        for touch in event.changed_touches() {
+            sender.send(main_window(Kind::Pointer(PointerEvent {
+                id: touch.identifier() as u64,
+                phase,
+                device: Device::Touch,
+                position: mouse::to_viewport(
+                    (touch.client_x(), touch.client_y()).into(),
+                    size,
+                    viewport,
+                ),
+                pressure: Some(touch.force()),
+                contact_size: Some((touch.radius_x() * 2., touch.radius_y() * 2.).into()),
+            })));
+        }
+        task.notify();
+    });
+}
+
+/// Settings from the window configuration that affect input handling.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Settings {
+    pub(crate) scroll_line_height: f64,
+    pub(crate) gamepad_dead_zone: f64,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        let config = WindowConfig::default();
+        Settings {
+            scroll_line_height: config.scroll_line_height,
+            gamepad_dead_zone: config.gamepad_dead_zone,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct Input {
     receiver: Receiver<Event>,
     task: Arc<AtomicTask>,
+    // Shared listeners stop reporting to the stream once every handle has been dropped.
+    _handle: Arc<()>,
 }
 
 impl Stream for Input {
@@ -51,68 +213,186 @@ impl IInput for Input {
 }
 
 impl Input {
-    pub(crate) fn new() -> Box<dyn IInput> {
+    /// Creates an input stream for the page. Pointer positions are mapped into the provided viewport and the provided settings are consulted as events arrive.
+    pub(crate) fn new<F, G>(viewport: F, settings: G) -> Box<dyn IInput>
+    where
+        F: Fn() -> Rect + 'static,
+        G: Fn() -> Settings + 'static,
+    {
+        let (viewport, settings) = (Rc::new(viewport), Rc::new(settings));
         let (sender, receiver) = unbounded();
         let task = Arc::new(AtomicTask::new());
         let (resize_sender, resize_task) = (sender.clone(), task.clone());
         window().add_event_listener(move |_: ResizeEvent| {
-            resize_sender.send(Event::Windowing(WindowingEvent::Resize));
+            let logical = body_size();
+            resize_sender.send(main_window(Kind::Windowing(WindowingEvent::Resize {
+                logical,
+                physical: logical * window().device_pixel_ratio(),
+            })));
             resize_task.notify();
         });
+        let (focus_sender, focus_task) = (sender.clone(), task.clone());
+        window().add_event_listener(move |_: FocusEvent| {
+            focus_sender.send(main_window(Kind::Windowing(WindowingEvent::Focus)));
+            focus_task.notify();
+        });
+        let (blur_sender, blur_task) = (sender.clone(), task.clone());
+        window().add_event_listener(move |_: BlurEvent| {
+            blur_sender.send(main_window(Kind::Windowing(WindowingEvent::Blur)));
+            blur_task.notify();
+        });
+        let (visibility_sender, visibility_task) = (sender.clone(), task.clone());
+        let visibility_change = move |hidden: bool| {
+            visibility_sender.send(main_window(Kind::Windowing(WindowingEvent::Visibility(
+                !hidden,
+            ))));
+            visibility_task.notify();
+        };
+        js! { @(no_return)
+            let callback = @{visibility_change};
+            document.addEventListener("visibilitychange", function() {
+                callback(document.hidden);
+            });
+        }
+        let (pointer_lock_sender, pointer_lock_task) = (sender.clone(), task.clone());
+        let pointer_lock_change = move |locked: bool| {
+            pointer_lock_sender.send(main_window(Kind::Windowing(WindowingEvent::CursorGrab(
+                if locked {
+                    CursorGrab::Lock
+                } else {
+                    CursorGrab::None
+                },
+            ))));
+            pointer_lock_task.notify();
+        };
+        js! { @(no_return)
+            let callback = @{pointer_lock_change};
+            document.addEventListener("pointerlockchange", function() {
+                callback(document.pointerLockElement !== null);
+            });
+            // Refused requests leave the cursor free.
+            document.addEventListener("pointerlockerror", function() {
+                callback(false);
+            });
+        }
+        let (unload_sender, unload_task) = (sender.clone(), task.clone());
+        let before_unload = move || {
+            unload_sender.send(main_window(Kind::Windowing(WindowingEvent::CloseRequested)));
+            unload_task.notify();
+        };
+        js! { @(no_return)
+            let callback = @{before_unload};
+            window.addEventListener("beforeunload", function() {
+                callback();
+            });
+        }
         let body = document().body().unwrap();
         let (mouse_up_sender, mouse_up_task) = (sender.clone(), task.clone());
         body.add_event_listener(move |event: MouseUpEvent| {
             event.prevent_default();
-            mouse_up_sender.send(Event::Mouse(MouseEvent::Up(match event.button() {
-                MouseButton::Left => mouse::Button::Left,
-                MouseButton::Right => mouse::Button::Right,
-                MouseButton::Wheel => mouse::Button::Middle,
-                MouseButton::Button4 => mouse::Button::Auxiliary(0),
-                MouseButton::Button5 => mouse::Button::Auxiliary(1),
-            })));
+            mouse_up_sender.send(main_window(Kind::Mouse(MouseEvent::Up(
+                match event.button() {
+                    MouseButton::Left => mouse::Button::Left,
+                    MouseButton::Right => mouse::Button::Right,
+                    MouseButton::Wheel => mouse::Button::Middle,
+                    MouseButton::Button4 => mouse::Button::Auxiliary(0),
+                    MouseButton::Button5 => mouse::Button::Auxiliary(1),
+                },
+            ))));
             mouse_up_task.notify();
         });
         let (mouse_down_sender, mouse_down_task) = (sender.clone(), task.clone());
         body.add_event_listener(move |event: MouseDownEvent| {
             event.prevent_default();
-            mouse_down_sender.send(Event::Mouse(MouseEvent::Down(match event.button() {
-                MouseButton::Left => mouse::Button::Left,
-                MouseButton::Right => mouse::Button::Right,
-                MouseButton::Wheel => mouse::Button::Middle,
-                MouseButton::Button4 => mouse::Button::Auxiliary(0),
-                MouseButton::Button5 => mouse::Button::Auxiliary(1),
-            })));
+            mouse_down_sender.send(main_window(Kind::Mouse(MouseEvent::Down(
+                match event.button() {
+                    MouseButton::Left => mouse::Button::Left,
+                    MouseButton::Right => mouse::Button::Right,
+                    MouseButton::Wheel => mouse::Button::Middle,
+                    MouseButton::Button4 => mouse::Button::Auxiliary(0),
+                    MouseButton::Button5 => mouse::Button::Auxiliary(1),
+                },
+            ))));
             mouse_down_task.notify();
         });
-        let (mouse_move_sender, mouse_move_task) = (sender.clone(), task.clone());
+        let (mouse_move_sender, mouse_move_task, mouse_move_viewport) =
+            (sender.clone(), task.clone(), viewport.clone());
         body.add_event_listener(move |event: MouseMoveEvent| {
             event.prevent_default();
-
-            mouse_move_sender.send(Event::Mouse(MouseEvent::Move(
+            mouse_move_sender.send(main_window(Kind::Mouse(MouseEvent::Move(
+                mouse::to_viewport(
+                    (f64::from(event.client_x()), f64::from(event.client_y())).into(),
+                    body_size(),
+                    mouse_move_viewport(),
+                ),
+            ))));
+            mouse_move_sender.send(main_window(Kind::Mouse(MouseEvent::Motion(
                 (f64::from(event.movement_x()), f64::from(event.movement_y())).into(),
-            )));
+            ))));
             mouse_move_task.notify();
         });
-        let (mouse_wheel_sender, mouse_wheel_task) = (sender.clone(), task.clone());
+        let (mouse_enter_sender, mouse_enter_task) = (sender.clone(), task.clone());
+        document()
+            .document_element()
+            .unwrap()
+            .add_event_listener(move |_: MouseEnterEvent| {
+                mouse_enter_sender.send(main_window(Kind::Mouse(MouseEvent::Enter)));
+                mouse_enter_task.notify();
+            });
+        let (mouse_leave_sender, mouse_leave_task) = (sender.clone(), task.clone());
+        document()
+            .document_element()
+            .unwrap()
+            .add_event_listener(move |_: MouseLeaveEvent| {
+                mouse_leave_sender.send(main_window(Kind::Mouse(MouseEvent::Leave)));
+                mouse_leave_task.notify();
+            });
+        let (mouse_wheel_sender, mouse_wheel_task, mouse_wheel_settings) =
+            (sender.clone(), task.clone(), settings.clone());
         body.add_event_listener(move |event: MouseWheelEvent| {
-            mouse_wheel_sender.send(Event::Mouse(MouseEvent::Scroll(
-                (event.delta_x(), event.delta_y()).into(),
-            )));
+            mouse_wheel_sender.send(main_window(Kind::Mouse(if event.ctrl_key() {
+                // Browsers report trackpad pinches as wheel events with the control key held, scaling by e every 100 pixels.
+                MouseEvent::Magnify((-event.delta_y() / 100.).exp())
+            } else {
+                MouseEvent::Scroll(mouse::Scroll::new(
+                    (event.delta_x(), event.delta_y()).into(),
+                    match event.delta_mode() {
+                        MouseWheelDeltaMode::Pixel => mouse::ScrollUnit::Pixel,
+                        MouseWheelDeltaMode::Line => mouse::ScrollUnit::Line,
+                        MouseWheelDeltaMode::Page => mouse::ScrollUnit::Page,
+                    },
+                    mouse_wheel_settings().scroll_line_height,
+                    body_size(),
+                    None,
+                ))
+            })));
             mouse_wheel_task.notify();
         });
+        // Pinches would otherwise also zoom the page. Listeners on the body are passive by default in some browsers,
+        // so this one is registered explicitly.
+        js! { @(no_return)
+            document.body.addEventListener("wheel", function(event) {
+                if (event.ctrlKey) {
+                    event.preventDefault();
+                }
+            }, { passive: false });
+        }
         let (key_down_sender, key_down_task) = (sender.clone(), task.clone());
         body.add_event_listener(move |e: KeyDownEvent| {
-            e.prevent_default();
             let key = e.key();
+            let printable = printable(&key);
+            // Keys that produce text or are handled by an input method must reach the text input element.
+            let text = printable.is_some() && !e.ctrl_key() && !e.meta_key();
+            if !(text || e.is_composing() || key == "Process" || key == "Dead") {
+                e.prevent_default();
+            }
             let k = keyboard::parse_code(e.code().as_str());
-            key_down_sender.send(Event::Keyboard(KeyboardEvent {
+            key_down_sender.send(main_window(Kind::Keyboard(KeyboardEvent {
                 action: keyboard_mod::Action::Down(k),
-                printable: if key.len() == 1 {
-                    Some(key.chars().take(1).collect::<Vec<char>>()[0])
-                } else {
-                    None
-                },
-            }));
+                printable,
+                modifiers: modifiers(&e),
+                is_repeat: e.repeat(),
+            })));
             key_down_task.notify();
         });
         let (key_up_sender, key_up_task) = (sender.clone(), task.clone());
@@ -120,16 +400,44 @@ impl Input {
             e.prevent_default();
             let key = e.key();
             let k = keyboard::parse_code(e.code().as_str());
-            key_up_sender.send(Event::Keyboard(KeyboardEvent {
+            key_up_sender.send(main_window(Kind::Keyboard(KeyboardEvent {
                 action: keyboard_mod::Action::Up(k),
-                printable: if key.len() == 1 {
-                    Some(key.chars().take(1).collect::<Vec<char>>()[0])
-                } else {
-                    None
-                },
-            }));
+                printable: printable(&key),
+                modifiers: modifiers(&e),
+                is_repeat: false,
+            })));
             key_up_task.notify();
         });
-        Box::new(Input { receiver, task })
+        let pointer_events: bool = js!(return typeof PointerEvent !== "undefined";)
+            .try_into()
+            .unwrap();
+        // Browsers with pointer events also fire touch events for the same contacts.
+        if pointer_events {
+            listen_pointer::<PointerDownEvent, _>(&body, &sender, &task, &viewport, Phase::Begin);
+            listen_pointer::<PointerMoveEvent, _>(&body, &sender, &task, &viewport, Phase::Move);
+            listen_pointer::<PointerUpEvent, _>(&body, &sender, &task, &viewport, Phase::End);
+            listen_pointer::<PointerCancelEvent, _>(
+                &body,
+                &sender,
+                &task,
+                &viewport,
+                Phase::Cancel,
+            );
+        } else {
+            listen_touch::<TouchStart, _>(&body, &sender, &task, &viewport, Phase::Begin);
+            listen_touch::<TouchMove, _>(&body, &sender, &task, &viewport, Phase::Move);
+            listen_touch::<TouchEnd, _>(&body, &sender, &task, &viewport, Phase::End);
+            listen_touch::<TouchCancel, _>(&body, &sender, &task, &viewport, Phase::Cancel);
+        }
+        let handle = Arc::new(());
+        text::listen(sender.clone(), task.clone(), Arc::downgrade(&handle));
+        gamepad::listen(sender, task.clone(), Arc::downgrade(&handle), move || {
+            settings().gamepad_dead_zone
+        });
+        Box::new(Input {
+            receiver,
+            task,
+            _handle: handle,
+        })
     }
 }
@@ -0,0 +1,147 @@
+use super::main_window;
+use crate::graphics::Vector2;
+use crate::input::{
+    text::{utf16_to_byte_offset, Event as TextEvent},
+    Event, Kind,
+};
+use crossbeam_channel::Sender;
+use futures::task::AtomicTask;
+use std::{
+    cell::RefCell,
+    sync::{Arc, Weak},
+};
+
+use stdweb::Value;
+
+const ELEMENT_ID: &str = "vessels-text-input";
+
+struct Listener {
+    sender: Sender<Event>,
+    task: Arc<AtomicTask>,
+    // Fails to upgrade once the input stream is no longer held anywhere.
+    input: Weak<()>,
+}
+
+#[derive(Default)]
+struct Listeners {
+    listeners: Vec<Listener>,
+    registered: bool,
+}
+
+thread_local! {
+    static LISTENERS: RefCell<Listeners> = RefCell::new(Listeners::default());
+}
+
+fn send(event: TextEvent) {
+    LISTENERS.with(|listeners| {
+        let mut listeners = listeners.borrow_mut();
+        listeners
+            .listeners
+            .retain(|listener| listener.input.upgrade().is_some());
+        for listener in &listeners.listeners {
+            listener.sender.send(main_window(Kind::Text(event.clone())));
+            listener.task.notify();
+        }
+    });
+}
+
+// Browsers only deliver text and compositions to editable elements, so a transparent text area holds the focus.
+fn element() -> Value {
+    js!(
+        var element = document.getElementById(@{ELEMENT_ID});
+        if (element === null) {
+            element = document.createElement("textarea");
+            element.id = @{ELEMENT_ID};
+            element.setAttribute("autocomplete", "off");
+            element.setAttribute("autocapitalize", "off");
+            element.setAttribute("spellcheck", "false");
+            element.style.cssText = "position: fixed; left: 0; top: 0; width: 1px; height: 1px; padding: 0; border: 0; margin: 0; opacity: 0; resize: none; overflow: hidden; pointer-events: none;";
+            document.body.appendChild(element);
+            element.focus({ preventScroll: true });
+        }
+        return element;
+    )
+}
+
+/// Reports text entered into the page, including the progress of input method compositions, until the provided
+/// input handle is dropped. The text area is shared, so its listeners are added once and fan out to every input.
+pub(crate) fn listen(sender: Sender<Event>, task: Arc<AtomicTask>, input: Weak<()>) {
+    let registered = LISTENERS.with(|listeners| {
+        let mut listeners = listeners.borrow_mut();
+        listeners.listeners.push(Listener {
+            sender,
+            task,
+            input,
+        });
+        std::mem::replace(&mut listeners.registered, true)
+    });
+    if registered {
+        return;
+    }
+    let commit = |text: String| send(TextEvent::Commit(text));
+    let composition = |start: bool| {
+        send(if start {
+            TextEvent::CompositionStart
+        } else {
+            TextEvent::CompositionEnd
+        })
+    };
+    // Selection offsets are reported in UTF-16 code units.
+    let preedit = |text: String, start: u32, end: u32| {
+        send(TextEvent::Preedit {
+            cursor: Some((
+                utf16_to_byte_offset(&text, start as usize),
+                utf16_to_byte_offset(&text, end as usize),
+            )),
+            text,
+        })
+    };
+    js! { @(no_return)
+        var element = @{element()};
+        var commit = @{commit};
+        var composition = @{composition};
+        var preedit = @{preedit};
+        var composing = false;
+        var flush = function() {
+            if (element.value !== "") {
+                commit(element.value);
+                element.value = "";
+            }
+        };
+        element.addEventListener("compositionstart", function() {
+            flush();
+            composing = true;
+            composition(true);
+        });
+        element.addEventListener("compositionend", function() {
+            composing = false;
+            composition(false);
+            flush();
+        });
+        // Only insertions are expected, as the element is emptied whenever text is committed.
+        element.addEventListener("beforeinput", function(event) {
+            if (!composing && event.inputType.indexOf("insert") !== 0) {
+                event.preventDefault();
+            }
+        });
+        element.addEventListener("input", function() {
+            if (composing) {
+                preedit(element.value, element.selectionStart, element.selectionEnd);
+            } else {
+                flush();
+            }
+        });
+        document.body.addEventListener("mousedown", function() {
+            element.focus({ preventScroll: true });
+        });
+    }
+}
+
+/// Moves the text input element, and with it the candidate window of input methods, to the provided position in page coordinates.
+pub(crate) fn set_position(position: Vector2) {
+    js! { @(no_return)
+        var element = @{element()};
+        element.style.left = @{position.x} + "px";
+        element.style.top = @{position.y} + "px";
+    }
+}
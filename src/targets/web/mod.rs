@@ -1,3 +1,4 @@
+pub(crate) mod clipboard;
 pub(crate) mod executor;
 pub(crate) mod graphics;
 pub(crate) mod input;